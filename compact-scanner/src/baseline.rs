@@ -0,0 +1,199 @@
+//! Baseline of a scan.
+//!
+//! A baseline is a JSON report previously emitted by the scanner, grouped by detector or by
+//! circuit. Findings already present in the baseline are filtered out of a new scan, so only new
+//! findings are reported. Edits shift the offsets of unchanged findings, so a finding matches a
+//! baseline finding of the same detector and file when their lines are at most `LINE_FUZZ` lines
+//! apart. Baseline findings without a line are matched on their start offset. Every baseline
+//! finding matches at most one finding.
+use std::{collections::HashMap, path::PathBuf};
+
+use compact_security_detectors_sdk::detector::DetectorResult;
+
+use crate::report::relative_file_path;
+
+/// Maximum distance, in lines, between a finding and the baseline finding it matches.
+pub(crate) const LINE_FUZZ: usize = 3;
+
+/// A finding of a baseline report.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct BaselineFinding {
+    pub(crate) detector: String,
+    pub(crate) path: String,
+    pub(crate) line: Option<usize>,
+    pub(crate) offset_start: u32,
+}
+
+/// Reads the findings of a scan report, grouped either by detector or by circuit.
+pub(crate) fn baseline_findings(report: &serde_json::Value) -> Vec<BaselineFinding> {
+    let mut findings = Vec::new();
+    if let Some(responses) = report["detector_responses"].as_object() {
+        for (detector, response) in responses {
+            let groups = response["findings"].as_array().into_iter().flatten();
+            for instance in
+                groups.flat_map(|group| group["instances"].as_array().into_iter().flatten())
+            {
+                findings.extend(baseline_finding(detector, instance));
+            }
+        }
+    }
+    if let Some(circuits) = report["circuit_responses"].as_object() {
+        for detectors in circuits.values().filter_map(serde_json::Value::as_object) {
+            for (detector, instances) in detectors {
+                for instance in instances.as_array().into_iter().flatten() {
+                    findings.extend(baseline_finding(detector, instance));
+                }
+            }
+        }
+    }
+    findings
+}
+
+fn baseline_finding(detector: &str, instance: &serde_json::Value) -> Option<BaselineFinding> {
+    Some(BaselineFinding {
+        detector: detector.to_string(),
+        path: instance["path"].as_str()?.to_string(),
+        line: instance["line"]
+            .as_u64()
+            .and_then(|line| usize::try_from(line).ok()),
+        offset_start: u32::try_from(instance["offset_start"].as_u64()?).ok()?,
+    })
+}
+
+/// Drops the results matching a baseline finding.
+///
+/// `line_of` returns the 1-based line of a result, and result paths are made relative to
+/// `project_root` before being compared with the baseline paths. Detectors left without results
+/// are removed.
+pub(crate) fn filter_against_baseline(
+    results: HashMap<String, Vec<DetectorResult>>,
+    baseline: &[BaselineFinding],
+    project_root: Option<&PathBuf>,
+    line_of: impl Fn(&DetectorResult) -> Option<usize>,
+) -> HashMap<String, Vec<DetectorResult>> {
+    let mut used = vec![false; baseline.len()];
+    results
+        .into_iter()
+        .filter_map(|(detector, mut detector_results)| {
+            detector_results.sort_by_key(|result| (result.file_path.clone(), result.offset_start));
+            let kept: Vec<DetectorResult> = detector_results
+                .into_iter()
+                .filter(|result| {
+                    let path = relative_file_path(&result.file_path, project_root);
+                    let line = line_of(result);
+                    let matched = baseline
+                        .iter()
+                        .enumerate()
+                        .filter(|(index, finding)| {
+                            !used[*index] && finding.detector == detector && finding.path == path
+                        })
+                        .filter_map(|(index, finding)| match (finding.line, line) {
+                            (Some(baseline_line), Some(line)) => {
+                                let distance = baseline_line.abs_diff(line);
+                                (distance <= LINE_FUZZ).then_some((distance, index))
+                            }
+                            (None, _) => {
+                                (finding.offset_start == result.offset_start).then_some((0, index))
+                            }
+                            (Some(_), None) => None,
+                        })
+                        .min();
+                    if let Some((_, index)) = matched {
+                        used[index] = true;
+                    }
+                    matched.is_none()
+                })
+                .collect();
+            (!kept.is_empty()).then_some((detector, kept))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use compact_security_detectors_sdk::source_map::SourceMap;
+    use serde_json::json;
+
+    const DETECTOR: &str = "assertion-error-message-verbose";
+
+    fn result(source: &str, needle: &str) -> DetectorResult {
+        let offset_start = u32::try_from(source.find(needle).unwrap()).unwrap();
+        DetectorResult {
+            file_path: "/project/test.compact".to_string(),
+            offset_start,
+            offset_end: offset_start + u32::try_from(needle.len()).unwrap(),
+            extra: None,
+        }
+    }
+
+    fn filter(source: &str, results: Vec<DetectorResult>, baseline: &serde_json::Value) -> usize {
+        let map = SourceMap::new(source);
+        let filtered = filter_against_baseline(
+            HashMap::from([(DETECTOR.to_string(), results)]),
+            &baseline_findings(baseline),
+            Some(&PathBuf::from("/project")),
+            |result| {
+                map.line_col(source, result.offset_start as usize)
+                    .map(|(line, _)| line)
+            },
+        );
+        filtered.values().map(Vec::len).sum()
+    }
+
+    fn baseline(line: usize, offset_start: usize) -> serde_json::Value {
+        json!({
+            "detector_responses": {
+                DETECTOR: {
+                    "findings": [{
+                        "instances": [
+                            {"path": "test.compact", "line": line, "offset_start": offset_start}
+                        ]
+                    }]
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_baseline_exact_match() {
+        let source = "circuit a(): [] {\n  assert x \"\";\n}\n";
+        let results = vec![result(source, "assert x")];
+        let offset = source.find("assert x").unwrap();
+        assert_eq!(filter(source, results, &baseline(2, offset)), 0);
+    }
+
+    #[test]
+    fn test_baseline_shifted_line_match() {
+        let source = "// header\n// added\ncircuit a(): [] {\n  assert x \"\";\n}\n";
+        let results = vec![result(source, "assert x")];
+        assert_eq!(filter(source, results, &baseline(2, 20)), 0);
+    }
+
+    #[test]
+    fn test_baseline_new_finding_survives() {
+        let source = "circuit a(): [] {\n  assert x \"\";\n}\n\n\n\n\n\ncircuit b(): [] {\n  assert y \"\";\n}\n";
+        let results = vec![result(source, "assert x"), result(source, "assert y")];
+        let offset = source.find("assert x").unwrap();
+        assert_eq!(filter(source, results.clone(), &baseline(2, offset)), 1);
+        assert_eq!(filter(source, results, &json!({})), 2);
+    }
+
+    #[test]
+    fn test_baseline_findings_by_circuit() {
+        let report = json!({
+            "circuit_responses": {
+                "a": {DETECTOR: [{"path": "test.compact", "line": null, "offset_start": 20}]}
+            }
+        });
+        assert_eq!(
+            baseline_findings(&report),
+            vec![BaselineFinding {
+                detector: DETECTOR.to_string(),
+                path: "test.compact".to_string(),
+                line: None,
+                offset_start: 20,
+            }]
+        );
+    }
+}
@@ -1,16 +1,33 @@
 #![warn(clippy::pedantic)]
+use baseline::{baseline_findings, filter_against_baseline, BaselineFinding};
 use clap::Parser;
 use compact_security_detectors::all_detectors;
 use compact_security_detectors_sdk::{
     build_codebase,
-    detector::{CompactDetector, DetectorResult},
+    codebase::{Codebase, SealedState},
+    detector::{CompactDetector, Detector, DetectorResult, Severity},
 };
 use libloading::{Library, Symbol};
-use parser::Cli;
+use parser::{Cli, GroupBy, OutputFormat};
+use report::{
+    build_error, detector_responses, exit_code, group_results_by_circuit, parse_errors,
+    relative_file_path, scan_report, scan_summary, to_json_string, unmatched_filter_errors,
+    BUILD_FAILURE_EXIT_CODE,
+};
+use sarif::{sarif_error_report, sarif_report};
+use selection::{select_detectors, DetectorSelection};
 use serde_json::{json, Map};
-use std::{collections::HashMap, path::PathBuf};
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
 
+mod baseline;
 mod parser;
+mod report;
+mod sarif;
+mod selection;
+mod watch;
 
 fn main() {
     let args = Cli::parse();
@@ -21,143 +38,258 @@ fn main() {
             detectors,
             project_root,
             load_lib,
+            group_by,
+            watch,
+            print_ast,
+            format,
+            baseline,
+            fail_on,
         } => {
-            let mut corpus = HashMap::new();
-            for path in &code {
-                if path.is_dir() {
-                    let mut stack = vec![path.clone()];
-                    while let Some(current_path) = stack.pop() {
-                        for entry in std::fs::read_dir(current_path).unwrap() {
-                            let entry = entry.unwrap();
-                            let p = entry.path();
-                            if p.is_dir() {
-                                stack.push(p);
-                            } else if p.is_file() && p.extension().unwrap_or_default() == "compact"
-                            {
-                                let file_content = std::fs::read_to_string(&p).unwrap();
-                                corpus.insert(p.to_string_lossy().to_string(), file_content);
-                            }
-                        }
-                    }
-                } else if path.is_file() {
-                    if path.extension().unwrap_or_default() != "compact" {
-                        continue;
-                    }
-                    let file_content = std::fs::read_to_string(path).unwrap();
-                    corpus.insert(path.to_string_lossy().to_string(), file_content);
-                }
+            let scanned = scan_detectors(load_lib.as_deref());
+            let severities = detector_severities(&scanned);
+            let selection = select_detectors(scanned, detectors.as_ref());
+            if let Some(dir) = watch {
+                warn_unmatched_filters(&selection.unmatched);
+                watch::watch(
+                    &dir,
+                    selection.detectors,
+                    project_root.as_ref(),
+                    args.compact,
+                );
+                return;
+            }
+            if let Some(file) = print_ast {
+                print_file_ast(code, &file);
+                return;
+            }
+            let fail_on = fail_on.as_deref().map(Severity::from);
+            let baseline = baseline.map(|path| read_baseline(&path));
+            let corpus = collect_corpus(&code);
+            if format == OutputFormat::Sarif {
+                let (sarif, code) = sarif_scan(
+                    &corpus,
+                    &selection,
+                    baseline.as_deref(),
+                    project_root.as_ref(),
+                    &severities,
+                    fail_on,
+                );
+                println!("{}", to_json_string(&sarif, args.compact));
+                std::process::exit(code);
             }
             let mut files_scanned = Vec::new();
-            let mut detector_responses = Map::new();
+            let mut responses = Map::new();
+            let mut summary = scan_summary(&HashMap::new(), &HashMap::new());
+            let mut errors = unmatched_filter_errors(&selection.unmatched);
+            let mut code = 0;
             if !corpus.is_empty() {
-                let result = execute_detectors(&corpus, detectors.as_ref(), load_lib);
-
                 files_scanned = corpus
                     .keys()
                     .map(|k| relative_file_path(k, project_root.as_ref()))
                     .collect();
-
-                for (detector_name, errors) in result {
-                    let instances = detector_result_to_json(errors, project_root.as_ref());
-
-                    let detector_response = json!({
-                        "findings": [
-                            {
-                                "instances": instances
+                match build_codebase(&corpus) {
+                    Ok((codebase, diagnostics)) => {
+                        errors.extend(parse_errors(&diagnostics, project_root.as_ref()));
+                        let result = run_detectors(&codebase, &selection.detectors);
+                        let result = apply_baseline(
+                            &codebase,
+                            result,
+                            baseline.as_deref(),
+                            project_root.as_ref(),
+                        );
+                        summary = scan_summary(&result, &severities);
+                        code = exit_code(&result, &severities, fail_on);
+                        responses = match group_by {
+                            GroupBy::Circuit => {
+                                group_results_by_circuit(&codebase, result, project_root.as_ref())
+                            }
+                            GroupBy::Detector => {
+                                detector_responses(&codebase, result, project_root.as_ref())
                             }
-                        ],
-                        "errors": [],
-                        "metadata": {}
-                    });
-                    detector_responses.insert(detector_name, detector_response);
+                        };
+                    }
+                    Err(error) => {
+                        errors.push(build_error(&error));
+                        code = BUILD_FAILURE_EXIT_CODE;
+                    }
                 }
             }
-            let res = json!({
-                "errors": [],
-                "scanned": files_scanned,
-                "detector_responses": detector_responses,
-            });
+            let res = scan_report(&files_scanned, group_by, &responses, &summary, &errors);
 
-            println!("{}", serde_json::to_string_pretty(&res).unwrap());
+            println!("{}", to_json_string(&res, args.compact));
+            std::process::exit(code);
         }
         parser::Commands::Metadata => {
-            println!("{}", get_scanner_metadata());
+            println!("{}", to_json_string(&get_scanner_metadata(), args.compact));
         }
     }
 }
 
-fn execute_detectors(
-    files: &HashMap<String, String>,
-    rules: Option<&Vec<String>>,
-    load_lib: Option<std::path::PathBuf>,
-) -> HashMap<String, Vec<DetectorResult>> {
-    let codebase = build_codebase(files).unwrap();
-    let mut results = HashMap::new();
-    if let Some(load_lib) = load_lib {
-        unsafe {
-            let lib = Library::new(load_lib).unwrap();
-            let constructor: Symbol<unsafe extern "C" fn() -> CompactDetector> =
-                lib.get(b"external_detector").unwrap();
-            let detector = constructor();
-            let detector_result = detector.check(codebase.as_ref());
-            if let Some(errors) = detector_result {
-                results.insert(detector.id().to_string(), errors);
+fn compact_files(code: &[PathBuf]) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for path in code {
+        if path.is_dir() {
+            let mut stack = vec![path.clone()];
+            while let Some(current_path) = stack.pop() {
+                for entry in std::fs::read_dir(current_path).unwrap() {
+                    let entry = entry.unwrap();
+                    let p = entry.path();
+                    if p.is_dir() {
+                        stack.push(p);
+                    } else if p.is_file() && p.extension().unwrap_or_default() == "compact" {
+                        files.push(p);
+                    }
+                }
             }
+        } else if path.is_file() && path.extension().unwrap_or_default() == "compact" {
+            files.push(path.clone());
         }
     }
-    let selected_detectors: Vec<_> = available_detectors()
+    files
+}
+
+fn collect_corpus(code: &[PathBuf]) -> HashMap<String, String> {
+    compact_files(code)
         .into_iter()
-        .filter(|detector| {
-            if let Some(rules) = rules {
-                rules.contains(&detector.id().to_string())
-                    || (rules.len() == 1 && rules[0].eq_ignore_ascii_case("all"))
-            } else {
-                true
-            }
+        .map(|path| {
+            let file_content = std::fs::read_to_string(&path).unwrap();
+            (path.to_string_lossy().to_string(), file_content)
         })
-        .collect();
+        .collect()
+}
 
-    for detector in selected_detectors {
-        let detector_result = detector.check(codebase.as_ref());
+/// Prints the AST dump of `file`, parsed together with `code`, or alone if `code` is empty.
+fn print_file_ast(code: Vec<PathBuf>, file: &Path) {
+    let code = if code.is_empty() {
+        vec![file.to_path_buf()]
+    } else {
+        code
+    };
+    let (codebase, _) = match build_codebase(&collect_corpus(&code)) {
+        Ok(built) => built,
+        Err(error) => {
+            eprintln!("Cannot build the codebase: {error:#}");
+            std::process::exit(BUILD_FAILURE_EXIT_CODE);
+        }
+    };
+    let Some(dump) = dump_file_ast(&codebase, file) else {
+        eprintln!("File {} is not part of the scanned code", file.display());
+        std::process::exit(1);
+    };
+    println!("{dump}");
+}
+
+/// Returns the AST dump of `file`, matching the codebase files by path or canonical path.
+fn dump_file_ast(codebase: &Codebase<SealedState>, file: &Path) -> Option<String> {
+    let canonical = file.canonicalize().ok();
+    codebase
+        .files()
+        .map(|source_file| source_file.file_path)
+        .find(|path| {
+            Path::new(path) == file
+                || canonical.is_some() && Path::new(path).canonicalize().ok() == canonical
+        })
+        .and_then(|path| codebase.dump_ast(&path))
+}
+
+/// Scans the corpus and returns the SARIF log and the exit code of the scan. If the codebase
+/// cannot be built, the log reports the error and the scan fails.
+fn sarif_scan(
+    corpus: &HashMap<String, String>,
+    selection: &DetectorSelection,
+    baseline: Option<&[BaselineFinding]>,
+    project_root: Option<&PathBuf>,
+    severities: &HashMap<String, Severity>,
+    fail_on: Option<Severity>,
+) -> (serde_json::Value, i32) {
+    let (codebase, diagnostics) = match build_codebase(corpus) {
+        Ok(built) => built,
+        Err(error) => return (sarif_error_report(&error), BUILD_FAILURE_EXIT_CODE),
+    };
+    for diagnostic in &diagnostics {
+        eprintln!("{diagnostic}");
+    }
+    warn_unmatched_filters(&selection.unmatched);
+    let result = run_detectors(&codebase, &selection.detectors);
+    let result = apply_baseline(&codebase, result, baseline, project_root);
+    let sarif = sarif_report(&codebase, &result, &selection.detectors, project_root);
+    (sarif, exit_code(&result, severities, fail_on))
+}
+
+/// Runs the detectors one after another, keyed by detector id. Results are ordered by
+/// `detector_responses` when reported.
+///
+/// The detectors are not run in parallel: the AST nodes of a codebase are reference counted with
+/// `Rc`, so `Codebase` is neither `Send` nor `Sync` and cannot be shared between threads.
+fn run_detectors(
+    codebase: &Codebase<SealedState>,
+    detectors: &[CompactDetector],
+) -> HashMap<String, Vec<DetectorResult>> {
+    let mut results = HashMap::new();
+    for detector in detectors {
+        let detector_result = detector.check(codebase);
         if let Some(errors) = detector_result {
+            let errors = dedup_results(detector.as_ref(), errors);
+            let errors = codebase.reportable_results(errors);
+            let errors = codebase.unsuppressed_results(&detector.id(), errors);
             results.insert(detector.id().to_string(), errors);
         }
     }
     results
 }
 
-fn detector_result_to_json(
-    errors: Vec<DetectorResult>,
-    project_root: Option<&PathBuf>,
-) -> serde_json::Value {
-    let mut json_errors = Vec::new();
-    for error in errors {
-        let path = relative_file_path(&error.file_path, project_root);
-
-        let json_error = json!({
-            "path": path,
-            "offset_start": error.offset_start,
-            "offset_end": error.offset_end,
-            "fixes": [],
-            "extra": {"metavars": error.extra},
-        });
-        json_errors.push(json_error);
+/// Prints the `--detectors` filters that matched no detector to stderr.
+fn warn_unmatched_filters(filters: &[String]) {
+    for filter in filters {
+        eprintln!("Detector filter `{filter}` does not match any detector");
     }
-    json!(json_errors)
 }
 
-fn relative_file_path(file_path: &str, project_root: Option<&PathBuf>) -> String {
-    if let Some(root) = project_root {
-        if let Ok(relative_path) = std::path::Path::new(file_path).strip_prefix(root) {
-            relative_path.to_string_lossy().to_string()
-        } else {
-            file_path.to_string()
+/// Reads the findings of the baseline report at `path`, exiting if it cannot be read.
+fn read_baseline(path: &Path) -> Vec<BaselineFinding> {
+    let report = std::fs::read_to_string(path)
+        .map_err(|error| error.to_string())
+        .and_then(|content| {
+            serde_json::from_str::<serde_json::Value>(&content).map_err(|error| error.to_string())
+        });
+    match report {
+        Ok(report) => baseline_findings(&report),
+        Err(error) => {
+            eprintln!("Cannot read baseline {}: {error}", path.display());
+            std::process::exit(1);
         }
-    } else {
-        file_path.to_string()
     }
 }
 
+/// Drops the results already reported in the baseline, if any.
+fn apply_baseline(
+    codebase: &Codebase<SealedState>,
+    results: HashMap<String, Vec<DetectorResult>>,
+    baseline: Option<&[BaselineFinding]>,
+    project_root: Option<&PathBuf>,
+) -> HashMap<String, Vec<DetectorResult>> {
+    let Some(baseline) = baseline else {
+        return results;
+    };
+    filter_against_baseline(results, baseline, project_root, |result| {
+        codebase
+            .line_col(&result.file_path, result.offset_start as usize)
+            .map(|(line, _)| line)
+    })
+}
+
+/// Drops the results whose `dedup_key` was already reported by the detector.
+fn dedup_results<D: Detector + ?Sized>(
+    detector: &D,
+    results: Vec<DetectorResult>,
+) -> Vec<DetectorResult> {
+    let mut seen = HashSet::new();
+    results
+        .into_iter()
+        .filter(|result| seen.insert(detector.dedup_key(result)))
+        .collect()
+}
+
 fn available_detectors() -> Vec<CompactDetector> {
     all_detectors()
         .into_iter()
@@ -165,6 +297,35 @@ fn available_detectors() -> Vec<CompactDetector> {
         .collect()
 }
 
+/// Loads the detector exported as `external_detector` by the library at `path`.
+///
+/// The library is never unloaded, since the code of the detector lives in it.
+fn load_detector(path: &Path) -> CompactDetector {
+    unsafe {
+        let lib = std::mem::ManuallyDrop::new(Library::new(path).unwrap());
+        let constructor: Symbol<unsafe extern "C" fn() -> CompactDetector> =
+            lib.get(b"external_detector").unwrap();
+        constructor()
+    }
+}
+
+/// Returns the available detectors and the detector of the `--load-lib` library, if any.
+fn scan_detectors(load_lib: Option<&Path>) -> Vec<CompactDetector> {
+    load_lib
+        .map(load_detector)
+        .into_iter()
+        .chain(available_detectors())
+        .collect()
+}
+
+/// Returns the typed severity of every detector, keyed by detector id.
+fn detector_severities(detectors: &[CompactDetector]) -> HashMap<String, Severity> {
+    detectors
+        .iter()
+        .map(|detector| (detector.id(), Severity::from(detector.severity().as_str())))
+        .collect()
+}
+
 #[allow(clippy::let_and_return, unused_mut)]
 fn custom_detectors() -> Vec<CompactDetector> {
     let mut detectors: Vec<CompactDetector> = Vec::new();
@@ -174,7 +335,7 @@ fn custom_detectors() -> Vec<CompactDetector> {
         .collect()
 }
 
-fn get_scanner_metadata() -> String {
+fn get_scanner_metadata() -> serde_json::Value {
     let version = env!("CARGO_PKG_VERSION");
     let org = "OpenZeppelin";
     let description = "Static analyzer for Midnight network Compact source code files";
@@ -184,6 +345,8 @@ fn get_scanner_metadata() -> String {
             "id": detector.id(),
             "uid": detector.uid(),
             "description": detector.description(),
+            "category": detector.category(),
+            "enabled_by_default": detector.enabled_by_default(),
             "report": {
                 "severity": detector.severity(),
                 "tags": detector.tags(),
@@ -200,9 +363,353 @@ fn get_scanner_metadata() -> String {
         "extensions": [".compact"],
         "detectors": detectors
     });
-    serde_json::to_string_pretty(&scanner_json).unwrap()
+    scanner_json
 }
 
 fn yml_string_to_json(yml_string: &str) -> Option<serde_json::Value> {
     serde_yaml::from_str::<serde_json::Value>(yml_string).ok()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs the available detectors selected by `rules`.
+    fn execute_detectors(
+        codebase: &Codebase<SealedState>,
+        rules: Option<&Vec<String>>,
+    ) -> HashMap<String, Vec<DetectorResult>> {
+        run_detectors(
+            codebase,
+            &select_detectors(available_detectors(), rules).detectors,
+        )
+    }
+
+    #[test]
+    fn test_dedup_results_with_custom_key() {
+        struct PerLedger;
+        impl Detector for PerLedger {
+            fn check(&self, _codebase: &Codebase<SealedState>) -> Option<Vec<DetectorResult>> {
+                None
+            }
+
+            fn dedup_key(&self, result: &DetectorResult) -> String {
+                result
+                    .extra
+                    .as_ref()
+                    .and_then(|extra| extra.get("LEDGER").cloned())
+                    .unwrap_or_default()
+            }
+        }
+        struct BySpan;
+        impl Detector for BySpan {
+            fn check(&self, _codebase: &Codebase<SealedState>) -> Option<Vec<DetectorResult>> {
+                None
+            }
+        }
+        let result = |offset_start: u32| DetectorResult {
+            file_path: "test.compact".to_string(),
+            offset_start,
+            offset_end: offset_start + 5,
+            extra: Some(HashMap::from([("LEDGER".to_string(), "admin".to_string())])),
+        };
+        let results = vec![result(10), result(40)];
+        assert_eq!(dedup_results(&PerLedger, results.clone()).len(), 1);
+
+        assert_eq!(dedup_results(&BySpan, results).len(), 2);
+    }
+
+    #[test]
+    fn test_group_results_by_circuit() {
+        let src = "export circuit set_admin(new_admin: Bytes<32>): [] {
+            const current_proof = generate_key_proof(sigCounter as Field as Bytes<32>);
+            assert admin == pad(32, \"\") \"\";
+            admin = new_admin;
+            return [];
+        }
+
+        export circuit get_admin(): Bytes<32> {
+            return admin;
+        }";
+        let mut corpus = HashMap::new();
+        corpus.insert("test.compact".to_string(), src.to_string());
+        let (codebase, _) = build_codebase(&corpus).unwrap();
+        let results = execute_detectors(&codebase, None);
+        let grouped = group_results_by_circuit(&codebase, results, None);
+        assert_eq!(grouped.len(), 1, "{grouped:?}");
+        let set_admin = grouped
+            .get("set_admin")
+            .and_then(|findings| findings.as_object())
+            .expect("set_admin findings not found");
+        let instances = set_admin
+            .get("assertion-error-message-verbose")
+            .and_then(|instances| instances.as_array())
+            .expect("assertion-error-message-verbose findings not found");
+        assert_eq!(instances.len(), 1);
+        assert_eq!(instances[0]["path"], "test.compact");
+    }
+    #[test]
+    fn test_scan_with_unparseable_file() {
+        let valid = "export circuit set_admin(new_admin: Bytes<32>): [] {
+            assert admin == pad(32, \"\") \"\";
+            admin = new_admin;
+            return [];
+        }";
+        let corpus = HashMap::from([
+            ("/project/valid.compact".to_string(), valid.to_string()),
+            (
+                "/project/invalid.compact".to_string(),
+                "export circuit broken(: [] { %% }".to_string(),
+            ),
+        ]);
+        let (codebase, diagnostics) = build_codebase(&corpus).unwrap();
+        let results = execute_detectors(&codebase, None);
+        assert!(results
+            .values()
+            .flatten()
+            .all(|result| result.file_path == "/project/valid.compact"));
+        assert!(results.contains_key("assertion-error-message-verbose"));
+        let errors = parse_errors(&diagnostics, Some(&PathBuf::from("/project")));
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0]["path"], "invalid.compact");
+        assert!(errors[0]["message"].is_string());
+        assert!(errors[0]["expected"].is_array());
+    }
+
+    #[test]
+    fn test_execute_detectors_deterministic() {
+        let corpus: HashMap<String, String> = (0..8)
+            .map(|index| {
+                (
+                    format!("file_{index}.compact"),
+                    format!(
+                        "export ledger admin_{index}: Bytes<32>;
+                        export circuit set_admin_{index}(new_admin: Bytes<32>): [] {{
+                            assert admin_{index} == pad(32, \"\") \"\";
+                            admin_{index} = new_admin;
+                        }}"
+                    ),
+                )
+            })
+            .collect();
+        let (codebase, _) = build_codebase(&corpus).unwrap();
+        let report = || {
+            let results = execute_detectors(&codebase, None);
+            let total: usize = results.values().map(Vec::len).sum();
+            (
+                to_json_string(&json!(detector_responses(&codebase, results, None)), true),
+                total,
+            )
+        };
+        let (first, total) = report();
+        assert_eq!(total, 8);
+        for _ in 0..4 {
+            assert_eq!(report().0, first);
+        }
+    }
+
+    #[test]
+    fn test_clone_shallow_detector_results() {
+        let src = "export circuit set_admin(new_admin: Bytes<32>): [] {
+            assert admin == pad(32, \"\") \"\";
+            admin = new_admin;
+        }";
+        let corpus = HashMap::from([("test.compact".to_string(), src.to_string())]);
+        let (codebase, _) = build_codebase(&corpus).unwrap();
+        let copy = codebase.clone_shallow();
+        let mut original = execute_detectors(&codebase, None);
+        let mut cloned = execute_detectors(&copy, None);
+        assert!(!original.is_empty());
+        for results in original.values_mut().chain(cloned.values_mut()) {
+            results.sort_by_key(|result| result.offset_start);
+        }
+        assert_eq!(original, cloned);
+    }
+
+    #[test]
+    fn test_suppression_comments() {
+        let src = "export circuit contains(arr: Vector<10, Address>, addr: Address): Bool {
+            for (const i of 0 .. 10) {
+                // compact-disable-next-line array-loop-bound-check
+                if (arr[11] == addr) {
+                    return true;
+                }
+                if (arr[12] == addr) {
+                    return true;
+                }
+            }
+            return false;
+        }";
+        let corpus = HashMap::from([("test.compact".to_string(), src.to_string())]);
+        let (codebase, _) = build_codebase(&corpus).unwrap();
+        let results =
+            execute_detectors(&codebase, Some(&vec!["array-loop-bound-check".to_string()]));
+        let instances = &results["array-loop-bound-check"];
+        assert_eq!(instances.len(), 1, "{instances:?}");
+        let offset = u32::try_from(src.find("arr[12]").unwrap()).unwrap();
+        assert_eq!(instances[0].offset_start, offset);
+    }
+
+    #[test]
+    fn test_compact_metadata_output() {
+        let args = Cli::try_parse_from(["compact-scanner", "metadata", "--compact"]).unwrap();
+        assert!(args.compact);
+        let output = to_json_string(&get_scanner_metadata(), args.compact);
+        assert!(!output.contains('\n'));
+        let parsed: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(parsed["name"], "compact-scanner");
+    }
+
+    #[test]
+    fn test_metadata_category_and_enabled_by_default() {
+        let metadata = get_scanner_metadata();
+        let detectors = metadata["detectors"].as_array().unwrap();
+        assert!(!detectors.is_empty());
+        for detector in detectors {
+            assert!(detector["category"].is_string());
+            assert!(detector["enabled_by_default"].is_boolean());
+        }
+        let array_bounds = detectors
+            .iter()
+            .find(|detector| detector["id"] == "array-loop-bound-check")
+            .expect("array-loop-bound-check metadata not found");
+        assert_eq!(array_bounds["category"], "array-bounds");
+        assert_eq!(array_bounds["enabled_by_default"], true);
+    }
+
+    #[test]
+    fn test_select_detectors_by_category() {
+        let src = "export circuit set_admin(new_admin: Bytes<32>): [] {
+            assert admin == pad(32, \"\") \"\";
+            admin = new_admin;
+        }";
+        let corpus = HashMap::from([("test.compact".to_string(), src.to_string())]);
+        let (codebase, _) = build_codebase(&corpus).unwrap();
+        let rules = vec!["error-handling".to_string()];
+        let results = execute_detectors(&codebase, Some(&rules));
+        assert!(results.contains_key("assertion-error-message-verbose"));
+        assert!(results
+            .keys()
+            .all(|id| id == "assertion-error-message-verbose"));
+    }
+
+    #[test]
+    fn test_unmatched_filter_reported_in_errors() {
+        let filters = vec!["no-such-detector-*".to_string()];
+        let selection = select_detectors(available_detectors(), Some(&filters));
+        assert!(selection.detectors.is_empty());
+        let errors = unmatched_filter_errors(&selection.unmatched);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0]["filter"], "no-such-detector-*");
+        assert!(errors[0]["message"].is_string());
+    }
+
+    /// Stands in for a detector loaded with `--load-lib`.
+    #[allow(unused_imports)]
+    mod lib_detector {
+        use compact_security_detectors_sdk::{
+            codebase::{Codebase, SealedState},
+            detector::DetectorResult,
+        };
+
+        compact_security_detectors_sdk::detector! {
+            #[type_name = LibDetector]
+            #[id = "lib-detector"]
+            #[severity = "high"]
+            fn check(_codebase: &Codebase<SealedState>) -> Option<Vec<DetectorResult>> {
+                None
+            }
+        }
+    }
+
+    #[test]
+    fn test_loaded_detector_severity() {
+        let detectors: Vec<CompactDetector> = vec![Box::new(lib_detector::LibDetector)];
+        let severities = detector_severities(&detectors);
+        assert_eq!(severities.get("lib-detector"), Some(&Severity::High));
+        let results = HashMap::from([(
+            "lib-detector".to_string(),
+            vec![DetectorResult {
+                file_path: "a.compact".to_string(),
+                offset_start: 0,
+                offset_end: 1,
+                extra: None,
+            }],
+        )]);
+        assert_eq!(
+            scan_summary(&results, &severities)["by_severity"]["high"],
+            1
+        );
+        assert_eq!(exit_code(&results, &severities, Some(Severity::High)), 1);
+        let unknown = scan_summary(&results, &HashMap::new());
+        assert_eq!(unknown["by_severity"]["informational"], 1);
+    }
+
+    #[test]
+    fn test_loaded_detector_is_selected() {
+        let detectors = |loaded: bool| -> Vec<CompactDetector> {
+            let lib: CompactDetector = Box::new(lib_detector::LibDetector);
+            loaded
+                .then_some(lib)
+                .into_iter()
+                .chain(available_detectors())
+                .collect()
+        };
+        let filters = vec!["lib-detector".to_string()];
+        let selection = select_detectors(detectors(true), Some(&filters));
+        let ids: Vec<String> = selection.detectors.iter().map(|d| d.id()).collect();
+        assert_eq!(ids, vec!["lib-detector"]);
+        assert!(selection.unmatched.is_empty());
+        let filters = vec!["assertion-error-message-verbose".to_string()];
+        let selection = select_detectors(detectors(true), Some(&filters));
+        assert!(selection.detectors.iter().all(|d| d.id() != "lib-detector"));
+        assert_eq!(
+            select_detectors(detectors(false), None).detectors.len() + 1,
+            select_detectors(detectors(true), None).detectors.len()
+        );
+    }
+
+    #[test]
+    fn test_fail_on_argument() {
+        let args =
+            Cli::try_parse_from(["compact-scanner", "scan", "src", "--fail-on", "high"]).unwrap();
+        let parser::Commands::Scan { fail_on, .. } = args.command else {
+            panic!("Expected the scan command");
+        };
+        assert_eq!(fail_on.as_deref().map(Severity::from), Some(Severity::High));
+        assert!(Cli::try_parse_from(["compact-scanner", "scan", "--fail-on", "severe"]).is_err());
+    }
+
+    #[test]
+    fn test_watch_rejects_report_options() {
+        let scan = |extra: &[&str]| {
+            let args = ["compact-scanner", "scan", "--watch", "src"];
+            Cli::try_parse_from(args.iter().chain(extra))
+        };
+        assert!(scan(&[]).is_ok());
+        assert!(scan(&["--fail-on", "high"]).is_err());
+        assert!(scan(&["--baseline", "baseline.json"]).is_err());
+        assert!(scan(&["--format", "sarif"]).is_err());
+        assert!(scan(&["--group-by", "circuit"]).is_err());
+    }
+
+    #[test]
+    fn test_dump_file_ast() {
+        let src = "export ledger admin: Bytes<32>;
+
+        export circuit get_admin(): Bytes<32> {
+            return admin;
+        }";
+        let mut corpus = HashMap::new();
+        corpus.insert("test.compact".to_string(), src.to_string());
+        let (codebase, _) = build_codebase(&corpus).unwrap();
+        let dump = dump_file_ast(&codebase, Path::new("test.compact")).expect("AST not dumped");
+        assert!(dump
+            .lines()
+            .any(|line| line.contains("Ledger") && line.contains("admin")));
+        assert!(dump
+            .lines()
+            .any(|line| line.contains("Circuit") && line.contains("get_admin")));
+        assert!(dump_file_ast(&codebase, Path::new("other.compact")).is_none());
+    }
+}
@@ -1,4 +1,18 @@
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum GroupBy {
+    #[default]
+    Detector,
+    Circuit,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum OutputFormat {
+    #[default]
+    Json,
+    Sarif,
+}
 
 #[derive(Subcommand, Debug)]
 pub(crate) enum Commands {
@@ -10,6 +24,31 @@ pub(crate) enum Commands {
         project_root: Option<std::path::PathBuf>,
         #[arg(long = "load", required = false, value_parser)]
         load_lib: Option<std::path::PathBuf>,
+        #[arg(long = "group-by", value_enum, default_value_t = GroupBy::Detector)]
+        group_by: GroupBy,
+        /// Directory to watch. Findings are printed as they change, so the report options do not
+        /// apply.
+        #[arg(
+            long = "watch",
+            required = false,
+            value_parser,
+            conflicts_with_all = ["format", "baseline", "fail_on", "group_by"]
+        )]
+        watch: Option<std::path::PathBuf>,
+        #[arg(long = "print-ast", required = false, value_parser)]
+        print_ast: Option<std::path::PathBuf>,
+        #[arg(long = "format", value_enum, default_value_t = OutputFormat::Json)]
+        format: OutputFormat,
+        /// Report of a previous scan whose findings are not reported again.
+        #[arg(long = "baseline", required = false, value_parser)]
+        baseline: Option<std::path::PathBuf>,
+        /// Exit with code 1 when a finding has at least this severity.
+        #[arg(
+            long = "fail-on",
+            required = false,
+            value_parser = ["critical", "high", "medium", "low", "informational"]
+        )]
+        fail_on: Option<String>,
     },
     Metadata,
 }
@@ -19,4 +58,7 @@ pub(crate) enum Commands {
 pub(crate) struct Cli {
     #[command(subcommand)]
     pub(crate) command: Commands,
+    /// Print single-line JSON instead of pretty-printed JSON.
+    #[arg(long = "compact", global = true)]
+    pub(crate) compact: bool,
 }
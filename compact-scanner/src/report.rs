@@ -0,0 +1,434 @@
+//! JSON report of a scan.
+//!
+//! The report shape is shared by every output of the scanner: the list of scanned files and
+//! the findings, grouped either by detector or by circuit, a summary of the findings by
+//! severity, and the errors of the files that could not be parsed and of the detector filters
+//! that matched no detector.
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    path::PathBuf,
+};
+
+use compact_security_detectors_sdk::{
+    codebase::{Codebase, ParseDiagnostic, SealedState},
+    detector::{DetectorResult, Severity},
+};
+use serde_json::{json, Map};
+
+use crate::parser::GroupBy;
+
+/// Serializes a JSON value, on a single line if `compact` is set, pretty-printed otherwise.
+pub(crate) fn to_json_string(value: &serde_json::Value, compact: bool) -> String {
+    if compact {
+        serde_json::to_string(value).unwrap()
+    } else {
+        serde_json::to_string_pretty(value).unwrap()
+    }
+}
+
+/// Builds the scan report from the scanned files, the grouped findings, their summary and the
+/// errors of the files that could not be parsed.
+pub(crate) fn scan_report(
+    files_scanned: &[String],
+    group_by: GroupBy,
+    responses: &Map<String, serde_json::Value>,
+    summary: &serde_json::Value,
+    errors: &[serde_json::Value],
+) -> serde_json::Value {
+    match group_by {
+        GroupBy::Detector => json!({
+            "errors": errors,
+            "scanned": files_scanned,
+            "summary": summary,
+            "detector_responses": responses,
+        }),
+        GroupBy::Circuit => json!({
+            "errors": errors,
+            "scanned": files_scanned,
+            "summary": summary,
+            "circuit_responses": responses,
+        }),
+    }
+}
+
+/// Converts the diagnostics of the files that could not be parsed into report errors, with file
+/// paths relative to the project root.
+pub(crate) fn parse_errors(
+    diagnostics: &[ParseDiagnostic],
+    project_root: Option<&PathBuf>,
+) -> Vec<serde_json::Value> {
+    diagnostics
+        .iter()
+        .map(|diagnostic| {
+            json!({
+                "path": relative_file_path(&diagnostic.file, project_root),
+                "offset": diagnostic.offset,
+                "message": diagnostic.message,
+                "expected": diagnostic.expected,
+            })
+        })
+        .collect()
+}
+
+/// Converts the `--detectors` filters that matched no detector into report errors.
+pub(crate) fn unmatched_filter_errors(filters: &[String]) -> Vec<serde_json::Value> {
+    filters
+        .iter()
+        .map(|filter| {
+            json!({
+                "filter": filter,
+                "message": format!("Detector filter `{filter}` does not match any detector"),
+            })
+        })
+        .collect()
+}
+
+/// Converts the error that prevented building the codebase into a report error.
+pub(crate) fn build_error(error: &anyhow::Error) -> serde_json::Value {
+    json!({
+        "message": format!("Cannot build the codebase: {error:#}"),
+    })
+}
+
+/// Counts the findings by severity and the files with at least one finding.
+///
+/// `severities` maps detector ids to their severity, detectors missing from it count as
+/// `informational`, like unknown severity names in `Severity::from`.
+pub(crate) fn scan_summary(
+    results: &HashMap<String, Vec<DetectorResult>>,
+    severities: &HashMap<String, Severity>,
+) -> serde_json::Value {
+    let mut by_severity: BTreeMap<Severity, usize> = BTreeMap::new();
+    for (detector_name, errors) in results {
+        *by_severity
+            .entry(detector_severity(severities, detector_name))
+            .or_default() += errors.len();
+    }
+    let by_severity: Map<String, serde_json::Value> = Severity::ALL
+        .iter()
+        .map(|severity| {
+            let count = by_severity.get(severity).copied().unwrap_or_default();
+            (severity.to_string(), json!(count))
+        })
+        .collect();
+    json!({
+        "total_findings": results.values().map(Vec::len).sum::<usize>(),
+        "files_with_findings": files_with_errors(results).len(),
+        "by_severity": by_severity,
+    })
+}
+
+/// Returns the paths of the files with at least one finding, sorted.
+pub(crate) fn files_with_errors(results: &HashMap<String, Vec<DetectorResult>>) -> Vec<&str> {
+    let files: BTreeSet<&str> = results
+        .values()
+        .flatten()
+        .map(|error| error.file_path.as_str())
+        .collect();
+    files.into_iter().collect()
+}
+
+/// Exit code of a scan whose codebase could not be built, e.g. because of a symbol table error.
+pub(crate) const BUILD_FAILURE_EXIT_CODE: i32 = 2;
+
+/// Returns the exit code of a scan: 1 if a finding has at least the `fail_on` severity, 0
+/// otherwise or without threshold. Severities are looked up as in `scan_summary`.
+pub(crate) fn exit_code(
+    results: &HashMap<String, Vec<DetectorResult>>,
+    severities: &HashMap<String, Severity>,
+    fail_on: Option<Severity>,
+) -> i32 {
+    let Some(fail_on) = fail_on else {
+        return 0;
+    };
+    let failed = results.iter().any(|(detector_name, errors)| {
+        !errors.is_empty() && detector_severity(severities, detector_name) >= fail_on
+    });
+    i32::from(failed)
+}
+
+fn detector_severity(severities: &HashMap<String, Severity>, detector_name: &str) -> Severity {
+    severities
+        .get(detector_name)
+        .copied()
+        .unwrap_or(Severity::Informational)
+}
+
+/// Returns the findings of every detector, keyed by detector id and ordered by it so the output
+/// does not depend on the order the detectors ran in.
+pub(crate) fn detector_responses(
+    codebase: &Codebase<SealedState>,
+    results: HashMap<String, Vec<DetectorResult>>,
+    project_root: Option<&PathBuf>,
+) -> Map<String, serde_json::Value> {
+    let results: BTreeMap<String, Vec<DetectorResult>> = results.into_iter().collect();
+    results
+        .into_iter()
+        .map(|(detector_name, errors)| {
+            let instances = detector_result_to_json(codebase, errors, project_root);
+            let detector_response = json!({
+                "findings": [
+                    {
+                        "instances": instances
+                    }
+                ],
+                "errors": [],
+                "metadata": {}
+            });
+            (detector_name, detector_response)
+        })
+        .collect()
+}
+
+/// Findings that are not located inside any circuit are grouped under this key.
+const NO_CIRCUIT_KEY: &str = "<top-level>";
+
+/// Groups the detector results by the circuit they were found in, then by detector.
+pub(crate) fn group_results_by_circuit(
+    codebase: &Codebase<SealedState>,
+    results: HashMap<String, Vec<DetectorResult>>,
+    project_root: Option<&PathBuf>,
+) -> Map<String, serde_json::Value> {
+    let mut grouped: BTreeMap<String, BTreeMap<String, Vec<DetectorResult>>> = BTreeMap::new();
+    for (detector_name, errors) in results {
+        for error in errors {
+            let circuit_name = codebase
+                .circuit_at(&error.file_path, error.offset_start)
+                .map_or_else(|| NO_CIRCUIT_KEY.to_string(), |circuit| circuit.name());
+            grouped
+                .entry(circuit_name)
+                .or_default()
+                .entry(detector_name.clone())
+                .or_default()
+                .push(error);
+        }
+    }
+    grouped
+        .into_iter()
+        .map(|(circuit_name, detectors)| {
+            let findings: Map<String, serde_json::Value> = detectors
+                .into_iter()
+                .map(|(detector_name, errors)| {
+                    (
+                        detector_name,
+                        detector_result_to_json(codebase, errors, project_root),
+                    )
+                })
+                .collect();
+            (circuit_name, serde_json::Value::Object(findings))
+        })
+        .collect()
+}
+
+/// Converts detector results into report instances. The 1-based `line` of an instance is `null`
+/// when the file is not part of the codebase.
+fn detector_result_to_json(
+    codebase: &Codebase<SealedState>,
+    errors: Vec<DetectorResult>,
+    project_root: Option<&PathBuf>,
+) -> serde_json::Value {
+    let mut json_errors = Vec::new();
+    for error in errors {
+        let path = relative_file_path(&error.file_path, project_root);
+        let line = codebase
+            .line_col(&error.file_path, error.offset_start as usize)
+            .map(|(line, _)| line);
+
+        let json_error = json!({
+            "path": path,
+            "line": line,
+            "offset_start": error.offset_start,
+            "offset_end": error.offset_end,
+            "fixes": [],
+            "extra": {"metavars": error.extra},
+        });
+        json_errors.push(json_error);
+    }
+    json!(json_errors)
+}
+
+pub(crate) fn relative_file_path(file_path: &str, project_root: Option<&PathBuf>) -> String {
+    if let Some(root) = project_root {
+        if let Ok(relative_path) = std::path::Path::new(file_path).strip_prefix(root) {
+            relative_path.to_string_lossy().to_string()
+        } else {
+            file_path.to_string()
+        }
+    } else {
+        file_path.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use compact_security_detectors_sdk::codebase::OpenState;
+
+    #[test]
+    fn test_scan_report_by_detector() {
+        let results = HashMap::from([(
+            "unused-variable".to_string(),
+            vec![DetectorResult {
+                file_path: "/project/src/test.compact".to_string(),
+                offset_start: 10,
+                offset_end: 15,
+                extra: None,
+            }],
+        )]);
+        let project_root = PathBuf::from("/project");
+        let summary = scan_summary(&results, &HashMap::new());
+        let codebase = Codebase::<OpenState>::new().seal().unwrap();
+        let responses = detector_responses(&codebase, results, Some(&project_root));
+        let report = scan_report(
+            &["src/test.compact".to_string()],
+            GroupBy::Detector,
+            &responses,
+            &summary,
+            &[],
+        );
+        assert_eq!(report["scanned"], json!(["src/test.compact"]));
+        let instances =
+            &report["detector_responses"]["unused-variable"]["findings"][0]["instances"];
+        assert_eq!(instances[0]["path"], "src/test.compact");
+        assert_eq!(instances[0]["offset_start"], 10);
+        assert!(report.get("circuit_responses").is_none());
+        assert_eq!(report["summary"]["total_findings"], 1);
+    }
+
+    #[test]
+    fn test_detector_responses_sorted() {
+        let result = DetectorResult {
+            file_path: "a.compact".to_string(),
+            offset_start: 0,
+            offset_end: 1,
+            extra: None,
+        };
+        let names = ["zeta", "alpha", "mu", "beta", "omega"];
+        let results: HashMap<String, Vec<DetectorResult>> = names
+            .iter()
+            .map(|name| ((*name).to_string(), vec![result.clone()]))
+            .collect();
+        let codebase = Codebase::<OpenState>::new().seal().unwrap();
+        let responses = detector_responses(&codebase, results, None);
+        let keys: Vec<&String> = responses.keys().collect();
+        assert_eq!(keys, ["alpha", "beta", "mu", "omega", "zeta"]);
+    }
+
+    #[test]
+    fn test_scan_summary_two_detectors() {
+        let result = |file_path: &str, offset_start: u32| DetectorResult {
+            file_path: file_path.to_string(),
+            offset_start,
+            offset_end: offset_start + 5,
+            extra: None,
+        };
+        let results = HashMap::from([
+            (
+                "unused-variable".to_string(),
+                vec![
+                    result("/project/a.compact", 10),
+                    result("/project/b.compact", 20),
+                ],
+            ),
+            (
+                "unchecked-arithmetic".to_string(),
+                vec![
+                    result("/project/a.compact", 30),
+                    result("/project/a.compact", 40),
+                    result("/project/a.compact", 50),
+                ],
+            ),
+        ]);
+        let severities = HashMap::from([
+            ("unused-variable".to_string(), Severity::Low),
+            ("unchecked-arithmetic".to_string(), Severity::High),
+        ]);
+        let summary = scan_summary(&results, &severities);
+        let codebase = Codebase::<OpenState>::new().seal().unwrap();
+        let responses = detector_responses(&codebase, results, None);
+        let report = scan_report(&[], GroupBy::Detector, &responses, &summary, &[]);
+
+        let instances = |detector: &str| {
+            report["detector_responses"][detector]["findings"][0]["instances"]
+                .as_array()
+                .unwrap()
+                .len()
+        };
+        let summary = &report["summary"];
+        assert_eq!(summary["by_severity"]["low"], instances("unused-variable"));
+        assert_eq!(
+            summary["by_severity"]["high"],
+            instances("unchecked-arithmetic")
+        );
+        assert_eq!(summary["by_severity"]["critical"], 0);
+        assert_eq!(summary["by_severity"]["medium"], 0);
+        assert_eq!(summary["by_severity"]["informational"], 0);
+        assert_eq!(
+            summary["total_findings"],
+            instances("unused-variable") + instances("unchecked-arithmetic")
+        );
+        assert_eq!(summary["files_with_findings"], 2);
+    }
+
+    #[test]
+    fn test_exit_code_fail_on() {
+        let results = HashMap::from([(
+            "unused-variable".to_string(),
+            vec![DetectorResult {
+                file_path: "a.compact".to_string(),
+                offset_start: 0,
+                offset_end: 1,
+                extra: None,
+            }],
+        )]);
+        let severities = HashMap::from([("unused-variable".to_string(), Severity::Medium)]);
+        assert_eq!(exit_code(&results, &severities, Some(Severity::High)), 0);
+        assert_eq!(exit_code(&results, &severities, Some(Severity::Medium)), 1);
+        assert_eq!(exit_code(&results, &severities, Some(Severity::Low)), 1);
+        assert_eq!(exit_code(&results, &severities, None), 0);
+        assert_eq!(
+            exit_code(&HashMap::new(), &severities, Some(Severity::Low)),
+            0
+        );
+    }
+
+    #[test]
+    fn test_build_error() {
+        let error = build_error(&anyhow::anyhow!("Symbol `x` is declared twice"));
+        assert_eq!(
+            error["message"],
+            "Cannot build the codebase: Symbol `x` is declared twice"
+        );
+    }
+
+    #[test]
+    fn test_files_with_errors() {
+        let result = |file_path: &str| DetectorResult {
+            file_path: file_path.to_string(),
+            offset_start: 0,
+            offset_end: 1,
+            extra: None,
+        };
+        let results = HashMap::from([
+            (
+                "a".to_string(),
+                vec![result("b.compact"), result("a.compact")],
+            ),
+            ("b".to_string(), vec![result("b.compact")]),
+            ("c".to_string(), vec![]),
+        ]);
+        assert_eq!(files_with_errors(&results), vec!["a.compact", "b.compact"]);
+    }
+
+    #[test]
+    fn test_to_json_string_compact() {
+        let value = json!({"errors": [], "scanned": ["a.compact", "b.compact"]});
+        let compact = to_json_string(&value, true);
+        assert!(!compact.contains('\n'));
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&compact).unwrap(),
+            value
+        );
+        assert!(to_json_string(&value, false).contains('\n'));
+    }
+}
@@ -0,0 +1,200 @@
+//! SARIF 2.1.0 report of a scan.
+//!
+//! Every detector result becomes a SARIF `result` whose `ruleId` is the detector id and whose
+//! level is mapped from the detector severity. Regions use 1-based lines and columns counted in
+//! Unicode code points, as declared by the run `columnKind`.
+use std::{collections::HashMap, path::PathBuf};
+
+use compact_security_detectors_sdk::{
+    codebase::{Codebase, SealedState},
+    detector::{CompactDetector, DetectorResult, Severity},
+};
+use serde_json::json;
+
+use crate::report::relative_file_path;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const SARIF_VERSION: &str = "2.1.0";
+
+/// Maps a detector severity to a SARIF result level.
+fn sarif_level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Critical | Severity::High => "error",
+        Severity::Medium => "warning",
+        Severity::Low | Severity::Informational => "note",
+    }
+}
+
+/// Builds the SARIF log of a scan. `detectors` provide the rules metadata, results of detectors
+/// missing from it are reported as warnings with the detector id as message.
+pub(crate) fn sarif_report(
+    codebase: &Codebase<SealedState>,
+    results: &HashMap<String, Vec<DetectorResult>>,
+    detectors: &[CompactDetector],
+    project_root: Option<&PathBuf>,
+) -> serde_json::Value {
+    let rules: Vec<serde_json::Value> = detectors
+        .iter()
+        .map(|detector| {
+            json!({
+                "id": detector.id(),
+                "name": detector.uid(),
+                "shortDescription": {"text": detector.description()},
+                "properties": {"tags": detector.tags()},
+            })
+        })
+        .collect();
+
+    let mut detector_ids: Vec<&String> = results.keys().collect();
+    detector_ids.sort();
+    let mut sarif_results = Vec::new();
+    for detector_id in detector_ids {
+        let detector = detectors
+            .iter()
+            .find(|detector| detector.id() == *detector_id);
+        let level = detector.map_or("warning", |detector| {
+            sarif_level(Severity::from(detector.severity().as_str()))
+        });
+        let message = detector
+            .map(|detector| detector.title_single_instance())
+            .filter(|title| !title.is_empty())
+            .unwrap_or_else(|| detector_id.clone());
+        for result in &results[detector_id] {
+            let mut region = serde_json::Map::new();
+            if let Some((line, column)) =
+                codebase.line_col(&result.file_path, result.offset_start as usize)
+            {
+                region.insert("startLine".to_string(), json!(line));
+                region.insert("startColumn".to_string(), json!(column));
+            }
+            if let Some((line, column)) =
+                codebase.line_col(&result.file_path, result.offset_end as usize)
+            {
+                region.insert("endLine".to_string(), json!(line));
+                region.insert("endColumn".to_string(), json!(column));
+            }
+            sarif_results.push(json!({
+                "ruleId": detector_id,
+                "level": level,
+                "message": {"text": message},
+                "locations": [
+                    {
+                        "physicalLocation": {
+                            "artifactLocation": {
+                                "uri": relative_file_path(&result.file_path, project_root),
+                            },
+                            "region": region,
+                        }
+                    }
+                ],
+            }));
+        }
+    }
+
+    sarif_log(&rules, &sarif_results)
+}
+
+/// Builds the SARIF log of a scan that failed before the detectors ran. The error is reported as
+/// a `toolExecutionNotifications` entry of an unsuccessful invocation.
+pub(crate) fn sarif_error_report(error: &anyhow::Error) -> serde_json::Value {
+    let mut log = sarif_log(&[], &[]);
+    log["runs"][0]["invocations"] = json!([
+        {
+            "executionSuccessful": false,
+            "toolExecutionNotifications": [
+                {
+                    "level": "error",
+                    "message": {"text": format!("Cannot build the codebase: {error:#}")},
+                }
+            ],
+        }
+    ]);
+    log
+}
+
+fn sarif_log(rules: &[serde_json::Value], results: &[serde_json::Value]) -> serde_json::Value {
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": SARIF_VERSION,
+        "runs": [
+            {
+                "tool": {
+                    "driver": {
+                        "name": "compact-scanner",
+                        "version": env!("CARGO_PKG_VERSION"),
+                        "informationUri": "https://github.com/OpenZeppelin/compact-security-detectors-sdk",
+                        "rules": rules,
+                    }
+                },
+                "columnKind": "unicodeCodePoints",
+                "results": results,
+            }
+        ]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{available_detectors, run_detectors};
+    use compact_security_detectors_sdk::build_codebase;
+
+    #[test]
+    fn test_sarif_report_required_fields() {
+        let src = "export circuit set_admin(new_admin: Bytes<32>): [] {
+            assert admin == pad(32, \"\") \"\";
+            admin = new_admin;
+            return [];
+        }";
+        let corpus = HashMap::from([("/project/test.compact".to_string(), src.to_string())]);
+        let (codebase, _) = build_codebase(&corpus).unwrap();
+        let detectors = available_detectors();
+        let results = run_detectors(&codebase, &detectors);
+        let project_root = PathBuf::from("/project");
+        let sarif = sarif_report(&codebase, &results, &detectors, Some(&project_root));
+
+        assert_eq!(sarif["version"], "2.1.0");
+        assert!(sarif["$schema"].is_string());
+        let run = &sarif["runs"][0];
+        let driver = &run["tool"]["driver"];
+        assert_eq!(driver["name"], "compact-scanner");
+        let rules = driver["rules"].as_array().unwrap();
+        assert!(rules.iter().all(|rule| rule["id"].is_string()));
+
+        let sarif_results = run["results"].as_array().unwrap();
+        assert!(!sarif_results.is_empty());
+        for result in sarif_results {
+            assert!(result["message"]["text"].is_string());
+            let rule_id = result["ruleId"].as_str().unwrap();
+            assert!(rules.iter().any(|rule| rule["id"] == rule_id));
+            assert!(["error", "warning", "note"].contains(&result["level"].as_str().unwrap()));
+            let location = &result["locations"][0]["physicalLocation"];
+            assert_eq!(location["artifactLocation"]["uri"], "test.compact");
+            assert!(location["region"]["startLine"].as_u64().unwrap() >= 1);
+            assert!(location["region"]["startColumn"].as_u64().unwrap() >= 1);
+        }
+        let verbose = sarif_results
+            .iter()
+            .find(|result| result["ruleId"] == "assertion-error-message-verbose")
+            .expect("assertion-error-message-verbose result not found");
+        assert_eq!(verbose["level"], "note");
+        assert_eq!(
+            verbose["locations"][0]["physicalLocation"]["region"]["startLine"],
+            2
+        );
+    }
+    #[test]
+    fn test_sarif_error_report() {
+        let sarif = sarif_error_report(&anyhow::anyhow!("Symbol `x` is declared twice"));
+        let run = &sarif["runs"][0];
+        assert_eq!(run["results"], json!([]));
+        let invocation = &run["invocations"][0];
+        assert_eq!(invocation["executionSuccessful"], false);
+        let notification = &invocation["toolExecutionNotifications"][0];
+        assert_eq!(notification["level"], "error");
+        assert_eq!(
+            notification["message"]["text"],
+            "Cannot build the codebase: Symbol `x` is declared twice"
+        );
+    }
+}
@@ -0,0 +1,212 @@
+//! Selection of the detectors to run from the `--detectors` filters.
+//!
+//! A filter is one of:
+//! - `all`, selecting every detector;
+//! - `category:<name>`, selecting the detectors of a category;
+//! - a pattern where `*` matches any sequence of characters and `?` a single character, e.g.
+//!   `array-*`, matched against the detector ids;
+//! - a detector id or a category name.
+//!
+//! Without filters, the detectors enabled by default are selected.
+use compact_security_detectors_sdk::detector::{CombinedDetector, CompactDetector};
+
+const CATEGORY_PREFIX: &str = "category:";
+
+/// The detectors selected by the filters and the filters that matched no detector.
+pub(crate) struct DetectorSelection {
+    pub(crate) detectors: Vec<CompactDetector>,
+    pub(crate) unmatched: Vec<String>,
+}
+
+/// Selects the detectors matching at least one of the filters among `detectors`.
+pub(crate) fn select_detectors(
+    detectors: Vec<CompactDetector>,
+    filters: Option<&Vec<String>>,
+) -> DetectorSelection {
+    let Some(filters) = filters else {
+        return DetectorSelection {
+            detectors: detectors
+                .into_iter()
+                .filter(|detector| detector.enabled_by_default())
+                .collect(),
+            unmatched: Vec::new(),
+        };
+    };
+    let unmatched = filters
+        .iter()
+        .filter(|filter| {
+            !detectors
+                .iter()
+                .any(|detector| filter_matches(filter, detector.as_ref()))
+        })
+        .cloned()
+        .collect();
+    let detectors = detectors
+        .into_iter()
+        .filter(|detector| {
+            filters
+                .iter()
+                .any(|filter| filter_matches(filter, detector.as_ref()))
+        })
+        .collect();
+    DetectorSelection {
+        detectors,
+        unmatched,
+    }
+}
+
+/// Checks if a single filter selects the detector.
+fn filter_matches(filter: &str, detector: &dyn CombinedDetector) -> bool {
+    if filter.eq_ignore_ascii_case("all") {
+        return true;
+    }
+    if let Some(category) = filter.strip_prefix(CATEGORY_PREFIX) {
+        return detector.category() == category;
+    }
+    if filter.contains(['*', '?']) {
+        return glob_matches(filter, &detector.id());
+    }
+    detector.id() == filter || detector.category() == filter
+}
+
+/// Checks if `text` matches `pattern`, where `*` matches any sequence of characters, including an
+/// empty one, and `?` matches exactly one character.
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Declares a fixture detector in its own module, as `detector!` imports the `Detector` trait.
+    macro_rules! fixture {
+        ($module:ident, $tname:ident, $id:literal, $category:literal, $enabled:literal) => {
+            #[allow(unused_imports)]
+            mod $module {
+                use compact_security_detectors_sdk::{
+                    codebase::{Codebase, SealedState},
+                    detector::DetectorResult,
+                };
+
+                compact_security_detectors_sdk::detector! {
+                    #[type_name = $tname]
+                    #[id = $id]
+                    #[category = $category]
+                    #[enabled_by_default = $enabled]
+                    fn check(_codebase: &Codebase<SealedState>) -> Option<Vec<DetectorResult>> {
+                        None
+                    }
+                }
+            }
+        };
+    }
+
+    fixture!(
+        array_bounds,
+        ArrayBounds,
+        "array-bounds",
+        "arithmetic",
+        true
+    );
+    fixture!(
+        array_length,
+        ArrayLength,
+        "array-length",
+        "arithmetic",
+        false
+    );
+    fixture!(
+        missing_assert,
+        MissingAssert,
+        "missing-assert",
+        "access-control",
+        true
+    );
+
+    fn detectors() -> Vec<CompactDetector> {
+        vec![
+            Box::new(array_bounds::ArrayBounds),
+            Box::new(array_length::ArrayLength),
+            Box::new(missing_assert::MissingAssert),
+        ]
+    }
+
+    fn selected_ids(filters: &[&str]) -> (Vec<String>, Vec<String>) {
+        let filters: Vec<String> = filters.iter().map(ToString::to_string).collect();
+        let selection = select_detectors(detectors(), Some(&filters));
+        let ids = selection
+            .detectors
+            .iter()
+            .map(|detector| detector.id())
+            .collect();
+        (ids, selection.unmatched)
+    }
+
+    #[test]
+    fn test_select_without_filters() {
+        let selection = select_detectors(detectors(), None);
+        let ids: Vec<String> = selection.detectors.iter().map(|d| d.id()).collect();
+        assert_eq!(ids, vec!["array-bounds", "missing-assert"]);
+        assert!(selection.unmatched.is_empty());
+        assert_eq!(selected_ids(&["all"]).0.len(), 3);
+    }
+
+    #[test]
+    fn test_select_by_id_and_glob() {
+        assert_eq!(selected_ids(&["missing-assert"]).0, vec!["missing-assert"]);
+        assert_eq!(
+            selected_ids(&["array-*"]).0,
+            vec!["array-bounds", "array-length"]
+        );
+        assert_eq!(selected_ids(&["array-b?unds"]).0, vec!["array-bounds"]);
+        assert_eq!(selected_ids(&["*-assert"]).0, vec!["missing-assert"]);
+    }
+
+    #[test]
+    fn test_select_by_category() {
+        assert_eq!(
+            selected_ids(&["category:arithmetic"]).0,
+            vec!["array-bounds", "array-length"]
+        );
+        assert_eq!(selected_ids(&["access-control"]).0, vec!["missing-assert"]);
+    }
+
+    #[test]
+    fn test_unmatched_filters() {
+        let (ids, unmatched) = selected_ids(&["missing-assert", "reentrancy-*", "category:none"]);
+        assert_eq!(ids, vec!["missing-assert"]);
+        assert_eq!(unmatched, vec!["reentrancy-*", "category:none"]);
+        let (ids, unmatched) = selected_ids(&["unknown"]);
+        assert!(ids.is_empty());
+        assert_eq!(unmatched, vec!["unknown"]);
+    }
+
+    #[test]
+    fn test_glob_matches() {
+        assert!(glob_matches("*", ""));
+        assert!(glob_matches("a*b*c", "aXXbYYc"));
+        assert!(glob_matches("a*c", "abcbc"));
+        assert!(!glob_matches("a*c", "abcb"));
+        assert!(!glob_matches("a?c", "ac"));
+    }
+}
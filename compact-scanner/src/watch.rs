@@ -0,0 +1,362 @@
+//! Watch mode for the scanner.
+//!
+//! The watched directory is polled for new, modified or deleted `.compact` files. Every changed
+//! file is re-parsed with `Codebase::replace_file`, deleted files are removed with
+//! `Codebase::remove_file`, the selected detectors, including the `--load` detector, are re-run
+//! and only the findings that appeared or were resolved since the previous run are printed.
+//!
+//! A change that leaves a file unparsable, e.g. while it is being edited, is reported as an error
+//! and the previous codebase is kept until the file parses again.
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use compact_security_detectors_sdk::{
+    build_codebase,
+    codebase::{Codebase, ParseDiagnostic, SealError, SealedState},
+    detector::CompactDetector,
+};
+use serde_json::json;
+
+use crate::{
+    collect_corpus, compact_files,
+    report::{parse_errors, relative_file_path, to_json_string},
+    run_detectors,
+};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// A finding identity: detector id, file path, start offset and end offset.
+pub(crate) type Finding = (String, String, u32, u32);
+
+/// Findings that changed between two detector runs, and the files that could not be parsed.
+#[derive(Debug, Default)]
+pub(crate) struct FindingsDiff {
+    pub(crate) added: Vec<Finding>,
+    pub(crate) resolved: Vec<Finding>,
+    pub(crate) errors: Vec<ParseDiagnostic>,
+}
+
+pub(crate) struct Watcher {
+    codebase: Codebase<SealedState>,
+    detectors: Vec<CompactDetector>,
+    findings: BTreeSet<Finding>,
+}
+
+impl Watcher {
+    /// Builds the codebase of the corpus and runs the detectors. Files that could not be parsed
+    /// are left out of the codebase and their diagnostics are returned.
+    pub(crate) fn new(
+        corpus: &HashMap<String, String>,
+        detectors: Vec<CompactDetector>,
+    ) -> anyhow::Result<(Self, Vec<ParseDiagnostic>)> {
+        let (codebase, diagnostics) = build_codebase(corpus)?;
+        let mut watcher = Self {
+            codebase: *codebase,
+            detectors,
+            findings: BTreeSet::new(),
+        };
+        watcher.findings = watcher.run_detectors();
+        Ok((watcher, diagnostics))
+    }
+
+    pub(crate) fn codebase(&self) -> &Codebase<SealedState> {
+        &self.codebase
+    }
+
+    pub(crate) fn findings(&self) -> &BTreeSet<Finding> {
+        &self.findings
+    }
+
+    /// Re-parses the changed file, re-runs the detectors and returns the changed findings. If the
+    /// file cannot be parsed, the previous codebase is kept and the diagnostics are returned.
+    pub(crate) fn on_file_changed(&mut self, file_path: &str, source: &str) -> FindingsDiff {
+        let updated = self
+            .codebase
+            .clone_shallow()
+            .replace_file(file_path, source);
+        self.apply(file_path, updated)
+    }
+
+    /// Removes the deleted file, re-runs the detectors and returns the changed findings.
+    pub(crate) fn on_file_removed(&mut self, file_path: &str) -> FindingsDiff {
+        let mut codebase = self.codebase.clone_shallow().unseal();
+        codebase.remove_file(file_path);
+        self.apply(file_path, codebase.reseal())
+    }
+
+    fn apply(
+        &mut self,
+        file_path: &str,
+        updated: anyhow::Result<Codebase<SealedState>>,
+    ) -> FindingsDiff {
+        let codebase = match updated {
+            Ok(codebase) => codebase,
+            Err(error) => {
+                return FindingsDiff {
+                    errors: diagnostics(file_path, &error),
+                    ..FindingsDiff::default()
+                }
+            }
+        };
+        self.codebase = codebase;
+        let findings = self.run_detectors();
+        let diff = FindingsDiff {
+            added: findings.difference(&self.findings).cloned().collect(),
+            resolved: self.findings.difference(&findings).cloned().collect(),
+            errors: Vec::new(),
+        };
+        self.findings = findings;
+        diff
+    }
+
+    fn run_detectors(&self) -> BTreeSet<Finding> {
+        run_detectors(self.codebase(), &self.detectors)
+            .into_iter()
+            .flat_map(|(detector, results)| {
+                results.into_iter().map(move |result| {
+                    (
+                        detector.clone(),
+                        result.file_path,
+                        result.offset_start,
+                        result.offset_end,
+                    )
+                })
+            })
+            .collect()
+    }
+}
+
+/// The diagnostics of a failed update of `file_path`.
+fn diagnostics(file_path: &str, error: &anyhow::Error) -> Vec<ParseDiagnostic> {
+    if let Some(error) = error.downcast_ref::<SealError>() {
+        return error.errors.iter().map(ParseDiagnostic::from).collect();
+    }
+    vec![ParseDiagnostic {
+        file: file_path.to_string(),
+        offset: 0,
+        message: error.to_string(),
+        expected: Vec::new(),
+    }]
+}
+
+pub(crate) fn watch(
+    dir: &Path,
+    detectors: Vec<CompactDetector>,
+    project_root: Option<&PathBuf>,
+    compact: bool,
+) {
+    let code = [dir.to_path_buf()];
+    let corpus = collect_corpus(&code);
+    let mut modified: HashMap<PathBuf, SystemTime> = compact_files(&code)
+        .into_iter()
+        .filter_map(|path| modification_time(&path).map(|time| (path, time)))
+        .collect();
+    let (mut watcher, errors) = match Watcher::new(&corpus, detectors) {
+        Ok(watcher) => watcher,
+        Err(error) => {
+            eprintln!("Failed to build the codebase: {error}");
+            return;
+        }
+    };
+    print_diff(
+        &FindingsDiff {
+            added: watcher.findings().iter().cloned().collect(),
+            resolved: Vec::new(),
+            errors,
+        },
+        project_root,
+        compact,
+    );
+    loop {
+        thread::sleep(POLL_INTERVAL);
+        let files = compact_files(&code);
+        for path in &files {
+            let Some(time) = modification_time(path) else {
+                continue;
+            };
+            if modified.get(path) == Some(&time) {
+                continue;
+            }
+            modified.insert(path.clone(), time);
+            let Ok(source) = std::fs::read_to_string(path) else {
+                continue;
+            };
+            let diff = watcher.on_file_changed(&path.to_string_lossy(), &source);
+            print_diff(&diff, project_root, compact);
+        }
+        let existing: HashSet<&PathBuf> = files.iter().collect();
+        let removed: Vec<PathBuf> = modified
+            .keys()
+            .filter(|path| !existing.contains(path))
+            .cloned()
+            .collect();
+        for path in removed {
+            modified.remove(&path);
+            let diff = watcher.on_file_removed(&path.to_string_lossy());
+            print_diff(&diff, project_root, compact);
+        }
+    }
+}
+
+fn modification_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn print_diff(diff: &FindingsDiff, project_root: Option<&PathBuf>, compact: bool) {
+    if diff.added.is_empty() && diff.resolved.is_empty() && diff.errors.is_empty() {
+        return;
+    }
+    let to_json = |findings: &[Finding]| {
+        findings
+            .iter()
+            .map(|(detector, file_path, offset_start, offset_end)| {
+                json!({
+                    "detector": detector,
+                    "path": relative_file_path(file_path, project_root),
+                    "offset_start": offset_start,
+                    "offset_end": offset_end,
+                })
+            })
+            .collect::<Vec<_>>()
+    };
+    let res = json!({
+        "added": to_json(&diff.added),
+        "resolved": to_json(&diff.resolved),
+        "errors": parse_errors(&diff.errors, project_root),
+    });
+    println!("{}", to_json_string(&res, compact));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::available_detectors;
+
+    fn corpus() -> HashMap<String, String> {
+        let mut corpus = HashMap::new();
+        corpus.insert(
+            "a.compact".to_string(),
+            "export circuit set_admin(new_admin: Bytes<32>): [] {
+                assert admin == pad(32, \"\") \"\";
+                admin = new_admin;
+            }"
+            .to_string(),
+        );
+        corpus.insert(
+            "b.compact".to_string(),
+            "export circuit get_admin(): Bytes<32> {
+                assert admin != pad(32, \"\") \"\";
+                return admin;
+            }"
+            .to_string(),
+        );
+        corpus
+    }
+
+    #[test]
+    fn test_only_changed_file_is_reparsed() {
+        let (mut watcher, _) = Watcher::new(&corpus(), available_detectors()).unwrap();
+        assert_eq!(watcher.findings().len(), 2);
+        let program_id = |watcher: &Watcher, path: &str| {
+            watcher
+                .codebase()
+                .files()
+                .find(|f| f.file_path == path)
+                .map(|f| f.ast().id)
+                .unwrap()
+        };
+        let a_before = program_id(&watcher, "a.compact");
+        let b_before = program_id(&watcher, "b.compact");
+
+        let diff = watcher.on_file_changed(
+            "a.compact",
+            "export circuit set_admin(new_admin: Bytes<32>): [] {
+                assert admin == pad(32, \"\") \"Admin is already set\";
+                admin = new_admin;
+            }",
+        );
+
+        assert_ne!(program_id(&watcher, "a.compact"), a_before);
+        assert_eq!(program_id(&watcher, "b.compact"), b_before);
+        assert!(diff.added.is_empty(), "{diff:?}");
+        assert_eq!(diff.resolved.len(), 1, "{diff:?}");
+        assert_eq!(diff.resolved[0].1, "a.compact");
+        assert_eq!(watcher.findings().len(), 1);
+    }
+
+    #[test]
+    fn test_syntax_error_keeps_previous_codebase() {
+        let (mut watcher, _) = Watcher::new(&corpus(), available_detectors()).unwrap();
+        let diff = watcher.on_file_changed("a.compact", "export circuit set_admin(");
+        assert_eq!(diff.errors.len(), 1, "{diff:?}");
+        assert_eq!(diff.errors[0].file, "a.compact");
+        assert!(diff.added.is_empty() && diff.resolved.is_empty());
+        assert_eq!(watcher.codebase().files().count(), 2);
+        assert_eq!(watcher.findings().len(), 2);
+
+        let diff = watcher.on_file_changed(
+            "a.compact",
+            "export circuit set_admin(new_admin: Bytes<32>): [] {
+                assert admin == pad(32, \"\") \"Admin is already set\";
+                admin = new_admin;
+            }",
+        );
+        assert!(diff.errors.is_empty(), "{diff:?}");
+        assert_eq!(diff.resolved.len(), 1, "{diff:?}");
+    }
+
+    #[test]
+    fn test_removed_file_resolves_its_findings() {
+        let (mut watcher, _) = Watcher::new(&corpus(), available_detectors()).unwrap();
+        let diff = watcher.on_file_removed("b.compact");
+        assert!(diff.errors.is_empty(), "{diff:?}");
+        assert_eq!(diff.resolved.len(), 1, "{diff:?}");
+        assert_eq!(diff.resolved[0].1, "b.compact");
+        assert_eq!(watcher.codebase().files().count(), 1);
+    }
+
+    /// Stands in for a detector loaded with `--load`, reporting the first byte of every file.
+    #[allow(unused_imports)]
+    mod lib_detector {
+        use compact_security_detectors_sdk::{
+            codebase::{Codebase, SealedState},
+            detector::DetectorResult,
+        };
+
+        compact_security_detectors_sdk::detector! {
+            #[type_name = LibDetector]
+            #[id = "lib-detector"]
+            fn check(codebase: &Codebase<SealedState>) -> Option<Vec<DetectorResult>> {
+                Some(
+                    codebase
+                        .files()
+                        .map(|file| DetectorResult {
+                            file_path: file.file_path,
+                            offset_start: 0,
+                            offset_end: 1,
+                            extra: None,
+                        })
+                        .collect(),
+                )
+            }
+        }
+    }
+
+    #[test]
+    fn test_loaded_detector_reports_while_watching() {
+        let detectors: Vec<CompactDetector> = vec![Box::new(lib_detector::LibDetector)];
+        let (mut watcher, _) = Watcher::new(&corpus(), detectors).unwrap();
+        let finding = |file: &str| ("lib-detector".to_string(), file.to_string(), 0, 1);
+        assert!(watcher.findings().contains(&finding("a.compact")));
+        assert!(watcher.findings().contains(&finding("b.compact")));
+
+        let diff = watcher.on_file_changed("c.compact", "export ledger owner: Bytes<32>;");
+        assert_eq!(diff.added, vec![finding("c.compact")], "{diff:?}");
+        let diff = watcher.on_file_removed("a.compact");
+        assert_eq!(diff.resolved, vec![finding("a.compact")], "{diff:?}");
+    }
+}
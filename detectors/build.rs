@@ -84,6 +84,8 @@ fn main() {
             let id = metadata["id"].as_str().unwrap();
             let uid = metadata["uid"].as_str().unwrap();
             let description = metadata["description"].as_str().unwrap_or("");
+            let category = metadata["category"].as_str().unwrap_or("general");
+            let enabled_by_default = metadata["enabled-by-default"].as_bool().unwrap_or(true);
             let report = &metadata["report"];
             let severity = report["severity"].as_str().unwrap_or("note");
             let tags = report["tags"]
@@ -111,6 +113,8 @@ impl DetectorReportTemplate for {type_name} {{
     fn description(&self) -> String {{ "{description}".to_string() }}
     fn severity(&self) -> String {{ "{severity}".to_string() }}
     fn tags(&self) -> Vec<String> {{ vec![{tags}] }}
+    fn category(&self) -> String {{ "{category}".to_string() }}
+    fn enabled_by_default(&self) -> bool {{ {enabled_by_default} }}
     fn title_single_instance(&self) -> String {{ "{title}".to_string() }}
     fn title_multiple_instance(&self) -> String {{ "{title}".to_string() }}
     fn opening(&self) -> String {{ "{opening}".to_string() }}
@@ -129,6 +133,8 @@ impl DetectorReportTemplate for {type_name} {{
                 description = escape_rust_string(description),
                 severity = escape_rust_string(severity),
                 tags = tags,
+                category = escape_rust_string(category),
+                enabled_by_default = enabled_by_default,
                 title = escape_rust_string(title),
                 opening = escape_rust_string(opening),
                 body_list_item = escape_rust_string(body_list_item),
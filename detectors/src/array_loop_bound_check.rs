@@ -87,7 +87,7 @@ mod tests {
         }";
         let mut data = HashMap::new();
         data.insert("test.compact".to_string(), src.to_string());
-        let codebase = build_codebase(&data).unwrap();
+        let (codebase, _) = build_codebase(&data).unwrap();
         let result = detector.check(codebase.as_ref());
         assert!(result.is_some());
         assert_eq!(result.as_ref().unwrap().len(), 1, "{result:?}");
@@ -4,13 +4,17 @@ use compact_security_detectors_sdk::{
         node_type::NodeType, ty::Type,
     },
     codebase::{Codebase, SealedState},
-    detector::{CompactDetector, DetectorOpaque, DetectorReportTemplate, DetectorResult},
+    detector::{CompactDetector, DetectorOpaque, DetectorResult},
 };
 use std::collections::HashMap;
 
 compact_security_detectors_sdk::detector! {
-
     #[type_name = ArrayLoopBoundCheck]
+    #[id = "array-loop-bound-check"]
+    #[uid = "3fTuAe"]
+    #[description = "Detects potential out-of-bounds array index accesses within loops, which can cause runtime errors or unexpected behavior."]
+    #[severity = "medium"]
+    #[tags("audit", "reportable", "compact")]
     fn array_loop_bound_check(
         codebase: &Codebase<SealedState>,
     ) -> Option<Vec<DetectorResult>> {
@@ -42,7 +46,7 @@ compact_security_detectors_sdk::detector! {
                             };
                             errors.push(
                                 DetectorResult {
-                                    file_path: codebase.find_node_file(index_access.id).unwrap().fname,
+                                    file_path: codebase.find_node_file(index_access.id).unwrap().file_path,
                                     offset_start: index_access.location.offset_start,
                                     offset_end: index_access.location.offset_end,
                                     extra: {
@@ -67,79 +71,6 @@ compact_security_detectors_sdk::detector! {
     }
 }
 
-impl DetectorReportTemplate for ArrayLoopBoundCheck {
-    fn id(&self) -> String {
-        String::from("array-loop-bound-check")
-    }
-    fn uid(&self) -> String {
-        String::from("3fTuAe")
-    }
-    fn description(&self) -> String {
-        String::from("Detects potential out-of-bounds array index accesses within loops, which can cause runtime errors or unexpected behavior.")
-    }
-    fn severity(&self) -> String {
-        String::from("medium")
-    }
-    fn tags(&self) -> Vec<String> {
-        vec![
-            String::from("audit"),
-            String::from("reportable"),
-            String::from("compact"),
-        ]
-    }
-    fn title_single_instance(&self) -> String {
-        String::from("Potential Out-of-Bounds Array Index Access Detected")
-    }
-    fn title_multiple_instance(&self) -> String {
-        String::from(
-            "The following potential out-of-bounds array index accesses were found in the code:",
-        )
-    }
-    fn opening(&self) -> String {
-        String::from("Accessing array elements outside their valid index range can lead to runtime errors, security vulnerabilities, or unpredictable program behavior. This issue typically occurs when a loop iterates beyond the array's defined bounds, resulting in unsafe memory access.")
-    }
-    fn body_single_file_single_instance(&self) -> String {
-        String::from("In `$file_name`, a potential out-of-bounds array index access was detected in the `$PARENT_NAME` $PARENT_TYPE on line $instance_line. The array access statement `$ARRAY_INDEX_ACCESS` may exceed the array's valid index range.")
-    }
-    fn body_single_file_multiple_instance(&self) -> String {
-        String::from("In `$file_name`, multiple potential out-of-bounds array index accesses were detected. Review each instance below to ensure array accesses remain within valid bounds.")
-    }
-    fn body_multiple_file_multiple_instance(&self) -> String {
-        String::from("Across $total_files files, multiple potential out-of-bounds array index accesses were detected. Review each instance below to ensure array accesses remain within valid bounds.")
-    }
-    fn body_list_item_single_file(&self) -> String {
-        {
-            "{body_list_item}".to_string()
-        }
-    }
-    fn body_list_item_multiple_file(&self) -> String {
-        {
-            "{body_list_item}".to_string()
-        }
-    }
-    fn closing(&self) -> String {
-        String::from("To resolve this issue, ensure that all array index accesses within loops are properly bounded and do not exceed the array's size. Failing to address this may result in runtime exceptions, data corruption, or exploitable vulnerabilities.")
-    }
-    fn template(&self) -> String {
-        String::from(
-            r#"template:
-      title: Potential Out-of-Bounds Array Index Access Detected
-      opening: Accessing array elements outside their valid index range can lead to runtime errors, security vulnerabilities, or unpredictable program behavior. This issue typically occurs when a loop iterates beyond the array's defined bounds, resulting in unsafe memory access.
-      body-single-file-single-instance: |
-        In `$file_name`, a potential out-of-bounds array index access was detected in the `$PARENT_NAME` $PARENT_TYPE on line $instance_line. The array access statement `$ARRAY_INDEX_ACCESS` may exceed the array's valid index range.
-      body-single-file-multiple-instance: |
-        In `$file_name`, multiple potential out-of-bounds array index accesses were detected. Review each instance below to ensure array accesses remain within valid bounds.
-      body-multiple-file-multiple-instance: |
-        Across $total_files files, multiple potential out-of-bounds array index accesses were detected. Review each instance below to ensure array accesses remain within valid bounds.
-      body-list-item-intro: 'The following potential out-of-bounds array index accesses were found in the code:'
-      body-list-item-always: '- The `$ARRAY_INDEX_ACCESS` statement in the `$PARENT_NAME` $PARENT_TYPE on line $instance_line of [`$file_name`]($instance_line_link)'
-      closing: |
-        To resolve this issue, ensure that all array index accesses within loops are properly bounded and do not exceed the array's size. Failing to address this may result in runtime exceptions, data corruption, or exploitable vulnerabilities.
-"#,
-        )
-    }
-}
-
 #[no_mangle]
 pub extern "C" fn external_detector() -> *mut DetectorOpaque {
     let detector: CompactDetector = Box::new(ArrayLoopBoundCheck);
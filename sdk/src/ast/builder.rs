@@ -25,7 +25,7 @@ use super::{
     node_type::NodeType,
     program::{CompactNode, Program},
     statement::{Assert, Assign, AssignOperator, Block, Const, For, If, Return, Statement},
-    ty::{Bytes, Opaque, Ref, Sum, Type, TypeBool, TypeField, Uint, Vector, VectorSize},
+    ty::{Bytes, Opaque, Ref, Sum, Type, TypeBool, TypeField, TypeMap, Uint, Vector, VectorSize},
 };
 
 /// Builds an AST from the given root node and source code.
@@ -1476,6 +1476,7 @@ fn build_expression(
                 "+" => BinaryExpressionOperator::Add,
                 "-" => BinaryExpressionOperator::Sub,
                 "*" => BinaryExpressionOperator::Mul,
+                "/" => BinaryExpressionOperator::Div,
                 _ => bail!("Invalid binary operator"),
             };
             let binary = Rc::new(Binary {
@@ -2077,6 +2078,20 @@ fn build_type(
                     .collect();
                 generic_parameters = Some(generic_nodes?);
             }
+            if let Some([GArgument::Type(key), GArgument::Type(value)]) =
+                generic_parameters.as_deref()
+            {
+                if ref_name.name == "Map" {
+                    let t_map = Type::Map(Rc::new(TypeMap {
+                        id: node_id,
+                        location: location(node, source),
+                        key: key.clone(),
+                        value: value.clone(),
+                    }));
+                    codebase.add_node(NodeType::Type(t_map.clone()), parent_id);
+                    return Ok(t_map);
+                }
+            }
             let t_ref = Type::Ref(Rc::new(Ref {
                 id: node_id,
                 location: location(node, source),
@@ -2212,6 +2227,33 @@ fn build_type(
     }
 }
 
+/// Parses a standalone Compact type, e.g. `Vector<2, Field>`.
+///
+/// The type is parsed as the type of a synthetic ledger declaration, so the locations of the
+/// returned nodes are relative to that declaration.
+pub(crate) fn parse_type(ty: &str) -> Result<Type> {
+    let source = format!("ledger __type: {ty};");
+    let compact_language = tree_sitter_compact::LANGUAGE.into();
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_language(&compact_language)?;
+    let tree = parser
+        .parse(&source, None)
+        .ok_or_else(|| anyhow!("Failed to parse type: {ty:?}"))?;
+    let root = tree.root_node();
+    if root.has_error() || root.named_child_count() != 1 {
+        bail!("Invalid type: {ty:?}");
+    }
+    let ledger_node = root
+        .named_child(0)
+        .filter(|node| node.kind() == "ldecl")
+        .ok_or_else(|| anyhow!("Invalid type: {ty:?}"))?;
+    let type_node = ledger_node
+        .child_by_field_name("type")
+        .ok_or_else(|| anyhow!("Invalid type: {ty:?}"))?;
+    let mut codebase = Codebase::new();
+    build_type(&mut codebase, &type_node, &source, 0)
+}
+
 fn build_gargument(
     codebase: &mut Codebase<OpenState>,
     node: &Node,
@@ -2464,7 +2506,7 @@ fn build_str(
 }
 
 #[allow(clippy::cast_possible_truncation)]
-fn location(node: &Node, source: &str) -> Location {
+pub(crate) fn location(node: &Node, source: &str) -> Location {
     let offset_start = node.start_byte() as u32;
     let offset_end = node.end_byte() as u32;
     let start_position = node.start_position();
@@ -2515,6 +2557,9 @@ mod tests {
         let source_code_file = SourceCodeFile {
             file_path: fname.to_string(),
             ast,
+            source: Rc::from(content),
+            source_map: Rc::new(crate::source_map::SourceMap::new(content)),
+            comments: Rc::from([]),
         };
         Ok(source_code_file)
     }
@@ -2756,6 +2801,48 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_ledger_map() {
+        let source = "ledger m: Map<Bytes<32>, Uint<64>>;";
+        let source_file = parse_content("dummy", source).unwrap();
+        assert_eq!(source_file.ast.declarations.len(), 1);
+        match &source_file.ast.declarations.first().unwrap() {
+            Declaration::Ledger(ledger) => {
+                assert_eq!(ledger.name(), "m");
+                let Type::Map(map) = &ledger.ty else {
+                    panic!("Expected a map type");
+                };
+                assert_eq!(map.location.source, "Map<Bytes<32>, Uint<64>>");
+                match map.key_type() {
+                    Type::Bytes(bt) => assert_eq!(bt.size.value, 32),
+                    _ => panic!("Expected a bytes key type"),
+                }
+                match map.value_type() {
+                    Type::Uint(ut) => assert_eq!(ut.start.value, 64),
+                    _ => panic!("Expected a uint value type"),
+                }
+            }
+            _ => panic!("Expected a ledger declaration"),
+        }
+
+        let source = "ledger m: Map<Field, Map<Bytes<32>, Boolean>>;";
+        let source_file = parse_content("dummy", source).unwrap();
+        match &source_file.ast.declarations.first().unwrap() {
+            Declaration::Ledger(ledger) => {
+                let Type::Map(map) = &ledger.ty else {
+                    panic!("Expected a map type");
+                };
+                assert!(matches!(map.key_type(), Type::Field(_)));
+                let Type::Map(inner) = map.value_type() else {
+                    panic!("Expected a nested map type");
+                };
+                assert!(matches!(inner.key_type(), Type::Bytes(_)));
+                assert!(matches!(inner.value_type(), Type::Boolean(_)));
+            }
+            _ => panic!("Expected a ledger declaration"),
+        }
+    }
+
     #[test]
     fn test_witness() {
         let source = "witness local_secret_key(): Bytes<32>;";
@@ -3187,4 +3274,32 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_else_if_chain() {
+        let source = "circuit pick(a: Boolean, b: Boolean, c: Boolean): Field { if (a) { return 1; } else if (b) { return 2; } else if (c) { return 3; } else { return 4; } }";
+        let source_file = parse_content("dummy", source).unwrap();
+        let Definition::Circuit(circuit) = source_file.ast.definitions.first().unwrap() else {
+            panic!("Expected a circuit definition");
+        };
+        let body = circuit.body.as_ref().unwrap();
+        assert_eq!(body.statements.len(), 1);
+        let mut ifs = Vec::new();
+        let mut statement = body.statements.first();
+        while let Some(Statement::If(if_stmt)) = statement {
+            ifs.push(if_stmt.clone());
+            statement = if_stmt.else_branch.as_ref();
+        }
+        assert_eq!(ifs.len(), 3);
+        assert!(matches!(statement, Some(Statement::Block(_))));
+        for (if_stmt, name) in ifs.iter().zip(["a", "b", "c"]) {
+            let condition = if_stmt.condition.location();
+            assert_eq!(condition.source, name);
+            let start = source.find(&format!("if ({name})")).unwrap() + 4;
+            assert_eq!(condition.offset_start as usize, start);
+            assert_eq!(condition.offset_end as usize, start + 1);
+            assert!(matches!(if_stmt.then_branch, Statement::Block(_)));
+        }
+        assert_ne!(ifs[0].id, ifs[1].id);
+        assert!(ifs[1].location.offset_start > ifs[0].condition.location().offset_end);
+    }
 }
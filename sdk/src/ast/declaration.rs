@@ -254,6 +254,18 @@ impl Ledger {
     pub fn name(&self) -> String {
         self.name.name.clone()
     }
+
+    /// Checks if the ledger field is declared `export`.
+    #[must_use = "Use this method to check if the ledger field is exported"]
+    pub fn is_exported(&self) -> bool {
+        self.is_exported
+    }
+
+    /// Checks if the ledger field is declared `sealed`, i.e. only writable by the constructor.
+    #[must_use = "Use this method to check if the ledger field is sealed"]
+    pub fn is_sealed(&self) -> bool {
+        self.is_sealed
+    }
 }
 
 impl SymbolNode for Ledger {
@@ -3,7 +3,7 @@ use std::{rc::Rc, vec};
 use crate::{ast::statement::Statement, ast_enum, ast_nodes, ast_nodes_impl};
 
 use super::{
-    declaration::{Argument, Declaration, GArgument, PatternArgument},
+    declaration::{Argument, Declaration, GArgument, Pattern, PatternArgument},
     expression::{Expression, Identifier},
     node::{Node, NodeKind},
     program::CompactNode,
@@ -139,6 +139,34 @@ impl Module {
     pub fn name(&self) -> String {
         self.name.name.clone()
     }
+
+    /// Checks if the module is declared `export`.
+    #[must_use = "This method checks if the module is exported"]
+    pub fn is_exported(&self) -> bool {
+        self.is_exported
+    }
+
+    /// Always `false`: modules cannot be declared `sealed`.
+    #[must_use = "This method checks if the module is sealed"]
+    pub fn is_sealed(&self) -> bool {
+        false
+    }
+
+    /// Returns the ids of the directives, declarations, definitions and nested modules declared
+    /// directly in the module, in source order. Comments are skipped.
+    #[must_use = "This method returns the ids of the module members"]
+    pub fn members(&self) -> Vec<u32> {
+        self.nodes
+            .iter()
+            .filter_map(|node| match node {
+                CompactNode::Directive(directive) => Some(directive.id()),
+                CompactNode::Declaration(declaration) => Some(declaration.id()),
+                CompactNode::Definition(definition) => Some(definition.id()),
+                CompactNode::Module(module) => Some(module.id),
+                CompactNode::Comment(_) => None,
+            })
+            .collect()
+    }
 }
 
 impl Structure {
@@ -147,6 +175,15 @@ impl Structure {
         self.name.name.clone()
     }
 
+    /// Returns the declared type of the field with the given name, if the structure has one.
+    #[must_use = "This method returns the type of a field of the structure"]
+    pub fn field_type(&self, name: &str) -> Option<Type> {
+        self.fields
+            .iter()
+            .find(|field| field.name.name == name)
+            .map(|field| field.ty.clone())
+    }
+
     #[must_use]
     pub fn ty(&self) -> Type {
         Type::Ref(Rc::new(Ref {
@@ -187,17 +224,128 @@ impl Enum {
     }
 }
 
+/// A parameter of a circuit.
+///
+/// # Fields
+///
+/// - `id`: the id of the parameter node.
+/// - `name`: the identifier bound by the parameter, `None` for tuple and struct patterns.
+/// - `ty`: the declared type of the parameter.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Parameter {
+    pub id: u32,
+    pub name: Option<Rc<Identifier>>,
+    pub ty: Type,
+}
+
 impl Circuit {
     #[must_use = "This method returns the name of the circuit"]
     pub fn name(&self) -> String {
         self.name.name.clone()
     }
 
+    /// Checks if the circuit is declared `export`.
+    #[must_use = "This method checks if the circuit is exported"]
+    pub fn is_exported(&self) -> bool {
+        self.is_exported
+    }
+
+    /// Always `false`: circuits cannot be declared `sealed`.
+    #[must_use = "This method checks if the circuit is sealed"]
+    pub fn is_sealed(&self) -> bool {
+        false
+    }
+
     #[must_use = "This method to check if the circuit is external"]
     pub fn is_external(&self) -> bool {
         self.body.is_none()
     }
 
+    /// Returns the parameters of the circuit, in declaration order.
+    #[must_use = "This method returns the parameters of the circuit"]
+    pub fn parameters(&self) -> Vec<Parameter> {
+        self.arguments
+            .iter()
+            .map(|argument| Parameter {
+                id: argument.id,
+                name: match &argument.pattern {
+                    Pattern::Identifier(identifier) => Some(identifier.clone()),
+                    _ => None,
+                },
+                ty: argument.ty.clone(),
+            })
+            .collect()
+    }
+
+    /// Returns the declared return type of the circuit, or `None` if it returns the empty tuple
+    /// `[]`.
+    #[must_use = "This method returns the return type of the circuit"]
+    pub fn return_type(&self) -> Option<Type> {
+        match &self.ty {
+            Type::Sum(sum) if sum.types.is_empty() => None,
+            ty => Some(ty.clone()),
+        }
+    }
+
+    /// Returns the kind of the first statement of the circuit body (see `Statement::kind`), or
+    /// `None` if the circuit has no body or an empty one.
+    #[must_use = "This method returns the kind of the first statement of the circuit"]
+    pub fn first_statement_kind(&self) -> Option<&'static str> {
+        self.body
+            .as_ref()
+            .and_then(|body| body.statements.first())
+            .map(Statement::kind)
+    }
+
+    /// Returns the number of `assert` statements in the circuit body, including nested ones.
+    #[must_use = "This method returns the number of asserts in the circuit"]
+    pub fn assert_count(&self) -> usize {
+        self.body.as_ref().map_or(0, |body| {
+            body.all_statements()
+                .iter()
+                .filter(|stmt| matches!(stmt, Statement::Assert(_)))
+                .count()
+        })
+    }
+
+    /// Returns the number of statements in the circuit body, including nested ones.
+    /// Blocks themselves are not counted.
+    #[must_use = "This method returns the number of statements in the circuit"]
+    pub fn statement_count(&self) -> usize {
+        self.body.as_ref().map_or(0, |body| {
+            body.all_statements()
+                .iter()
+                .filter(|stmt| !matches!(stmt, Statement::Block(_)))
+                .count()
+        })
+    }
+
+    /// Returns the canonical signature of the circuit, e.g.
+    /// `export circuit set_admin(new_admin: Bytes<32>): []`.
+    #[must_use = "This method returns the signature of the circuit"]
+    pub fn signature(&self) -> String {
+        let arguments = self
+            .arguments
+            .iter()
+            .map(|arg| {
+                format!(
+                    "{}: {}",
+                    arg.pattern.location().source,
+                    type_source(&arg.ty)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!(
+            "{}{}circuit {}({}): {}",
+            if self.is_exported { "export " } else { "" },
+            if self.is_pure { "pure " } else { "" },
+            self.name(),
+            arguments,
+            type_source(&self.ty)
+        )
+    }
+
     #[must_use]
     pub fn inline_function_calls(&self) -> Vec<Statement> {
         if let Some(body) = &self.body {
@@ -222,3 +370,17 @@ impl Circuit {
         }
     }
 }
+
+fn type_source(ty: &Type) -> String {
+    match ty {
+        Type::Sum(sum) => format!(
+            "[{}]",
+            sum.types
+                .iter()
+                .map(type_source)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        _ => ty.location().source,
+    }
+}
@@ -4,7 +4,7 @@ use crate::{ast_enum, ast_nodes, ast_nodes_impl};
 
 use super::{
     expression::Identifier,
-    literal::Version,
+    literal::{Version, VersionOperator},
     node::{Node, NodeKind},
 };
 
@@ -41,4 +41,45 @@ impl Pragma {
     pub fn name(&self) -> &str {
         &self.value.name
     }
+
+    /// Returns the version constraint as written in the source, e.g. `>= 0.14.0`.
+    #[must_use]
+    pub fn constraint(&self) -> String {
+        let source = self.location.source.trim().trim_end_matches(';');
+        source
+            .split_once(self.name())
+            .map_or(source, |(_, constraint)| constraint)
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+impl VersionExpr {
+    /// Returns the lowest version satisfying the expression, if the expression has a lower bound.
+    ///
+    /// `>` bounds are treated as inclusive.
+    #[must_use]
+    pub fn minimum_version(&self) -> Option<Rc<Version>> {
+        match self {
+            VersionExpr::Version(version) => match version.operator {
+                VersionOperator::Eq | VersionOperator::Ge | VersionOperator::Gt => {
+                    Some(version.clone())
+                }
+                VersionOperator::Lt | VersionOperator::Le | VersionOperator::Neq => None,
+            },
+            VersionExpr::And(left, right) => {
+                match (left.minimum_version(), right.minimum_version()) {
+                    (Some(l), Some(r)) => Some(if l.triple() >= r.triple() { l } else { r }),
+                    (l, r) => l.or(r),
+                }
+            }
+            VersionExpr::Or(left, right) => {
+                match (left.minimum_version(), right.minimum_version()) {
+                    (Some(l), Some(r)) => Some(if l.triple() <= r.triple() { l } else { r }),
+                    _ => None,
+                }
+            }
+        }
+    }
 }
@@ -32,6 +32,27 @@ ast_enum! {
     }
 }
 
+impl Expression {
+    /// Returns the number of leaf operands of the binary and unary operator tree rooted at the
+    /// expression, e.g. 5 for `a == b || c == d && e`. Sequences count the operands of each of
+    /// their expressions, any other expression is a single operand.
+    #[must_use = "Use this method to get the number of operands of the expression"]
+    pub fn operand_count(&self) -> usize {
+        match self {
+            Expression::Binary(binary) => {
+                binary.left.operand_count() + binary.right.operand_count()
+            }
+            Expression::Unary(unary) => unary.operand.operand_count(),
+            Expression::Sequence(sequence) => sequence
+                .expressions
+                .iter()
+                .map(Expression::operand_count)
+                .sum(),
+            _ => 1,
+        }
+    }
+}
+
 ast_enum! {
     pub enum StructExprArg {
         @raw Expression(Expression),
@@ -100,3 +100,28 @@ ast_nodes_impl! {
         }
     }
 }
+
+impl Str {
+    /// Checks if the string contains a `${...}` interpolation marker.
+    ///
+    /// Compact strings do not support interpolation, such markers are emitted verbatim and
+    /// usually indicate a mistake.
+    #[must_use = "This method checks if the string contains an interpolation marker"]
+    pub fn has_interpolation_marker(&self) -> bool {
+        self.value
+            .match_indices("${")
+            .any(|(index, _)| self.value[index + 2..].contains('}'))
+    }
+}
+
+impl Version {
+    /// Returns the version as a `(major, minor, bugfix)` triple, missing parts are `0`.
+    #[must_use = "This method returns the version as a comparable triple"]
+    pub fn triple(&self) -> (u64, u64, u64) {
+        (
+            self.major.value,
+            self.minor.as_ref().map_or(0, |minor| minor.value),
+            self.bugfix.as_ref().map_or(0, |bugfix| bugfix.value),
+        )
+    }
+}
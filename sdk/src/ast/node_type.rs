@@ -37,6 +37,7 @@ pub enum NodeType {
 }
 
 impl NodeType {
+    /// Returns the id of the wrapped node, regardless of its variant.
     #[must_use]
     pub fn id(&self) -> u32 {
         match self {
@@ -58,6 +59,7 @@ impl NodeType {
         }
     }
 
+    /// Returns the source location of the wrapped node, regardless of its variant.
     #[must_use]
     pub fn location(&self) -> Location {
         match self {
@@ -79,9 +81,53 @@ impl NodeType {
         }
     }
 
+    /// Returns the name of the concrete AST node type, e.g. `Circuit` or `Identifier`.
+    #[must_use]
+    pub fn kind_name(&self) -> String {
+        let kind = match self {
+            NodeType::Program(node) => NodeKind::from(node),
+            NodeType::Statement(node) => NodeKind::from(node),
+            NodeType::Declaration(node) => NodeKind::from(node),
+            NodeType::Definition(node) => NodeKind::from(node),
+            NodeType::Directive(node) => NodeKind::from(node),
+            NodeType::Expression(node) => NodeKind::from(node),
+            NodeType::Function(node) => NodeKind::from(node),
+            NodeType::FunctionArgument(node) => NodeKind::from(node),
+            NodeType::Literal(node) => NodeKind::from(node),
+            NodeType::Type(node) => NodeKind::from(node),
+            NodeType::VectorSize(node) => NodeKind::from(node),
+            NodeType::Pattern(node) => NodeKind::from(node),
+            NodeType::GArgument(node) => NodeKind::from(node),
+            NodeType::StructExprArg(node) => NodeKind::from(node),
+            NodeType::StructArgument(node) => NodeKind::from(node),
+        };
+        match kind {
+            NodeKind::NewScope(node) | NodeKind::SameScopeNode(SameScopeNode::Composite(node)) => {
+                node.node_type_name()
+            }
+            NodeKind::SameScopeNode(SameScopeNode::Symbol(node)) => (*node).node_type_name(),
+        }
+    }
+
     #[must_use]
     pub fn children(&self) -> Vec<NodeType> {
-        let node_children: Vec<Rc<NodeKind>> = match self {
+        self.node_children()
+            .into_iter()
+            .map(convert_nodekind_to_nodetype)
+            .collect()
+    }
+
+    /// Returns the ids of the direct children of the wrapped node, in source order.
+    #[must_use]
+    pub fn child_ids(&self) -> Vec<u32> {
+        self.node_children()
+            .iter()
+            .map(|child| child.id())
+            .collect()
+    }
+
+    fn node_children(&self) -> Vec<Rc<NodeKind>> {
+        match self {
             NodeType::Program(node) => node.children(),
             NodeType::Statement(node) => node.children(),
             NodeType::Declaration(node) => node.children(),
@@ -97,11 +143,7 @@ impl NodeType {
             NodeType::FunctionArgument(node) => node.children(),
             NodeType::VectorSize(node) => node.children(),
             NodeType::StructArgument(node) => node.children(),
-        };
-        node_children
-            .into_iter()
-            .map(convert_nodekind_to_nodetype)
-            .collect()
+        }
     }
 }
 
@@ -3,10 +3,10 @@ use std::rc::Rc;
 use crate::{ast_node, ast_node_impl};
 
 use super::{
-    declaration::{Constructor, Declaration},
+    declaration::{Constructor, Declaration, Import},
     definition::{Circuit, Definition, Module},
     directive::Directive,
-    literal::{Literal, Str},
+    literal::{Literal, Str, Version},
     node::{Node, NodeKind, SameScopeNode},
 };
 
@@ -74,6 +74,34 @@ impl Program {
             .collect()
     }
 
+    /// Returns the import declarations of the program file.
+    #[must_use = "Use this function to get the imports of the program file"]
+    pub fn imports(&self) -> Vec<&Rc<Import>> {
+        self.declarations
+            .iter()
+            .filter_map(|d| {
+                if let Declaration::Import(import) = d {
+                    Some(import)
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the minimum language version required by the `language_version` pragma, if any.
+    #[must_use = "Use this function to get the language version of the program file"]
+    pub fn language_version(&self) -> Option<Rc<Version>> {
+        self.directives
+            .iter()
+            .find_map(|directive| match directive {
+                Directive::Pragma(pragma) if pragma.name() == "language_version" => {
+                    pragma.version.minimum_version()
+                }
+                Directive::Pragma(_) => None,
+            })
+    }
+
     #[must_use]
     pub fn constructors(&self) -> Vec<Rc<Constructor>> {
         self.declarations
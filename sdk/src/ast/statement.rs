@@ -175,6 +175,61 @@ impl SymbolNode for Const {
     }
 }
 
+impl Statement {
+    /// Returns the kind of the statement: `assign`, `assert`, `block`, `const`, `var`,
+    /// `expression`, `if`, `for` or `return`.
+    #[must_use = "Use this method to get the kind of the statement"]
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Statement::Assign(_) => "assign",
+            Statement::Assert(_) => "assert",
+            Statement::Block(_) => "block",
+            Statement::Const(_) => "const",
+            Statement::Var(_) => "var",
+            Statement::ExpressionSequence(_) | Statement::Expression(_) => "expression",
+            Statement::If(_) => "if",
+            Statement::For(_) => "for",
+            Statement::Return(_) => "return",
+        }
+    }
+}
+
+impl Block {
+    /// Returns all statements of the block, including statements nested in inner blocks,
+    /// `if` branches and `for` bodies, in source order.
+    #[must_use = "Use this method to get all nested statements of the block"]
+    pub fn all_statements(&self) -> Vec<Statement> {
+        let mut statements = Vec::new();
+        for stmt in &self.statements {
+            collect_statements(stmt, &mut statements);
+        }
+        statements
+    }
+}
+
+fn collect_statements(stmt: &Statement, statements: &mut Vec<Statement>) {
+    statements.push(stmt.clone());
+    match stmt {
+        Statement::Block(block) => {
+            for inner in &block.statements {
+                collect_statements(inner, statements);
+            }
+        }
+        Statement::If(if_stmt) => {
+            collect_statements(&if_stmt.then_branch, statements);
+            if let Some(else_branch) = &if_stmt.else_branch {
+                collect_statements(else_branch, statements);
+            }
+        }
+        Statement::For(for_stmt) => {
+            for inner in &for_stmt.body.statements {
+                collect_statements(inner, statements);
+            }
+        }
+        _ => {}
+    }
+}
+
 impl Assert {
     #[must_use]
     pub fn message(&self) -> Option<String> {
@@ -182,6 +237,38 @@ impl Assert {
     }
 }
 
+impl Return {
+    /// Checks if the statement returns the empty tuple, i.e. `return [];`.
+    #[must_use = "Use this method to check if the statement returns the empty tuple"]
+    pub fn is_empty_tuple(&self) -> bool {
+        let mut value = self.value.as_ref();
+        while let Some(Expression::Sequence(sequence)) = value {
+            if sequence.expressions.len() != 1 {
+                return false;
+            }
+            value = sequence.expressions.first();
+        }
+        matches!(value, Some(Expression::Literal(Literal::Array(array))) if array.elements.is_empty())
+    }
+}
+
+impl If {
+    #[must_use]
+    pub fn condition_id(&self) -> u32 {
+        self.condition.id()
+    }
+
+    #[must_use]
+    pub fn then_branch_id(&self) -> u32 {
+        self.then_branch.id()
+    }
+
+    #[must_use]
+    pub fn else_branch_id(&self) -> Option<u32> {
+        self.else_branch.as_ref().map(Statement::id)
+    }
+}
+
 impl For {
     #[must_use]
     #[allow(clippy::missing_panics_doc)]
@@ -195,4 +282,19 @@ impl For {
         let (_, end) = self.range.as_ref().unwrap();
         Some(end.value)
     }
+
+    /// Returns the first value of the counter of a `for (const i of start .. end)` loop, or `None`
+    /// for a loop over the elements of a vector.
+    #[must_use = "Use this method to get the lower bound of the loop range"]
+    pub fn lower_bound_nat(&self) -> Option<u64> {
+        self.range.as_ref().map(|(start, _)| start.value)
+    }
+
+    /// Checks if the upper bound of the loop range is part of the range. Compact ranges
+    /// `start .. end` exclude `end` and the language has no inclusive range form, so this is
+    /// always `false`, and `upper_bound_nat() - lower_bound_nat()` is the number of iterations.
+    #[must_use = "Use this method to check if the loop range includes its upper bound"]
+    pub fn is_inclusive(&self) -> bool {
+        false
+    }
 }
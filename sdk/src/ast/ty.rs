@@ -5,7 +5,7 @@ use super::{
     node::{Node, NodeKind},
 };
 use crate::{ast_enum, ast_nodes, ast_nodes_impl};
-use std::{fmt::Display, rc::Rc};
+use std::{fmt::Display, ops::RangeInclusive, rc::Rc};
 
 ast_enum! {
     pub enum Type {
@@ -19,6 +19,7 @@ ast_enum! {
         Bytes(Rc<Bytes>),
         Ref(Rc<Ref>),
         Sum(Rc<Sum>),
+        Map(Rc<TypeMap>),
     }
 }
 
@@ -30,21 +31,88 @@ ast_enum! {
 }
 
 impl Type {
+    /// Parses a type from its Compact source representation, e.g. `Vector<2, Field>`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the string is not a valid Compact type.
+    pub fn parse(ty: &str) -> anyhow::Result<Type> {
+        super::builder::parse_type(ty)
+    }
+
+    /// Checks if both types are of the same kind. Map types additionally require their key and
+    /// value types to match, recursively, and `Uint` types require one range to be contained in
+    /// the other, e.g. `Uint<8>` matches `Uint<0..255>` and `Uint<16>` but not `Uint<256..300>`.
     #[must_use]
     pub fn matches(&self, ty: &Type) -> bool {
-        matches!(
-            (self, ty),
+        match (self, ty) {
+            (Type::Map(left), Type::Map(right)) => {
+                left.key.matches(&right.key) && left.value.matches(&right.value)
+            }
+            (Type::Uint(left), Type::Uint(right)) => left.contains(right) || right.contains(left),
+            _ => matches!(
+                (self, ty),
+                (Type::Nat(_), Type::Nat(_))
+                    | (Type::Boolean(_), Type::Boolean(_))
+                    | (Type::String(_), Type::String(_))
+                    | (Type::Field(_), Type::Field(_))
+                    | (Type::Vector(_), Type::Vector(_))
+                    | (Type::Opaque(_), Type::Opaque(_))
+                    | (Type::Bytes(_), Type::Bytes(_))
+                    | (Type::Ref(_), Type::Ref(_))
+                    | (Type::Sum(_), Type::Sum(_))
+            ),
+        }
+    }
+
+    /// Checks if both types denote the same type, ignoring node ids and source locations, e.g.
+    /// `Uint<8>` equals `Uint<8>` but not `Uint<16>`.
+    #[must_use = "Use this method to compare two types"]
+    pub fn structurally_eq(&self, ty: &Type) -> bool {
+        match (self, ty) {
             (Type::Nat(_), Type::Nat(_))
-                | (Type::Boolean(_), Type::Boolean(_))
-                | (Type::String(_), Type::String(_))
-                | (Type::Field(_), Type::Field(_))
-                | (Type::Uint(_), Type::Uint(_))
-                | (Type::Vector(_), Type::Vector(_))
-                | (Type::Opaque(_), Type::Opaque(_))
-                | (Type::Bytes(_), Type::Bytes(_))
-                | (Type::Ref(_), Type::Ref(_))
-                | (Type::Sum(_), Type::Sum(_))
-        )
+            | (Type::Boolean(_), Type::Boolean(_))
+            | (Type::String(_), Type::String(_))
+            | (Type::Field(_), Type::Field(_)) => true,
+            (Type::Uint(left), Type::Uint(right)) => {
+                left.start.value == right.start.value
+                    && left.end.as_ref().map(|end| end.value)
+                        == right.end.as_ref().map(|end| end.value)
+            }
+            (Type::Vector(left), Type::Vector(right)) => {
+                let same_size = match (&left.size, &right.size) {
+                    (VectorSize::Nat(left), VectorSize::Nat(right)) => left.value == right.value,
+                    (VectorSize::Ref(left), VectorSize::Ref(right)) => left.name == right.name,
+                    _ => false,
+                };
+                same_size && left.ty.structurally_eq(&right.ty)
+            }
+            (Type::Opaque(left), Type::Opaque(right)) => left.value.value == right.value.value,
+            (Type::Bytes(left), Type::Bytes(right)) => left.size.value == right.size.value,
+            (Type::Ref(left), Type::Ref(right)) => {
+                let arguments = |ty: &Ref| {
+                    ty.generic_parameters.as_ref().map(|parameters| {
+                        parameters
+                            .iter()
+                            .map(|parameter| parameter.location().source.clone())
+                            .collect::<Vec<_>>()
+                    })
+                };
+                left.name() == right.name() && arguments(left) == arguments(right)
+            }
+            (Type::Sum(left), Type::Sum(right)) => {
+                left.types.len() == right.types.len()
+                    && left
+                        .types
+                        .iter()
+                        .zip(&right.types)
+                        .all(|(left, right)| left.structurally_eq(right))
+            }
+            (Type::Map(left), Type::Map(right)) => {
+                left.key.structurally_eq(&right.key) && left.value.structurally_eq(&right.value)
+            }
+            _ => false,
+        }
     }
 }
 
@@ -61,6 +129,7 @@ impl Display for Type {
             Type::Bytes(_) => write!(f, "bytes"),
             Type::Ref(_) => write!(f, "ref"),
             Type::Sum(_) => write!(f, "sum"),
+            Type::Map(_) => write!(f, "map"),
         }
     }
 }
@@ -95,6 +164,10 @@ ast_nodes! {
     pub struct Sum {
         pub types: Vec<Type>,
     }
+    pub struct TypeMap {
+        pub key: Type,
+        pub value: Type,
+    }
 }
 
 ast_nodes_impl! {
@@ -148,6 +221,11 @@ ast_nodes_impl! {
             vec![]
         }
     }
+    impl Node for TypeMap {
+        fn children(&self) -> Vec<Rc<NodeKind>> {
+            vec![]
+        }
+    }
 }
 
 impl TypeNat {
@@ -187,6 +265,39 @@ impl Ref {
     }
 }
 
+impl Uint {
+    /// Returns the inclusive range of values of the type: `0..=2^N - 1` for `Uint<N>` and
+    /// `A..=B` for `Uint<A..B>`. Bounds that do not fit in a `u128` saturate to `u128::MAX`.
+    #[must_use = "Use this method to get the range of values of the type"]
+    pub fn range(&self) -> RangeInclusive<u128> {
+        if let Some(end) = &self.end {
+            return u128::from(self.start.value)..=u128::from(end.value);
+        }
+        let max = u32::try_from(self.start.value)
+            .ok()
+            .and_then(|bits| 1u128.checked_shl(bits))
+            .map_or(u128::MAX, |bound| bound - 1);
+        0..=max
+    }
+
+    /// Returns the number of bits needed to represent every value of the type: `N` for `Uint<N>`
+    /// and the bit length of `B` for `Uint<A..B>`. Widths that do not fit in a `u32` saturate.
+    #[must_use = "Use this method to get the bit width of the type"]
+    pub fn bit_width(&self) -> u32 {
+        match &self.end {
+            Some(end) => u64::BITS - end.value.leading_zeros(),
+            None => u32::try_from(self.start.value).unwrap_or(u32::MAX),
+        }
+    }
+
+    /// Checks if every value of `other` is also a value of this type.
+    #[must_use = "Use this method to check if a range is contained in the type"]
+    pub fn contains(&self, other: &Uint) -> bool {
+        let (range, other) = (self.range(), other.range());
+        range.start() <= other.start() && other.end() <= range.end()
+    }
+}
+
 impl Vector {
     #[must_use]
     pub fn size_nat(&self) -> Option<u64> {
@@ -196,3 +307,25 @@ impl Vector {
         }
     }
 }
+
+impl Bytes {
+    /// Returns the number of bytes of the type, e.g. 32 for `Bytes<32>`.
+    #[must_use = "Use this method to get the width of the bytes type"]
+    pub fn width(&self) -> u64 {
+        self.size.value
+    }
+}
+
+impl TypeMap {
+    /// Returns the key type of the map, e.g. `Bytes<32>` for `Map<Bytes<32>, Uint<64>>`.
+    #[must_use = "Use this method to get the key type of the map"]
+    pub fn key_type(&self) -> &Type {
+        &self.key
+    }
+
+    /// Returns the value type of the map, e.g. `Uint<64>` for `Map<Bytes<32>, Uint<64>>`.
+    #[must_use = "Use this method to get the value type of the map"]
+    pub fn value_type(&self) -> &Type {
+        &self.value
+    }
+}
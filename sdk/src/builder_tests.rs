@@ -12,7 +12,7 @@ use crate::{
 fn build_codebase_wrapper(src: &str) -> Box<Codebase<SealedState>> {
     let mut files = HashMap::new();
     files.insert("dummy".to_string(), src.to_string());
-    build_codebase(&files).unwrap()
+    build_codebase(&files).unwrap().0
 }
 
 #[allow(dead_code)]
@@ -856,6 +856,31 @@ mod external_parsing_tests {
         assert!(circuit.body.is_none());
     }
 
+    #[test]
+    fn circuit_parameters_and_return_type() {
+        let codebase = build_codebase_wrapper(
+            "circuit empty () : [];
+            circuit compute (x: Field, [a, b]: [Field, Boolean]) : Uint<32>;",
+        );
+        let source_file = codebase.files.iter().find(|f| f.file_path == "dummy").unwrap();
+        let circuits = source_file.ast.circuits();
+        assert_eq!(circuits.len(), 2);
+
+        let empty = circuits.iter().find(|c| c.name() == "empty").unwrap();
+        assert!(empty.parameters().is_empty());
+        assert!(empty.return_type().is_none());
+
+        let compute = circuits.iter().find(|c| c.name() == "compute").unwrap();
+        let parameters = compute.parameters();
+        assert_eq!(parameters.len(), 2);
+        assert_eq!(parameters[0].id, compute.arguments[0].id);
+        assert_eq!(parameters[0].name.as_ref().unwrap().name, "x");
+        assert!(matches!(parameters[0].ty, Type::Field(_)));
+        assert!(parameters[1].name.is_none());
+        assert!(matches!(parameters[1].ty, Type::Sum(_)));
+        assert!(matches!(compute.return_type(), Some(Type::Uint(_))));
+    }
+
     #[test]
     fn circuit_with_vector_return_type() {
         let codebase = build_codebase_wrapper("circuit build (a: Field) : Vector<10, Field>;");
@@ -1158,6 +1183,50 @@ mod ledger_parsing_tests {
         }
     }
 
+    #[test]
+    fn ledger_circuit_and_module_modifiers() {
+        let codebase = build_codebase_wrapper(
+            "ledger a : Field;
+            export ledger b : Field;
+            sealed ledger c : Field;
+            export sealed ledger d : Field;
+            circuit f () : [] {}
+            export circuit g () : [] {}
+            module M {}
+            export module N {}",
+        );
+        let source_file = codebase.files.iter().find(|f| f.file_path == "dummy").unwrap();
+        let ast = &source_file.ast;
+        let modifiers: Vec<(String, bool, bool)> = ast
+            .declarations
+            .iter()
+            .filter_map(|declaration| match declaration {
+                Declaration::Ledger(ledger) => {
+                    Some((ledger.name(), ledger.is_exported(), ledger.is_sealed()))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            modifiers,
+            vec![
+                ("a".to_string(), false, false),
+                ("b".to_string(), true, false),
+                ("c".to_string(), false, true),
+                ("d".to_string(), true, true),
+            ]
+        );
+        for circuit in ast.circuits() {
+            assert_eq!(circuit.is_exported(), circuit.name() == "g");
+            assert!(!circuit.is_sealed());
+        }
+        assert_eq!(ast.modules.len(), 2);
+        for module in &ast.modules {
+            assert_eq!(module.is_exported(), module.name() == "N");
+            assert!(!module.is_sealed());
+        }
+    }
+
     #[test]
     fn ledger_with_vector_type() {
         let codebase = build_codebase_wrapper("ledger myLedger : Vector<10, Boolean>;");
@@ -1962,6 +2031,23 @@ mod statements_parsing_tests {
         }
     }
 
+    #[test]
+    fn for_loop_bounds() {
+        let codebase =
+            build_codebase_wrapper("circuit foo(): Bool { for (const i of 2 .. 10) { x = i; } }");
+        let source_file = codebase.files.iter().find(|f| f.file_path == "dummy").unwrap();
+        let circuits = source_file.ast.circuits();
+        let statement = circuits[0].body.as_ref().unwrap().statements.first().unwrap();
+        let Statement::For(for_stmt) = statement else {
+            panic!("Expected for loop statement");
+        };
+        assert_eq!(for_stmt.lower_bound_nat(), Some(2));
+        assert_eq!(for_stmt.upper_bound_nat(), Some(10));
+        assert!(!for_stmt.is_inclusive());
+        let iterations = for_stmt.upper_bound_nat().unwrap() - for_stmt.lower_bound_nat().unwrap();
+        assert_eq!(iterations, 8);
+    }
+
     #[test]
     fn assert_statement() {
         let codebase = build_codebase_wrapper(
@@ -2774,6 +2860,60 @@ mod statements_parsing_tests {
         }
     }
 
+    #[test]
+    fn nested_conditional_expression() {
+        let codebase =
+            build_codebase_wrapper("circuit foo(a: Boolean, c: Boolean): Field { return a ? 1 : c ? 2 : 3; }");
+        let source_file = codebase.files.iter().find(|f| f.file_path == "dummy").unwrap();
+        let circuits = source_file.ast.circuits();
+        let statement = circuits.first().unwrap().body.as_ref().unwrap().statements.first().unwrap();
+        let Statement::Return(return_stmt) = statement else {
+            panic!("Expected return statement");
+        };
+        let Some(Expression::Sequence(sequence)) = &return_stmt.value else {
+            panic!("Expected sequence expression");
+        };
+        let Expression::Conditional(outer) = &sequence.expressions[0] else {
+            panic!("Expected ternary expression");
+        };
+        assert_eq!(outer.location.source, "a ? 1 : c ? 2 : 3");
+        assert!(matches!(&outer.condition, Expression::Identifier(ident) if ident.name == "a"));
+        assert!(matches!(&outer.then_branch, Expression::Literal(Literal::Nat(nat)) if nat.value == 1));
+        let Expression::Conditional(inner) = &outer.else_branch else {
+            panic!("Expected nested ternary expression in the else branch");
+        };
+        assert_eq!(inner.location.source, "c ? 2 : 3");
+        assert!(matches!(&inner.condition, Expression::Identifier(ident) if ident.name == "c"));
+        assert!(matches!(&inner.then_branch, Expression::Literal(Literal::Nat(nat)) if nat.value == 2));
+        assert!(matches!(&inner.else_branch, Expression::Literal(Literal::Nat(nat)) if nat.value == 3));
+    }
+
+    #[test]
+    fn expression_operand_count() {
+        let codebase = build_codebase_wrapper(
+            "circuit foo(a: Field, b: Field, c: Field, d: Field, e: Boolean): Boolean { return a == b || c == d && e; }
+            circuit bar(a: Field): Field { return a; }",
+        );
+        let source_file = codebase.files.iter().find(|f| f.file_path == "dummy").unwrap();
+        let circuits = source_file.ast.circuits();
+        let returned = |index: usize| {
+            let statement = circuits[index].body.as_ref().unwrap().statements.first().unwrap().clone();
+            let Statement::Return(return_stmt) = statement else {
+                panic!("Expected return statement");
+            };
+            let Some(Expression::Sequence(sequence)) = &return_stmt.value else {
+                panic!("Expected sequence expression");
+            };
+            sequence.expressions[0].clone()
+        };
+        let condition = returned(0);
+        assert!(matches!(condition, Expression::Binary(_)));
+        assert_eq!(condition.operand_count(), 5);
+        let identifier = returned(1);
+        assert!(matches!(identifier, Expression::Identifier(_)));
+        assert_eq!(identifier.operand_count(), 1);
+    }
+
     #[test]
     fn expression_statement_logical_or() {
         let codebase = build_codebase_wrapper("circuit foo(): Bool { x || y; }");
@@ -5,8 +5,9 @@
 ///
 /// # Overview
 ///
-/// The `Codebase` struct is a generic container that operates in two states:
+/// The `Codebase` struct is a generic container that operates in three states:
 /// - `OpenState`: Allows modifications such as adding files and nodes.
+/// - `ParsedState`: Holds the ASTs of the files only, without symbol tables or pass results.
 /// - `SealedState`: Prevents further modifications and provides read-only access to the codebase.
 ///
 /// The module also defines traits (`CodebaseOpen` and `CodebaseSealed`) to enforce state-specific
@@ -24,16 +25,104 @@
 /// - `new`: Creates a new `Codebase` in the open state.
 /// - `add_file`: Parses and adds a source code file to the codebase.
 /// - `add_node`: Adds a node to the codebase's storage.
+/// - `add_prelude_file`: Adds a file whose circuits are visible everywhere and that is not reported.
+/// - `register_pass`: Registers a custom analysis pass that runs when the codebase is sealed.
 /// - `seal`: Seals the codebase, preventing further modifications and building symbol tables.
+///   Fails with a `SealError` listing every file that could not be parsed.
+/// - `seal_with_diagnostics`: Seals the parsed files and returns a `ParseDiagnostic` for every file
+///   that could not be parsed.
+/// - `update_file`: Re-parses a single file, keeping the node ids of the other files.
+/// - `remove_file`: Removes a file and its nodes, keeping the node ids of the other files.
+/// - `reseal`: Seals a codebase opened with `unseal`, rebuilding the cross-file indices.
+///
+/// ## Codebase<ParsedState>
+/// - `parse_only`: Parses files into ASTs, skipping symbol tables and analysis passes.
+/// - `files`: Returns an iterator over all source code files in the codebase.
+/// - `seal`: Builds the symbol tables and runs the passes, producing a sealed codebase.
 ///
 /// ## Codebase<SealedState>
+/// - `replace_file`: Re-parses a single file and re-seals the codebase.
+/// - `unseal`: Opens the codebase for `update_file`, keeping its files and nodes.
+/// - `rename_file`: Moves a file to a new path without re-parsing it.
+/// - `clone_shallow`: Copies the codebase, sharing its AST nodes with the original.
+/// - `to_json`, `from_json`: Serialize a sealed codebase to JSON and restore it.
+/// - `is_prelude_file`: Checks if a file was added as a prelude file.
+/// - `reportable_results`: Drops detector results reported in prelude files.
+/// - `is_suppressed`, `unsuppressed_results`: Honor `compact-disable-line` and `compact-disable-next-line` comments.
+/// - `comments_of_file`: Returns the comments of a file.
+/// - `comments_for`: Returns the comments leading a node.
+/// - `doc_comment`: Returns the documentation of a declaration, from its leading comments.
 /// - `files`: Returns an iterator over all source code files in the codebase.
+/// - `source_of_file`: Returns the source code of a file.
+/// - `pass_result`: Retrieves the result of a registered analysis pass by its type.
 /// - `get_symbol_type_by_id`: Retrieves the type of a symbol by its ID.
+/// - `get_symbol_definition`: Resolves an identifier to the id of its declaration.
+///   Module-qualified names such as `Token.mint` resolve to the file imported as `Token`.
+/// - `find_references`: Lists the identifiers resolving to a declaration.
+/// - `find_node_by_id`: Retrieves a node by its ID in constant time.
 /// - `list_assert_nodes`: Lists all `Assert` statement nodes in the codebase.
 /// - `list_for_statement_nodes`: Lists all `For` statement nodes in the codebase.
+/// - `list_if_statement_nodes`: Lists all `If` statement nodes in the codebase.
+/// - `list_return_nodes`: Lists all `Return` statement nodes in the codebase.
+/// - `for_statements_with_files`: Lists all `For` statement nodes paired with their file path.
+/// - `list_interpolated_strings`: Lists all string literals containing a `${...}` marker.
 /// - `list_exported_circuits_from_program`: Lists all exported circuits in a program.
 /// - `list_non_exported_circuits_from_program`: Lists all non-exported circuits in a program.
 /// - `get_parent_container`: Retrieves the parent container (e.g., module or circuit) of a node.
+/// - `get_ancestors`: Retrieves all ancestors of a node, from its parent up to the program.
+/// - `circuit_at`: Retrieves the innermost circuit enclosing an offset in a file.
+/// - `circuit_by_name_in_file`: Resolves a circuit name as seen from a file.
+/// - `circuits_calling`: Lists all circuits calling a function by name.
+/// - `compact_version_report`: Groups files by their declared language version.
+/// - `calls_to_deprecated`: Lists calls to functions deprecated at or before the file's language
+///   version.
+/// - `calls_with_literal_args`: Lists the calls to a function with their literal arguments.
+/// - `file_dependency_graph`: Builds the graph of imports between files.
+/// - `reexported_symbols`: Lists the imported symbols a file re-exports, with their origin file.
+/// - `exit_points`: Lists the `return` statements of a circuit and its implicit end.
+/// - `circuit_effects`: Summarizes the ledger reads and writes, calls and asserts of a circuit.
+/// - `ledger_write_shadowed_by_local`: Lists assignments to a local named like a ledger field.
+/// - `parameters_shadowing_outer`: Lists circuit parameters named like a visible ledger field.
+/// - `circuits_writing_ledger_without_assert`: Lists exported circuits writing the ledger with no
+///   `assert`.
+/// - `guarding_asserts`: Lists the asserts executed on every path to a ledger write.
+/// - `write_before_any_assert`: Finds the first ledger write of a circuit preceding its first assert.
+/// - `asserts_after_write`: Lists asserts placed after a ledger write in the same circuit.
+/// - `weak_asserts`: Lists asserts with a missing or blank message or a constant condition.
+/// - `unchecked_witness_flows`: Lists witness results reaching a ledger write or a return unchecked.
+/// - `identifiers_matching_reserved`: Lists declarations whose name is in a reserved word set.
+/// - `type_of_expression`: Returns the type of an expression, when it is known.
+/// - `redundant_casts`: Lists casts to the type the expression already has.
+/// - `mismatched_returns`: Lists returns whose value does not match the circuit return type.
+/// - `integer_divisions`: Lists divisions whose operands are `Uint` or `Field` values.
+/// - `expression_result_width`: Computes the bits needed by the result of an arithmetic on `Uint`s.
+/// - `boolean_literal_comparisons`: Lists `==` and `!=` comparisons against `true` or `false`.
+/// - `oversized_collections`: Lists `Vector` and `Bytes` types with more elements than a limit.
+/// - `is_constant_expression`: Checks if an expression only depends on literals and constants.
+/// - `unused_parameters`: Lists the parameters of a circuit never referenced in its body.
+/// - `fan_in`: Counts the distinct circuits calling a circuit.
+/// - `unreachable_circuits`: Lists the non-exported circuits not reachable from any entry point.
+/// - `fan_out`: Counts the distinct circuits called by a circuit.
+/// - `uses_witness`: Checks if a circuit calls a witness, directly or through the circuits it calls.
+/// - `circuit_contains_loop`: Checks if a circuit body contains a `for` loop at any depth.
+/// - `shadows_builtin`: Checks if a declaration shadows a builtin function or a declared circuit.
+/// - `snippet`: Renders the source lines of a node with surrounding context and an underline.
+/// - `assert_density`: Computes the number of asserts per statement in a circuit.
+/// - `statement_histogram`: Counts the statements of all circuit bodies by kind.
+/// - `stats`: Counts the files, circuits, modules, ledgers, asserts, loops and nodes of the codebase.
+/// - `circuits_exceeding_statements`: Lists the ids of circuits with more statements than a limit.
+/// - `circuit_signatures`: Lists the canonical signatures of all circuits.
+/// - `node_path`: Returns the chain of nodes containing an offset in a file, for breadcrumbs.
+/// - `line_of`, `column_of`: Return the 1-based line and character column where a node starts.
+/// - `normalized_source`: Returns the source text of a node with whitespace runs collapsed.
+/// - `line_col`: Converts a byte offset of a file into a 1-based line and character column.
+/// - `dump_ast`: Renders the AST of a file as an indented tree, for debugging.
+/// - `to_ctags`: Exports circuits, ledgers, structs, enums and witnesses in the ctags `tags` format.
+/// - `children_of`: Lists the direct children of a node in source order.
+/// - `descendants_of`: Iterates over the subtree of a node in pre-order.
+/// - `walk`: Runs a `Visitor` over the ASTs of all files in a single pass.
+/// - `get_children_cmp`: Lists the nodes of a subtree, including its root, matching a predicate.
+/// - `list_nodes_by_kind`: Lists the nodes of the whole codebase matching a predicate, by id.
 ///
 /// # Internal Functionality
 ///
@@ -51,23 +140,98 @@
 /// - Panics are used in cases where critical errors occur, such as failing to load the grammar.
 use crate::{
     ast::{
-        builder::build_ast,
-        declaration::Declaration,
+        builder::{build_ast, location},
+        declaration::{Declaration, Pattern},
         definition::{Circuit, Definition, Module},
-        expression::Expression,
+        directive::Directive,
+        expression::{
+            Binary, BinaryExpressionOperator, Expression, Identifier, MemberAccess, StructExprArg,
+            UnaryExpressionOperator,
+        },
         function::Function,
-        node::NodeKind,
+        literal::{Literal, Str, Version},
+        node::{Location, NodeKind},
         node_type::NodeType,
         program::Program,
-        statement::{Assert, For, Statement},
-        ty::Type,
+        statement::{Assert, AssignOperator, For, If, Return, Statement},
+        ty::{Type, TypeBool, TypeString},
     },
-    storage::NodesStorage,
+    dependency_graph::DependencyGraph,
+    detector::DetectorResult,
+    passes::AnalysisPass,
+    source_map::SourceMap,
+    storage::{NodesStorage, SerializedRoutes},
     symbol_table::{build_symbol_table, SymbolTable},
+    visitor::{visit, Visitor},
 };
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, marker::PhantomData, rc::Rc};
+use std::{
+    any::{Any, TypeId},
+    cell::OnceCell,
+    collections::{HashMap, HashSet},
+    marker::PhantomData,
+    rc::Rc,
+};
+
+/// Builtin functions of the Compact language and its standard library.
+const BUILTIN_FUNCTIONS: &[&str] = &[
+    "assert",
+    "blockTimeGreaterThan",
+    "blockTimeLessThan",
+    "burnAddress",
+    "createZswapInput",
+    "createZswapOutput",
+    "default",
+    "degradeToTransient",
+    "disclose",
+    "ecAdd",
+    "ecMul",
+    "ecMulGenerator",
+    "evolveNonce",
+    "hashToCurve",
+    "left",
+    "mergeCoin",
+    "mergeCoinImmediate",
+    "merkleTreePathRoot",
+    "merkleTreePathRootNoLeafHash",
+    "mintToken",
+    "nativeToken",
+    "none",
+    "ownPublicKey",
+    "pad",
+    "persistentCommit",
+    "persistentHash",
+    "receive",
+    "right",
+    "send",
+    "sendImmediate",
+    "some",
+    "tokenType",
+    "transientCommit",
+    "transientHash",
+    "upgradeFromTransient",
+];
+
+/// Ledger operations that modify the ledger state they are called on.
+const LEDGER_WRITE_OPERATIONS: &[&str] = &[
+    "decrement",
+    "increment",
+    "insert",
+    "insertCoin",
+    "insertDefault",
+    "insertHash",
+    "insertHashIndex",
+    "insertIndex",
+    "insertIndexDefault",
+    "popFront",
+    "pushFront",
+    "remove",
+    "resetHistory",
+    "resetToDefault",
+    "write",
+    "writeCoin",
+];
 
 #[allow(dead_code)]
 trait CodebaseOpen {}
@@ -78,6 +242,18 @@ trait CodebaseSealed {}
 pub struct OpenState;
 impl CodebaseOpen for OpenState {}
 
+/// Represents a codebase whose files are parsed but not sealed. The AST is available, but
+/// symbol information such as `get_symbol_type_by_id` is not:
+///
+/// ```compile_fail
+/// use compact_security_detectors_sdk::codebase::{Codebase, ParsedState};
+///
+/// fn symbol_type(codebase: &Codebase<ParsedState>) {
+///     let _ = codebase.get_symbol_type_by_id(0);
+/// }
+/// ```
+pub struct ParsedState;
+
 /// Represents the sealed state of the codebase, preventing modifications.
 pub struct SealedState;
 impl CodebaseSealed for SealedState {}
@@ -87,10 +263,180 @@ impl CodebaseSealed for SealedState {}
 /// # Fields
 ///
 /// - `file_path`: a path to the source code file.
+/// - `ast`: the root node of the file.
+/// - `source`: the source code the AST was built from.
+/// - `source_map`: the line starts of the source code.
+/// - `comments`: the comments of the source code, in source order.
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct SourceCodeFile {
     pub file_path: String,
     pub(crate) ast: Rc<Program>,
+    pub(crate) source: Rc<str>,
+    pub(crate) source_map: Rc<SourceMap>,
+    pub(crate) comments: Rc<[Comment]>,
+}
+
+impl SourceCodeFile {
+    #[must_use = "Use this method to get the root node of the file"]
+    pub fn ast(&self) -> &Rc<Program> {
+        &self.ast
+    }
+}
+
+/// `Comment` is a line (`//`) or block (`/* */`) comment of a source file. Comments are trivia,
+/// they are not part of the AST and have no node id.
+///
+/// # Fields
+///
+/// - `location`: the location of the comment, delimiters included.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Comment {
+    pub location: Location,
+}
+
+impl Comment {
+    /// Returns the text of the comment without its delimiters, trimmed.
+    #[must_use = "Use this method to get the text of the comment"]
+    pub fn text(&self) -> &str {
+        let source = self.location.source.as_str();
+        source
+            .strip_prefix("//")
+            .or_else(|| {
+                source
+                    .strip_prefix("/*")
+                    .map(|text| text.strip_suffix("*/").unwrap_or(text))
+            })
+            .unwrap_or(source)
+            .trim()
+    }
+}
+
+/// `CircuitEffects` summarizes the side effects of a circuit.
+///
+/// # Fields
+///
+/// - `ledger_reads`: names of the ledger fields read by the circuit.
+/// - `ledger_writes`: names of the ledger fields written by the circuit, by assignment or by a
+///   mutating ledger operation (e.g. `insert`, `increment`).
+/// - `witness_calls`: names of the witnesses called by the circuit.
+/// - `external_calls`: names of the called functions that are neither witnesses nor circuits
+///   with a body in the codebase (e.g. standard library or external circuits).
+/// - `asserts`: number of `assert` statements in the circuit.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct CircuitEffects {
+    pub ledger_reads: Vec<String>,
+    pub ledger_writes: Vec<String>,
+    pub witness_calls: Vec<String>,
+    pub external_calls: Vec<String>,
+    pub asserts: usize,
+}
+
+/// `CodebaseStats` summarizes the size of a codebase.
+///
+/// # Fields
+///
+/// - `files`: number of source files.
+/// - `circuits`: number of circuit definitions, external circuits included.
+/// - `modules`: number of module definitions.
+/// - `ledgers`: number of ledger field declarations.
+/// - `asserts`: number of `assert` statements.
+/// - `for_loops`: number of `for` statements.
+/// - `total_nodes`: number of distinct AST nodes.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct CodebaseStats {
+    pub files: usize,
+    pub circuits: usize,
+    pub modules: usize,
+    pub ledgers: usize,
+    pub asserts: usize,
+    pub for_loops: usize,
+    pub total_nodes: usize,
+}
+
+/// `CompactParseError` describes a source file that could not be turned into an AST.
+///
+/// # Fields
+///
+/// - `file_path`: the path of the file that failed to parse.
+/// - `message`: the error reported by the AST builder.
+/// - `location`: the first syntax error in the file, or the whole file if the parser recovered.
+/// - `expected`: the kinds of the tokens the grammar accepts where the syntax error starts.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+#[error("{file_path}:{}:{}: {message}", location.start_line, location.start_column)]
+pub struct CompactParseError {
+    pub file_path: String,
+    pub message: String,
+    pub location: Location,
+    pub expected: Vec<String>,
+}
+
+/// `ParseDiagnostic` is the serializable report of a file that could not be parsed, as returned by
+/// `seal_with_diagnostics`.
+///
+/// # Fields
+///
+/// - `file`: the path of the file that failed to parse.
+/// - `offset`: the byte offset of the first syntax error in the file.
+/// - `message`: the error reported by the AST builder.
+/// - `expected`: the kinds of the tokens the grammar accepts at `offset`, sorted.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, thiserror::Error)]
+#[error("{file}:{offset}: {message}")]
+pub struct ParseDiagnostic {
+    pub file: String,
+    pub offset: u32,
+    pub message: String,
+    pub expected: Vec<String>,
+}
+
+impl From<&CompactParseError> for ParseDiagnostic {
+    fn from(error: &CompactParseError) -> Self {
+        ParseDiagnostic {
+            file: error.file_path.clone(),
+            offset: error.location.offset_start,
+            message: error.message.clone(),
+            expected: error.expected.clone(),
+        }
+    }
+}
+
+/// `SealError` is returned by `seal` when one or more files of the codebase failed to parse.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+#[error("failed to parse {} file(s):\n{}", errors.len(), errors.iter().map(ToString::to_string).collect::<Vec<_>>().join("\n"))]
+pub struct SealError {
+    pub errors: Vec<CompactParseError>,
+}
+
+/// `WeakAssertReason` tells why an assert is reported by `weak_asserts`.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum WeakAssertReason {
+    /// The assert has no message.
+    MissingMessage,
+    /// The assert message is empty or only contains whitespace.
+    BlankMessage,
+    /// The condition always holds, so the assert never fails.
+    AlwaysTrue,
+    /// The condition never holds, so the assert always fails.
+    AlwaysFalse,
+}
+
+/// `WeakAssert` is an assert statement together with the reasons it is considered weak.
+///
+/// # Fields
+///
+/// - `id`: the id of the assert statement.
+/// - `reasons`: the weaknesses found, in the order of `WeakAssertReason`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct WeakAssert {
+    pub id: u32,
+    pub reasons: Vec<WeakAssertReason>,
+}
+
+/// A read or a write of a ledger field inside a circuit.
+struct LedgerAccess {
+    id: u32,
+    name: String,
+    is_write: bool,
+    offset: u32,
 }
 
 /// `Codebase` represents a collection of source code files and their associated ASTs with API access functions
@@ -100,6 +446,12 @@ pub struct SourceCodeFile {
 /// - `storage`: a storage for AST nodes.
 /// - `files`: a vector of `SourceCodeFile`
 /// - `symbol_tables`: a map <file path: `Rc<SymbolTable>>`
+/// - `passes`: custom analysis passes run when the codebase is sealed
+/// - `pass_results`: results of the analysis passes, keyed by the result type
+/// - `original_paths`: the path each file moved with `rename_file` was parsed under, keyed by its
+///   current path
+/// - `references`: the ids of the identifiers resolving to each declaration, built on the first
+///   `find_references` call
 /// - `_state`: A phantom data marker for the state
 
 #[derive(Serialize, Deserialize, Default)]
@@ -107,9 +459,33 @@ pub struct Codebase<S> {
     pub(crate) storage: NodesStorage,
     pub(crate) files: Vec<SourceCodeFile>,
     pub(crate) symbol_tables: HashMap<String, Rc<SymbolTable>>,
+    #[serde(skip)]
+    pub(crate) passes: Vec<Rc<dyn AnalysisPass>>,
+    #[serde(skip)]
+    pub(crate) pass_results: HashMap<TypeId, Rc<dyn Any>>,
+    #[serde(skip)]
+    pub(crate) parse_errors: Vec<CompactParseError>,
+    #[serde(default)]
+    pub(crate) prelude_files: HashSet<String>,
+    #[serde(default)]
+    pub(crate) original_paths: HashMap<String, String>,
+    #[serde(skip)]
+    pub(crate) references: OnceCell<HashMap<u32, Vec<u32>>>,
     pub(crate) _state: PhantomData<S>,
 }
 
+/// The parts of a serialized codebase `from_json` restores. The storage nodes are rebuilt from
+/// the file programs, so only their routes are read.
+#[derive(Deserialize)]
+struct SerializedCodebase {
+    storage: SerializedRoutes,
+    files: Vec<SourceCodeFile>,
+    #[serde(default)]
+    prelude_files: HashSet<String>,
+    #[serde(default)]
+    original_paths: HashMap<String, String>,
+}
+
 impl Codebase<OpenState> {
     #[must_use]
     pub fn new() -> Self {
@@ -117,15 +493,21 @@ impl Codebase<OpenState> {
             storage: NodesStorage::default(),
             files: Vec::new(),
             symbol_tables: HashMap::new(),
+            passes: Vec::new(),
+            pass_results: HashMap::new(),
+            parse_errors: Vec::new(),
+            prelude_files: HashSet::new(),
+            original_paths: HashMap::new(),
+            references: OnceCell::new(),
             _state: PhantomData,
         }
     }
 
-    /// Parses the content of a source code file and returns a `SourceCodeFile` object.
-    ///
-    /// # Errors
+    /// Parses the content of a source code file and adds it to the codebase.
     ///
-    /// This function will return an error if the AST cannot be built from the source code.
+    /// A file that cannot be parsed is not added. Its parse error is recorded and surfaced by
+    /// `seal`, which fails with the errors of every such file, or by `seal_with_diagnostics`,
+    /// which returns them as diagnostics.
     ///
     /// # Panics
     ///
@@ -138,28 +520,108 @@ impl Codebase<OpenState> {
             .expect("Error loading Inference grammar");
         let tree = parser.parse(source_code, None).unwrap();
         let root_node = tree.root_node();
-        let ast = build_ast(self, &root_node, source_code).unwrap();
+        let nodes_count = self.storage.nodes.len();
+        let ast = match build_ast(self, &root_node, source_code) {
+            Ok(ast) => ast,
+            Err(error) => {
+                self.storage.truncate(nodes_count);
+                let error_node = first_syntax_error(root_node).unwrap_or(root_node);
+                self.parse_errors.push(CompactParseError {
+                    file_path: fname.to_string(),
+                    message: error.to_string(),
+                    location: location(&error_node, source_code),
+                    expected: expected_tokens(&compact_language, error_node),
+                });
+                return;
+            }
+        };
         let source_code_file = SourceCodeFile {
             file_path: fname.to_string(),
             ast,
+            source: Rc::from(source_code),
+            source_map: Rc::new(SourceMap::new(source_code)),
+            comments: collect_comments(root_node, source_code).into(),
         };
         self.files.push(source_code_file);
     }
 
+    /// Parses and adds a prelude file to the codebase.
+    ///
+    /// Circuits of prelude files can be called from every file without an import. Prelude files
+    /// take part in resolution like any other file, but results reported in them are dropped by
+    /// `reportable_results`. Like in `add_file`, a prelude file that cannot be parsed is not
+    /// added, so it is not recorded as a prelude file either.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if there is an error loading the Inference grammar.
+    pub fn add_prelude_file(&mut self, fname: &str, source_code: &str) {
+        let files_count = self.files.len();
+        self.add_file(fname, source_code);
+        if self.files.len() > files_count {
+            self.prelude_files.insert(fname.to_string());
+        }
+    }
+
     pub(crate) fn add_node(&mut self, node: NodeType, parent: u32) {
         self.storage.add_node(node, parent);
     }
 
+    /// Registers a custom analysis pass that runs at the end of `seal`.
+    ///
+    /// The pass result can be retrieved from the sealed codebase with `pass_result`.
+    pub fn register_pass(&mut self, pass: Box<dyn AnalysisPass>) {
+        self.passes.push(Rc::from(pass));
+    }
+
+    /// Replaces the source of a single file, or adds it if it is not part of the codebase yet.
+    ///
+    /// Only the given file is re-parsed; nodes of the other files keep their ids, so analyses
+    /// cached by node id stay valid for them. Cross-file indices are rebuilt by `reseal`.
+    pub fn update_file(&mut self, fname: &str, source_code: &str) {
+        self.remove_file(fname);
+        self.add_file(fname, source_code);
+    }
+
+    /// Seals a codebase opened with `unseal`, rebuilding the symbol tables, resolved imports and
+    /// references of every file.
+    ///
+    /// # Errors
+    ///
+    /// This function will return a `SealError` if an updated file failed to parse, or an error if
+    /// building the symbol table fails.
+    pub fn reseal(self) -> Result<Codebase<SealedState>> {
+        self.seal()
+    }
+
+    /// Removes a file and all of its nodes from the codebase. Nodes of the other files keep their
+    /// ids; imports of the removed file are left unresolved by `reseal`.
+    pub fn remove_file(&mut self, fname: &str) {
+        self.parse_errors.retain(|error| error.file_path != fname);
+        self.original_paths.remove(fname);
+        if let Some(index) = self.files.iter().position(|f| f.file_path == fname) {
+            let file = self.files.remove(index);
+            self.storage.remove_subtree(file.ast.id);
+        }
+    }
+
     /// Seals the codebase, preventing further modifications.
     ///
     /// # Errors
     ///
-    /// This function will return an error if building the symbol table fails.
+    /// This function will return a `SealError` listing every file that failed to parse, or an
+    /// error if building the symbol table fails.
     ///
     /// # Panics
     ///
     /// This function will panic if the symbol table for a file path is not found.
     pub fn seal(mut self) -> Result<Codebase<SealedState>> {
+        if !self.parse_errors.is_empty() {
+            return Err(SealError {
+                errors: self.parse_errors,
+            }
+            .into());
+        }
         self.link_imports();
         let mut local_symbol_tables = HashMap::new();
         for file in &self.files {
@@ -197,19 +659,82 @@ impl Codebase<OpenState> {
         }
         self.link_function_calls();
         self.storage.seal();
-        Ok(Codebase {
+        let mut sealed = Codebase {
             storage: self.storage,
             files: self.files,
             symbol_tables,
+            passes: self.passes,
+            pass_results: HashMap::new(),
+            parse_errors: Vec::new(),
+            prelude_files: self.prelude_files,
+            original_paths: self.original_paths,
+            references: OnceCell::new(),
             _state: PhantomData,
-        })
+        };
+        let pass_results: Vec<Rc<dyn Any>> = sealed
+            .passes
+            .iter()
+            .map(|pass| Rc::from(pass.run(&sealed)))
+            .collect();
+        for result in pass_results {
+            sealed.pass_results.insert((*result).type_id(), result);
+        }
+        Ok(sealed)
+    }
+
+    /// Seals the files that could be parsed and returns the diagnostics of the files that could
+    /// not, instead of failing like `seal`. Files that failed to parse are not part of the sealed
+    /// codebase.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if building the symbol table fails.
+    pub fn seal_with_diagnostics(
+        mut self,
+    ) -> Result<(Codebase<SealedState>, Vec<ParseDiagnostic>)> {
+        let diagnostics = self
+            .parse_errors
+            .drain(..)
+            .map(|error| ParseDiagnostic::from(&error))
+            .collect();
+        Ok((self.seal()?, diagnostics))
     }
 
     fn link_imports(&mut self) {
+        let importing_files: HashMap<u32, String> = self
+            .storage
+            .nodes
+            .iter()
+            .filter(|node| matches!(node, NodeType::Declaration(Declaration::Import(_))))
+            .filter_map(|node| {
+                self.find_node_file(node.id()).map(|file| {
+                    let path = self.original_path(&file.file_path).to_string();
+                    (node.id(), path)
+                })
+            })
+            .collect();
         for node in &mut self.storage.nodes {
             if let NodeType::Declaration(Declaration::Import(ref mut import)) = node {
                 let import_mut = Rc::make_mut(import);
-                if let Some(file) = self.files.iter().find(|f| f.file_path == import_mut.name()) {
+                let importing_file = importing_files.get(&import_mut.id);
+                let resolves_to = |path: &str| {
+                    path == import_mut.name()
+                        || importing_file.is_some_and(|importing_file| {
+                            import_resolves_to(importing_file, &import_mut.name(), path)
+                        })
+                };
+                if let Some(file) = self
+                    .files
+                    .iter()
+                    .find(|f| resolves_to(&f.file_path))
+                    .or_else(|| {
+                        self.files.iter().find(|f| {
+                            self.original_paths
+                                .get(&f.file_path)
+                                .is_some_and(|path| resolves_to(path))
+                        })
+                    })
+                {
                     import_mut.reference = Some(file.ast.clone());
                     if let Some(imported_symtab) = self.symbol_tables.get(&file.file_path) {
                         let symbols_to_add: Vec<_> = imported_symtab
@@ -227,11 +752,20 @@ impl Codebase<OpenState> {
                                 .upsert(0, name, Some(ty));
                         }
                     }
+                } else {
+                    import_mut.reference = None;
                 }
             }
         }
     }
 
+    /// Returns the path the file was parsed under, before it was moved with `rename_file`.
+    fn original_path<'a>(&'a self, file_path: &'a str) -> &'a str {
+        self.original_paths
+            .get(file_path)
+            .map_or(file_path, String::as_str)
+    }
+
     fn link_function_calls(&mut self) {
         let function_calls: Vec<(u32, String)> = self
             .storage
@@ -290,13 +824,30 @@ impl Codebase<OpenState> {
                         }
                     }
                 }
-                if let Some(circuit) = circuit_opt {
-                    if let Some(NodeType::Expression(Expression::FunctionCall(
-                        ref mut function_call_node,
-                    ))) = self.storage.find_node_mut(call_id)
+                if circuit_opt.is_none() {
+                    // Prelude circuits are visible from every file without an import.
+                    circuit_opt = self
+                        .files
+                        .iter()
+                        .filter(|f| self.prelude_files.contains(&f.file_path))
+                        .find_map(|f| {
+                            f.ast
+                                .circuits()
+                                .iter()
+                                .find(|c| c.name() == function_name)
+                                .cloned()
+                        });
+                }
+                if let Some(NodeType::Expression(Expression::FunctionCall(
+                    ref mut function_call_node,
+                ))) = self.storage.find_node_mut(call_id)
+                {
+                    // A call may have been linked before the codebase was re-sealed.
+                    if function_call_node.reference.as_ref().map(|c| c.id)
+                        != circuit_opt.as_ref().map(|c| c.id)
                     {
                         let function_call_mut = Rc::make_mut(function_call_node);
-                        function_call_mut.reference = Some(circuit);
+                        function_call_mut.reference = circuit_opt;
                     }
                 }
             }
@@ -325,20 +876,467 @@ impl Codebase<OpenState> {
     }
 }
 
+impl Codebase<ParsedState> {
+    /// Parses the given files into ASTs without building symbol tables, linking calls or running
+    /// analysis passes, for tools that only need the syntax tree.
+    ///
+    /// # Arguments
+    ///
+    /// * `files` - A map where the keys are file paths and the values are the corresponding source code strings.
+    ///
+    /// # Errors
+    ///
+    /// This function will return a `SealError` listing every file that failed to parse.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if there is an error loading the Inference grammar.
+    pub fn parse_only<H: std::hash::BuildHasher>(
+        files: &HashMap<String, String, H>,
+    ) -> Result<Codebase<ParsedState>> {
+        let mut codebase = Codebase::<OpenState>::new();
+        for (file_path, source_code) in files {
+            codebase.add_file(file_path, source_code);
+        }
+        if !codebase.parse_errors.is_empty() {
+            return Err(SealError {
+                errors: codebase.parse_errors,
+            }
+            .into());
+        }
+        Ok(Codebase {
+            storage: codebase.storage,
+            files: codebase.files,
+            symbol_tables: HashMap::new(),
+            passes: codebase.passes,
+            pass_results: HashMap::new(),
+            parse_errors: Vec::new(),
+            prelude_files: codebase.prelude_files,
+            original_paths: codebase.original_paths,
+            references: OnceCell::new(),
+            _state: PhantomData,
+        })
+    }
+
+    pub fn files(&self) -> impl Iterator<Item = SourceCodeFile> + '_ {
+        self.files.iter().cloned()
+    }
+
+    /// Seals the parsed codebase, building the symbol tables and running the analysis passes.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if building the symbol table fails.
+    pub fn seal(self) -> Result<Codebase<SealedState>> {
+        Codebase::<OpenState> {
+            storage: self.storage,
+            files: self.files,
+            symbol_tables: HashMap::new(),
+            passes: self.passes,
+            pass_results: HashMap::new(),
+            parse_errors: Vec::new(),
+            prelude_files: self.prelude_files,
+            original_paths: self.original_paths,
+            references: OnceCell::new(),
+            _state: PhantomData,
+        }
+        .seal()
+    }
+}
+
 impl Codebase<SealedState> {
+    /// Replaces the source of a single file and re-seals the codebase.
+    ///
+    /// Only the given file is re-parsed; nodes of the other files keep their ids. If the file is
+    /// not part of the codebase yet, it is added.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if re-sealing the codebase fails.
+    pub fn replace_file(self, fname: &str, source_code: &str) -> Result<Codebase<SealedState>> {
+        let mut codebase = self.unseal();
+        codebase.update_file(fname, source_code);
+        codebase.reseal()
+    }
+
+    /// Opens the codebase for modifications, keeping its files, nodes and registered passes.
+    ///
+    /// Symbol tables, references and pass results are dropped until the codebase is resealed.
+    #[must_use = "Use this function to update files of a sealed codebase"]
+    pub fn unseal(self) -> Codebase<OpenState> {
+        let mut storage = self.storage;
+        storage.unseal();
+        Codebase::<OpenState> {
+            storage,
+            files: self.files,
+            symbol_tables: HashMap::new(),
+            passes: self.passes,
+            pass_results: HashMap::new(),
+            parse_errors: Vec::new(),
+            prelude_files: self.prelude_files,
+            original_paths: self.original_paths,
+            references: OnceCell::new(),
+            _state: PhantomData,
+        }
+    }
+
+    /// Returns a copy of the codebase sharing the `Rc` AST nodes, sources and symbol tables of
+    /// this one, with its own node storage and index.
+    ///
+    /// The nodes are shared, so structural edits must go through `replace_file`, which re-seals
+    /// the copy. The copy shares the registered analysis passes and their results, and runs the
+    /// passes again when it is re-sealed.
+    #[must_use = "Use this function to get a copy of the codebase"]
+    pub fn clone_shallow(&self) -> Codebase<SealedState> {
+        Codebase {
+            storage: self.storage.clone(),
+            files: self.files.clone(),
+            symbol_tables: self.symbol_tables.clone(),
+            passes: self.passes.clone(),
+            pass_results: self.pass_results.clone(),
+            parse_errors: Vec::new(),
+            prelude_files: self.prelude_files.clone(),
+            original_paths: self.original_paths.clone(),
+            references: self.references.clone(),
+            _state: PhantomData,
+        }
+    }
+
+    /// Serializes the codebase, its node storage, files and symbol tables, to JSON.
+    ///
+    /// Registered analysis passes and their results are not serialized.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if serialization fails.
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    /// Restores a codebase serialized with `to_json`.
+    ///
+    /// The node storage is rebuilt from the file programs, so the nodes found by id are the nodes
+    /// of the file programs, kept under their serialized parents. The derived indices (node index,
+    /// import and call references, symbol definitions) and the symbol tables are rebuilt by
+    /// sealing the restored codebase.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the JSON is not a serialized codebase or if
+    /// re-sealing it fails.
+    pub fn from_json(json: &str) -> Result<Box<Codebase<SealedState>>> {
+        let serialized: SerializedCodebase = serde_json::from_str(json)?;
+        let parents = serialized.storage.parents();
+        let mut storage = NodesStorage::default();
+        let mut added = HashSet::new();
+        for file in &serialized.files {
+            let mut stack = vec![(NodeType::Program(file.ast.clone()), 0)];
+            while let Some((node, ast_parent)) = stack.pop() {
+                let id = node.id();
+                if !added.insert(id) {
+                    continue;
+                }
+                stack.extend(node.children().into_iter().rev().map(|child| (child, id)));
+                storage.add_node(node, parents.get(&id).copied().unwrap_or(ast_parent));
+            }
+        }
+        let codebase = Codebase::<OpenState> {
+            storage,
+            files: serialized.files,
+            symbol_tables: HashMap::new(),
+            passes: Vec::new(),
+            pass_results: HashMap::new(),
+            parse_errors: Vec::new(),
+            prelude_files: serialized.prelude_files,
+            original_paths: serialized.original_paths,
+            references: OnceCell::new(),
+            _state: PhantomData,
+        };
+        Ok(Box::new(codebase.seal()?))
+    }
+
+    /// Moves the file `old` to the path `new` without re-parsing it.
+    ///
+    /// The file keeps its AST, node ids and symbol table, and imports already resolved to the
+    /// file keep pointing to it. The path the file was parsed under is remembered, so when the
+    /// codebase is resealed, imports of that path still resolve to the file, and the relative
+    /// imports of the file are still resolved from its original directory. Replacing or removing
+    /// the file drops its original path.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if there is no file `old` or if a file `new` already
+    /// exists in the codebase.
+    pub fn rename_file(&mut self, old: &str, new: &str) -> Result<()> {
+        if self.files.iter().any(|file| file.file_path == new) {
+            anyhow::bail!("File {new} already exists in the codebase");
+        }
+        let Some(file) = self.files.iter_mut().find(|file| file.file_path == old) else {
+            anyhow::bail!("File {old} not found in the codebase");
+        };
+        file.file_path = new.to_string();
+        let original = self
+            .original_paths
+            .remove(old)
+            .unwrap_or_else(|| old.to_string());
+        self.original_paths.insert(new.to_string(), original);
+        if let Some(symbol_table) = self.symbol_tables.remove(old) {
+            self.symbol_tables.insert(new.to_string(), symbol_table);
+        }
+        if self.prelude_files.remove(old) {
+            self.prelude_files.insert(new.to_string());
+        }
+        Ok(())
+    }
+
+    /// Checks if the file with the given path was added as a prelude file.
+    #[must_use = "Use this function to check if a file is a prelude file"]
+    pub fn is_prelude_file(&self, file_path: &str) -> bool {
+        self.prelude_files.contains(file_path)
+    }
+
+    /// Drops the detector results reported in prelude files.
+    #[must_use = "Use this function to filter out results reported in prelude files"]
+    pub fn reportable_results(&self, results: Vec<DetectorResult>) -> Vec<DetectorResult> {
+        results
+            .into_iter()
+            .filter(|result| !self.is_prelude_file(&result.file_path))
+            .collect()
+    }
+
     pub fn files(&self) -> impl Iterator<Item = SourceCodeFile> + '_ {
         self.files.iter().cloned()
     }
 
+    /// Returns the source code of the file with the given path.
+    #[must_use = "Use this function to get the source code of a file"]
+    pub fn source_of_file(&self, path: &str) -> Option<&str> {
+        self.files
+            .iter()
+            .find(|file| file.file_path == path)
+            .map(|file| file.source.as_ref())
+    }
+
+    /// Returns the result stored by a registered analysis pass, if a pass returned a `T`.
+    #[must_use = "Use this function to get the result of a registered analysis pass"]
+    pub fn pass_result<T: 'static>(&self) -> Option<&T> {
+        self.pass_results
+            .get(&TypeId::of::<T>())
+            .and_then(|result| result.downcast_ref::<T>())
+    }
+
+    /// Returns the type of the symbol with the given id. Symbols not typed by the symbol table of
+    /// their file, such as the member of a module-qualified name like `Token.balance`, get the
+    /// type of their declaration when it is declared in another file.
+    ///
+    /// A member access into a structure value, e.g. `p.x`, and its member identifier get the
+    /// declared type of the field, following nested accesses such as `a.b.c`.
     #[must_use = "Use this function to get a type for a symbol (Identifier)"]
     pub fn get_symbol_type_by_id(&self, id: u32) -> Option<Type> {
-        if let Some(file) = self.find_node_file(id) {
-            self.symbol_tables
-                .get(&file.file_path)
-                .and_then(|table| table.lookdown_by_id(id))
-        } else {
-            None
+        if let Some(ty) = self
+            .member_access_of(id)
+            .and_then(|member_access| self.field_type(&member_access))
+        {
+            return Some(ty);
+        }
+        let file = self.find_node_file(id)?;
+        if let Some(ty) = self
+            .symbol_tables
+            .get(&file.file_path)
+            .and_then(|table| table.lookdown_by_id(id))
+        {
+            return Some(ty);
+        }
+        // Only the members of module-qualified names are declared in another file.
+        let member_access = self.member_access_with_member(id)?;
+        let definition = self.qualified_definition(&member_access.base, &member_access.member)?;
+        let definition_file = self.find_node_file(definition)?;
+        if definition_file.file_path == file.file_path {
+            return None;
+        }
+        let declaration = self.find_node_by_id(definition)?;
+        if let NodeType::Declaration(Declaration::Ledger(ledger)) = declaration {
+            return Some(ledger.ty.clone());
+        }
+        let name_id = declared_name_id(declaration)?;
+        self.symbol_tables
+            .get(&definition_file.file_path)
+            .and_then(|table| table.lookdown_by_id(name_id))
+    }
+
+    /// Returns the id of the declaration an identifier refers to: the parameter, `const`, `var`,
+    /// loop counter, ledger field, witness, circuit, struct or enum bound to its name where the
+    /// identifier appears. Bindings follow lexical scoping, the innermost one wins, and a local
+    /// binding is visible from the end of its statement to the end of its enclosing block.
+    ///
+    /// Parameters resolve to their pattern argument, `const` and `var` to their statement, loop
+    /// counters to the counter identifier, and file level names to their declaration or
+    /// definition in the file or the files it imports. The member of a module-qualified name such
+    /// as `Token.mint` resolves to its declaration in the file imported as `Token`, other members
+    /// are not resolved. Returns `None` if the node is not an identifier or its name is not
+    /// declared.
+    #[must_use = "Use this function to get the declaration of an identifier"]
+    pub fn get_symbol_definition(&self, id: u32) -> Option<u32> {
+        let identifier = match self.find_node_by_id(id)? {
+            NodeType::Expression(Expression::Identifier(identifier))
+            | NodeType::Pattern(Pattern::Identifier(identifier)) => identifier.clone(),
+            _ => return None,
+        };
+        if let Some(member_access) = self.member_access_with_member(identifier.id) {
+            return self.qualified_definition(&member_access.base, &identifier);
+        }
+        let file = self.find_node_file(id)?;
+        if let Some(definition) = self
+            .circuit_at(&file.file_path, identifier.location.offset_start)
+            .and_then(|circuit| local_definition(&circuit, &identifier))
+        {
+            return Some(definition);
+        }
+        self.visible_programs(id)
+            .iter()
+            .find_map(|program| program_definition(program, &identifier.name))
+    }
+
+    /// Returns the member access with the given id, or whose member identifier has the given id.
+    fn member_access_of(&self, id: u32) -> Option<Rc<MemberAccess>> {
+        match self.storage.find_node_ref(id)? {
+            NodeType::Expression(Expression::MemberAccess(member_access)) => {
+                Some(member_access.clone())
+            }
+            NodeType::Expression(Expression::Identifier(_)) => self.member_access_with_member(id),
+            _ => None,
+        }
+    }
+
+    /// Returns the member access whose member identifier has the given id. The member and its
+    /// access are stored under the same parent, so only the siblings of the identifier are read.
+    fn member_access_with_member(&self, id: u32) -> Option<Rc<MemberAccess>> {
+        let parent = self.storage.find_parent_node(id)?;
+        self.storage
+            .find_child_nodes(parent)
+            .iter()
+            .find_map(|sibling| match self.storage.find_node_ref(*sibling)? {
+                NodeType::Expression(Expression::MemberAccess(member_access))
+                    if member_access.member.id == id =>
+                {
+                    Some(member_access.clone())
+                }
+                _ => None,
+            })
+    }
+
+    /// Returns the type of the structure field accessed by a member access, when the base has the
+    /// type of a structure declared in the file of the access or in the files it imports.
+    fn field_type(&self, member_access: &MemberAccess) -> Option<Type> {
+        let base_type = match &member_access.base {
+            Expression::MemberAccess(base) => self.field_type(base),
+            base => self.get_symbol_type_by_id(base.id()),
+        }?;
+        let Type::Ref(reference) = base_type else {
+            return None;
+        };
+        self.visible_programs(member_access.id)
+            .iter()
+            .flat_map(|program| program.definitions.iter())
+            .find_map(|definition| match definition {
+                Definition::Structure(structure) if structure.name() == reference.name.name => {
+                    Some(structure.clone())
+                }
+                _ => None,
+            })?
+            .field_type(&member_access.member.name)
+    }
+
+    /// Resolves the member of a module-qualified name such as `Token.mint`, where `Token` names a
+    /// file imported by the file of the member, to its declaration in the imported file. Returns
+    /// `None` if the base is not an identifier naming an imported file, or if it names a local
+    /// binding shadowing the module.
+    fn qualified_definition(&self, base: &Expression, member: &Identifier) -> Option<u32> {
+        let Expression::Identifier(module) = base else {
+            return None;
+        };
+        let file = self.find_node_file(member.id)?;
+        if self
+            .circuit_at(&file.file_path, module.location.offset_start)
+            .and_then(|circuit| local_definition(&circuit, module))
+            .is_some()
+        {
+            return None;
         }
+        self.imported_modules(&file.file_path)
+            .into_iter()
+            .filter(|(name, _)| *name == module.name)
+            .find_map(|(_, program)| program_definition(&program, &member.name))
+    }
+
+    /// Returns the programs imported by a file, keyed by the module name of their import: the
+    /// last component of the import path without its extension, e.g. `Test` for `"../../Test"`.
+    fn imported_modules(&self, file_path: &str) -> Vec<(String, Rc<Program>)> {
+        self.list_nodes_cmp(|node| match node {
+            NodeType::Declaration(Declaration::Import(import)) => import
+                .reference
+                .as_ref()
+                .map(|program| (import.id, import.name(), program.clone())),
+            _ => None,
+        })
+        .filter(|(import_id, _, _)| {
+            self.find_node_file(*import_id)
+                .is_some_and(|file| file.file_path == file_path)
+        })
+        .filter_map(|(_, name, program)| {
+            std::path::Path::new(&name)
+                .file_stem()
+                .map(|stem| (stem.to_string_lossy().to_string(), program))
+        })
+        .collect()
+    }
+
+    /// Returns the ids of the identifiers resolving to the declaration with the given id (see
+    /// `get_symbol_definition`), including the bases of member accesses such as `counter` in
+    /// `counter.increment(1)`, ordered by id. The declared name itself is not a reference.
+    ///
+    /// The references of every declaration are indexed on the first call.
+    #[must_use = "Use this function to get the references to a declaration"]
+    pub fn find_references(&self, decl_id: u32) -> Vec<u32> {
+        self.references
+            .get_or_init(|| self.build_references())
+            .get(&decl_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Resolves every identifier of the codebase to its declaration and groups the identifier ids
+    /// by declaration id.
+    fn build_references(&self) -> HashMap<u32, Vec<u32>> {
+        let mut references: HashMap<u32, Vec<u32>> = HashMap::new();
+        for node in &self.storage.nodes {
+            let NodeType::Expression(Expression::Identifier(identifier)) = node else {
+                continue;
+            };
+            let Some(decl_id) = self.get_symbol_definition(identifier.id) else {
+                continue;
+            };
+            if self
+                .find_node_by_id(decl_id)
+                .and_then(declared_name_id)
+                .is_some_and(|name_id| name_id == identifier.id)
+            {
+                continue;
+            }
+            references.entry(decl_id).or_default().push(identifier.id);
+        }
+        for ids in references.values_mut() {
+            ids.sort_unstable();
+            ids.dedup();
+        }
+        references
+    }
+
+    /// Returns the node with the given id. The lookup uses the node index built when sealing
+    /// the codebase.
+    #[must_use = "Use this function to get a Node by its ID"]
+    pub fn find_node_by_id(&self, id: u32) -> Option<&NodeType> {
+        self.storage.find_node_ref(id)
     }
 
     pub fn list_assert_nodes(&self) -> impl Iterator<Item = Rc<Assert>> + '_ {
@@ -361,6 +1359,52 @@ impl Codebase<SealedState> {
         })
     }
 
+    pub fn list_if_statement_nodes(&self) -> impl Iterator<Item = Rc<If>> + '_ {
+        self.list_nodes_cmp(|node| {
+            if let NodeType::Statement(Statement::If(stmt)) = node {
+                Some(stmt.clone())
+            } else {
+                None
+            }
+        })
+    }
+
+    pub fn list_return_nodes(&self) -> impl Iterator<Item = Rc<Return>> + '_ {
+        self.list_nodes_cmp(|node| {
+            if let NodeType::Statement(Statement::Return(stmt)) = node {
+                Some(stmt.clone())
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Lists all `For` statement nodes paired with the path of the file they are declared in,
+    /// file by file in the order the files were added, and in source order within a file.
+    pub fn for_statements_with_files(&self) -> impl Iterator<Item = (Rc<For>, &str)> + '_ {
+        self.files.iter().flat_map(move |file| {
+            self.descendants_of(file.ast.id)
+                .filter_map(move |node| match node {
+                    NodeType::Statement(Statement::For(for_stmt)) => {
+                        Some((for_stmt.clone(), file.file_path.as_str()))
+                    }
+                    _ => None,
+                })
+        })
+    }
+
+    /// Lists all string literals containing a `${...}` interpolation marker.
+    ///
+    /// Compact does not support string interpolation, the marker ends up verbatim in the string.
+    pub fn list_interpolated_strings(&self) -> impl Iterator<Item = Rc<Str>> + '_ {
+        self.list_nodes_cmp(|node| match node {
+            NodeType::Literal(Literal::Str(str)) if str.has_interpolation_marker() => {
+                Some(str.clone())
+            }
+            _ => None,
+        })
+    }
+
     #[must_use = "Use this function to get a list of all exported circuits in the file"]
     pub fn list_exported_circuits_from_program(&self, program: &Rc<Program>) -> Vec<Rc<Circuit>> {
         self.list_exported_circuits(program.id)
@@ -436,239 +1480,4967 @@ impl Codebase<SealedState> {
         None
     }
 
-    pub fn get_children_cmp<F>(&self, id: u32, comparator: F) -> Vec<NodeType>
-    where
-        F: Fn(&NodeType) -> bool,
-    {
-        let mut result = Vec::new();
-        let mut stack: Vec<NodeType> = Vec::new();
-
-        if let Some(root_node) = self.storage.find_node(id) {
-            stack.push(root_node.clone());
+    /// Returns the ancestors of the node with the given id, from its immediate parent up to the
+    /// program node of its file. Modules are crossed like any other node, so the chain of a
+    /// circuit declared in a nested module contains every enclosing module.
+    ///
+    /// The walk stops at the program node; a program node has no ancestors.
+    #[must_use = "Use this function to get the ancestors of a node"]
+    pub fn get_ancestors(&self, id: u32) -> Vec<NodeType> {
+        let mut ancestors = Vec::new();
+        if matches!(self.storage.find_node_ref(id), Some(NodeType::Program(_))) {
+            return ancestors;
         }
-
-        while let Some(current_node) = stack.pop() {
-            if comparator(&current_node) {
-                result.push(current_node.clone());
+        let mut visited = HashSet::from([id]);
+        let mut current_id = id;
+        while let Some(parent_id) = self.storage.find_parent_node(current_id) {
+            if !visited.insert(parent_id) {
+                break;
             }
-            stack.extend(current_node.children());
+            let Some(parent) = self.storage.find_node(parent_id) else {
+                break;
+            };
+            let is_program = matches!(parent, NodeType::Program(_));
+            ancestors.push(parent);
+            if is_program {
+                break;
+            }
+            current_id = parent_id;
         }
+        ancestors
+    }
 
-        result
+    /// Returns the innermost circuit of `file_path` whose span contains `offset`.
+    ///
+    /// This is used to attribute detector results, which only carry a file path and offsets,
+    /// to the circuit they were found in.
+    #[must_use = "Use this function to get the circuit enclosing a source offset"]
+    pub fn circuit_at(&self, file_path: &str, offset: u32) -> Option<Rc<Circuit>> {
+        self.list_nodes_cmp(|node| {
+            if let NodeType::Definition(Definition::Circuit(circuit)) = node {
+                Some(circuit.clone())
+            } else {
+                None
+            }
+        })
+        .filter(|circuit| {
+            circuit.location.offset_start <= offset && offset < circuit.location.offset_end
+        })
+        .filter(|circuit| {
+            self.find_node_file(circuit.id)
+                .is_some_and(|file| file.file_path == file_path)
+        })
+        .min_by_key(|circuit| circuit.location.offset_end - circuit.location.offset_start)
     }
 
-    fn list_nodes_cmp<'a, T, F>(&'a self, cast: F) -> impl Iterator<Item = T> + 'a
-    where
-        F: Fn(&NodeType) -> Option<T> + 'a,
-        T: Clone + 'static,
-    {
-        self.storage.nodes.iter().filter_map(cast)
+    /// Returns the circuit named `name` as seen from `file_path`.
+    ///
+    /// Circuits declared in the file take precedence over circuits declared in the files it
+    /// imports, which are searched in import order.
+    #[must_use = "Use this function to resolve a circuit name within a file"]
+    pub fn circuit_by_name_in_file(&self, file_path: &str, name: &str) -> Option<Rc<Circuit>> {
+        let file = self.files.iter().find(|file| file.file_path == file_path)?;
+        std::iter::once(file)
+            .chain(self.imported_files(file_path))
+            .find_map(|file| {
+                file.ast
+                    .circuits()
+                    .into_iter()
+                    .find(|circuit| circuit.name() == name)
+            })
     }
-}
 
-impl<T> Codebase<T> {
-    #[must_use = "Use this function to get a Node's source file"]
-    pub fn find_node_file(&self, id: u32) -> Option<SourceCodeFile> {
-        if let Some(file) = self.files.iter().find(|file| file.ast.id == id) {
-            Some(file.clone())
-        } else {
-            let mut node_id = id;
-            while let Some(parent) = self.storage.find_parent_node(node_id) {
-                if parent == 0 {
-                    if let Some(file) = self.storage.find_node(node_id) {
-                        match file {
-                            NodeType::Program(f) => {
-                                if let Some(sf) =
-                                    self.files.iter().find(|file| Rc::ptr_eq(&file.ast, &f))
-                                {
-                                    return Some(SourceCodeFile {
-                                        file_path: sf.file_path.clone(),
-                                        ast: f.clone(),
-                                    });
-                                }
-                            }
-                            _ => return None,
+    /// Returns all circuits containing a call to the function with the given name.
+    ///
+    /// The `pad` builtin is parsed as a literal, its uses are reported as calls to `pad`.
+    #[must_use = "Use this function to find the circuits calling a function"]
+    pub fn circuits_calling(&self, callee_name: &str) -> Vec<Rc<Circuit>> {
+        let call_ids: Vec<u32> = self
+            .list_nodes_cmp(|node| match node {
+                NodeType::Expression(Expression::FunctionCall(function_call)) => {
+                    match &function_call.function {
+                        Expression::Function(Function::Named(function))
+                            if function.name() == callee_name =>
+                        {
+                            Some(function_call.id)
                         }
+                        _ => None,
                     }
                 }
-                node_id = parent;
+                NodeType::Literal(Literal::Pad(pad)) if callee_name == "pad" => Some(pad.id),
+                _ => None,
+            })
+            .collect();
+        let mut circuits: Vec<Rc<Circuit>> = Vec::new();
+        for call_id in call_ids {
+            if let Some(NodeType::Definition(Definition::Circuit(circuit))) =
+                self.get_parent_container(call_id)
+            {
+                if !circuits.iter().any(|c| c.id == circuit.id) {
+                    circuits.push(circuit);
+                }
             }
-            None
         }
+        circuits
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Groups the file paths of the codebase by the version constraint of their
+    /// `language_version` pragma, as written in the source (e.g. `>= 0.14.0`).
+    ///
+    /// Files without a `language_version` pragma are grouped under `"unspecified"`.
+    #[must_use = "Use this function to get the language versions used by the codebase"]
+    pub fn compact_version_report(&self) -> HashMap<String, Vec<String>> {
+        let mut report: HashMap<String, Vec<String>> = HashMap::new();
+        for file in &self.files {
+            let version = file
+                .ast
+                .directives
+                .iter()
+                .find_map(|directive| match directive {
+                    Directive::Pragma(pragma) if pragma.name() == "language_version" => {
+                        Some(pragma.constraint())
+                    }
+                    Directive::Pragma(_) => None,
+                })
+                .unwrap_or_else(|| "unspecified".to_string());
+            report
+                .entry(version)
+                .or_default()
+                .push(file.file_path.clone());
+        }
+        for files in report.values_mut() {
+            files.sort();
+        }
+        report
+    }
 
-    #[test]
-    fn test_import_reference_set_correctly() -> anyhow::Result<()> {
-        let mut codebase = Codebase::<OpenState>::new();
-        codebase.add_file("./a.compact", r#"import "./b.compact";"#);
-        codebase.add_file("./b.compact", r#"import "./a.compact";"#);
-        let codebase = codebase.seal()?;
-        let imports: Vec<_> = codebase
-            .list_nodes_cmp(|node| {
-                if let NodeType::Declaration(Declaration::Import(import)) = node {
-                    Some(import.clone())
-                } else {
-                    None
+    /// Returns the ids of the calls to deprecated functions.
+    ///
+    /// `deprecated` maps function names to the language version they were deprecated in. A call
+    /// is reported if the minimum language version of its file, as declared by the
+    /// `language_version` pragma, is at or past the deprecation version. Files without a
+    /// `language_version` pragma are not reported.
+    #[must_use = "Use this function to find calls to deprecated functions"]
+    pub fn calls_to_deprecated(&self, deprecated: &HashMap<String, Version>) -> Vec<u32> {
+        self.list_nodes_cmp(|node| match node {
+            NodeType::Expression(Expression::FunctionCall(function_call)) => {
+                match &function_call.function {
+                    Expression::Function(Function::Named(function)) => deprecated
+                        .get(function.name())
+                        .map(|version| (function_call.id, version.triple())),
+                    _ => None,
+                }
+            }
+            _ => None,
+        })
+        .filter(|(call_id, deprecated_in)| {
+            self.find_node_file(*call_id)
+                .and_then(|file| file.ast.language_version())
+                .is_some_and(|version| version.triple() >= *deprecated_in)
+        })
+        .map(|(call_id, _)| call_id)
+        .collect()
+    }
+
+    /// Returns the calls to the function `name` with their arguments, in order, where literal
+    /// arguments are `Some` and other arguments are `None` slots, ordered by call id.
+    ///
+    /// `pad(n, "...")` is parsed as a `Pad` literal rather than a call, so for `pad` the ids of the
+    /// pad literals are returned, with their length and string as arguments.
+    #[must_use = "Use this function to get the literal arguments of the calls to a function"]
+    pub fn calls_with_literal_args(&self, name: &str) -> Vec<(u32, Vec<Option<Literal>>)> {
+        let mut calls: Vec<(u32, Vec<Option<Literal>>)> = self
+            .list_nodes_cmp(|node| match node {
+                NodeType::Expression(Expression::FunctionCall(function_call)) => {
+                    match &function_call.function {
+                        Expression::Function(Function::Named(function))
+                            if function.name() == name =>
+                        {
+                            let arguments = function_call
+                                .arguments
+                                .iter()
+                                .map(|argument| match argument {
+                                    Expression::Literal(literal) => Some(literal.clone()),
+                                    _ => None,
+                                })
+                                .collect();
+                            Some((function_call.id, arguments))
+                        }
+                        _ => None,
+                    }
                 }
+                NodeType::Literal(Literal::Pad(pad)) if name == "pad" => Some((
+                    pad.id,
+                    vec![
+                        Some(Literal::Nat(pad.number.clone())),
+                        Some(Literal::Str(pad.name.clone())),
+                    ],
+                )),
+                _ => None,
             })
             .collect();
-        assert_eq!(imports.len(), 2);
-        for import in imports {
-            assert!(
-                import.reference.is_some(),
-                "Import reference should be set for all import nodes"
-            );
-        }
-        Ok(())
+        calls.sort_by_key(|(id, _)| *id);
+        calls.dedup_by_key(|(id, _)| *id);
+        calls
     }
 
-    #[test]
-    fn test_imported_function_types_resolved_correctly() -> anyhow::Result<()> {
-        let mut codebase = Codebase::<OpenState>::new();
-        let source_a = r"
-            export pure circuit unknown_ship_def(): ShipDef {
-              return ShipDef {
-                ship: SHIP.unknown,
-                ship_cell: Coord { 0, 0 },
-                ship_v: false
-              };
+    /// Returns the graph of imports between the files of the codebase.
+    ///
+    /// Imports of files that are not part of the codebase (e.g. the standard library) are not
+    /// included.
+    #[must_use = "Use this function to get the dependencies between files"]
+    pub fn file_dependency_graph(&self) -> DependencyGraph {
+        let mut graph = DependencyGraph::default();
+        for file in &self.files {
+            graph.add_file(&file.file_path);
+            for import in file.ast.imports() {
+                let imported = import.name();
+                if self.files.iter().any(|f| f.file_path == imported) {
+                    graph.add_dependency(&file.file_path, &imported);
+                }
             }
-        ";
-        let source_b = r#"
-            import "./a.compact";
-            pure circuit calculate_ship_def(shot_attempt: Coord, ship_state: ShipState, updated_ship_state: ShipState, ships: Ships, player: Bytes<32>): ShotResult {
-                return unknown_ship_def();
+        }
+        graph
+    }
+
+    /// Returns the symbols exported by `file_path` that are not declared in the file itself,
+    /// together with the path of the file declaring them.
+    ///
+    /// The origin is resolved through the file imports, following re-export chains. Exported
+    /// names that cannot be resolved are skipped.
+    #[must_use = "Use this function to find the re-exported symbols of a file"]
+    pub fn reexported_symbols(&self, file_path: &str) -> Vec<(String, String)> {
+        let Some(file) = self.files.iter().find(|file| file.file_path == file_path) else {
+            return Vec::new();
+        };
+        exported_names(&file.ast)
+            .into_iter()
+            .filter(|name| !declares_symbol(&file.ast, name))
+            .filter_map(|name| {
+                let origin = self.symbol_origin(file_path, &name, &mut HashSet::new())?;
+                Some((name, origin))
+            })
+            .collect()
+    }
+
+    fn symbol_origin(
+        &self,
+        file_path: &str,
+        name: &str,
+        visited: &mut HashSet<String>,
+    ) -> Option<String> {
+        if !visited.insert(file_path.to_string()) {
+            return None;
+        }
+        for imported in self.imported_files(file_path) {
+            if declares_symbol(&imported.ast, name) {
+                return Some(imported.file_path.clone());
             }
-        "#;
-        codebase.add_file("./a.compact", source_a);
-        codebase.add_file("./b.compact", source_b);
-        let sealed = codebase.seal()?;
-        let unknown_ship_def_node_id = sealed
-            .list_nodes_cmp(|node| {
-                if let NodeType::Definition(Definition::Circuit(circuit)) = node {
-                    if circuit.name() == "unknown_ship_def" {
-                        return Some(node.id());
-                    }
+            if exported_names(&imported.ast).iter().any(|n| n == name) {
+                if let Some(origin) = self.symbol_origin(&imported.file_path, name, visited) {
+                    return Some(origin);
                 }
-                None
-            })
-            .next()
-            .expect("unknown_ship_def node not found");
-        let ship_def_type = sealed
-            .get_symbol_type_by_id(unknown_ship_def_node_id)
-            .unwrap_or_else(|| {
-                panic!("Type for unknown_ship_def not found [{unknown_ship_def_node_id}]")
-            });
-        match ship_def_type {
-            Type::Ref(ref ty) => {
-                assert_eq!(ty.name(), "ShipDef");
             }
-            _ => panic!("Expected a reference type for unknown_ship_def"),
         }
-        Ok(())
+        None
     }
 
-    #[test]
-    fn test_function_call_single_file_reference_resolution() -> anyhow::Result<()> {
-        let mut codebase = Codebase::<OpenState>::new();
-        let source_a = r"
-             export pure circuit unknown_ship_def(): ShipDef {
-               return ShipDef {
-                 ship: SHIP.unknown,
-                 ship_cell: Coord { 0, 0 },
-                 ship_v: false
-               };
-             }
+    fn imported_files(&self, file_path: &str) -> Vec<&SourceCodeFile> {
+        self.list_nodes_cmp(|node| {
+            if let NodeType::Declaration(Declaration::Import(import)) = node {
+                import
+                    .reference
+                    .as_ref()
+                    .map(|program| (import.id, program.id))
+            } else {
+                None
+            }
+        })
+        .filter(|(import_id, _)| {
+            self.find_node_file(*import_id)
+                .is_some_and(|file| file.file_path == file_path)
+        })
+        .filter_map(|(_, program_id)| self.files.iter().find(|file| file.ast.id == program_id))
+        .collect()
+    }
 
-            pure circuit calculate_ship_def(shot_attempt: Coord, ship_state: ShipState, updated_ship_state: ShipState, ships: Ships, player: Bytes<32>): ShotResult {
-               return unknown_ship_def();
+    /// Returns the ids of the `return` statements of the circuit with the given id, in source
+    /// order, followed by the id of its body if the end of the body can be reached without a
+    /// `return` (the implicit fall-through exit).
+    #[must_use = "Use this function to get the exit points of a circuit"]
+    pub fn exit_points(&self, circuit_id: u32) -> Vec<u32> {
+        let Some(NodeType::Definition(Definition::Circuit(circuit))) =
+            self.storage.find_node_ref(circuit_id)
+        else {
+            return Vec::new();
+        };
+        let Some(body) = &circuit.body else {
+            return Vec::new();
+        };
+        let mut exit_points: Vec<u32> = body
+            .all_statements()
+            .iter()
+            .filter_map(|stmt| match stmt {
+                Statement::Return(ret) => Some(ret.id),
+                _ => None,
+            })
+            .collect();
+        if falls_through(&body.statements) {
+            exit_points.push(body.id);
+        }
+        exit_points
+    }
+
+    /// Returns the ledger reads and writes, witness and external calls and the number of asserts
+    /// of the circuit with the given id.
+    ///
+    /// Ledger fields declared in the circuit file or in the files it imports are considered.
+    /// Names bound by the circuit parameters or `const` declarations shadow ledger fields.
+    /// Returns an empty summary if the id is not a circuit.
+    #[must_use = "Use this function to get the side effects of a circuit"]
+    pub fn circuit_effects(&self, circuit_id: u32) -> CircuitEffects {
+        let Some(NodeType::Definition(Definition::Circuit(circuit))) =
+            self.storage.find_node(circuit_id)
+        else {
+            return CircuitEffects::default();
+        };
+        let mut effects = CircuitEffects {
+            asserts: circuit.assert_count(),
+            ..CircuitEffects::default()
+        };
+        for access in self.ledger_accesses(&circuit) {
+            let names = if access.is_write {
+                &mut effects.ledger_writes
+            } else {
+                &mut effects.ledger_reads
+            };
+            if !names.contains(&access.name) {
+                names.push(access.name);
             }
-         ";
-        codebase.add_file("./a.compact", source_a);
-        let sealed = codebase.seal()?;
+        }
+        let witnesses: HashSet<String> = self
+            .visible_programs(circuit.id)
+            .iter()
+            .flat_map(|program| program.declarations.iter())
+            .filter_map(|declaration| match declaration {
+                Declaration::Witness(witness) => Some(witness.name()),
+                _ => None,
+            })
+            .collect();
+        for node in self.circuit_nodes(circuit.id) {
+            let NodeType::Expression(Expression::FunctionCall(function_call)) = node else {
+                continue;
+            };
+            let Expression::Function(Function::Named(function)) = &function_call.function else {
+                continue;
+            };
+            let name = function.name().to_string();
+            let calls = if witnesses.contains(&name) {
+                &mut effects.witness_calls
+            } else if function_call
+                .reference
+                .as_ref()
+                .is_some_and(|callee| callee.body.is_some())
+            {
+                continue;
+            } else {
+                &mut effects.external_calls
+            };
+            if !calls.contains(&name) {
+                calls.push(name);
+            }
+        }
+        effects
+    }
 
-        let unknown_ship_def_circuit_node = sealed
-            .list_nodes_cmp(|node| {
-                if let NodeType::Definition(Definition::Circuit(circuit)) = node {
-                    if circuit.name() == "unknown_ship_def" {
-                        return Some(circuit.clone());
+    /// Returns the asserts with a missing or blank message, or with a condition that is always
+    /// true or always false, ordered by id.
+    ///
+    /// Conditions are folded from boolean literals with `!`, `&&`, `||`, `==` and `!=`.
+    #[must_use = "Use this function to find asserts that are weak"]
+    pub fn weak_asserts(&self) -> Vec<WeakAssert> {
+        let mut seen = HashSet::new();
+        let mut weak_asserts: Vec<WeakAssert> = self
+            .list_assert_nodes()
+            .filter(|assert| seen.insert(assert.id))
+            .filter_map(|assert| {
+                let mut reasons = Vec::new();
+                match assert.message() {
+                    None => reasons.push(WeakAssertReason::MissingMessage),
+                    Some(message) if message.trim().is_empty() => {
+                        reasons.push(WeakAssertReason::BlankMessage);
                     }
+                    Some(_) => {}
                 }
-                None
-            })
-            .next()
-            .expect("unknown_ship_def node not found");
-        let function_call_node = sealed
-            .list_nodes_cmp(|node| {
-                if let NodeType::Expression(Expression::FunctionCall(func_call)) = node {
-                    return Some(func_call.clone());
+                match constant_bool(&assert.condition) {
+                    Some(true) => reasons.push(WeakAssertReason::AlwaysTrue),
+                    Some(false) => reasons.push(WeakAssertReason::AlwaysFalse),
+                    None => {}
                 }
-                None
+                (!reasons.is_empty()).then_some(WeakAssert {
+                    id: assert.id,
+                    reasons,
+                })
             })
-            .next()
-            .expect("Function call node not found");
-        assert_eq!(
-            function_call_node.reference.as_ref().unwrap().id,
-            unknown_ship_def_circuit_node.id,
-            "Function call reference should be set to the correct circuit id, expected: {}, found: {}",
-            unknown_ship_def_circuit_node.id, function_call_node.reference.as_ref().unwrap().id
-        );
-        Ok(())
+            .collect();
+        weak_asserts.sort_by_key(|weak_assert| weak_assert.id);
+        weak_asserts
     }
 
-    #[test]
+    /// Returns the ids of the assignments whose target resolves to a local binding (a parameter,
+    /// `const`, `var` or loop counter declared earlier in the circuit) while a ledger field with
+    /// the same name is visible, so the intended ledger write only updates the local.
+    ///
+    /// Bindings are not scoped to their block, a binding declared in an earlier branch also
+    /// shadows the ledger field.
+    #[must_use = "Use this function to find ledger writes shadowed by local bindings"]
+    pub fn ledger_write_shadowed_by_local(&self) -> Vec<u32> {
+        let mut ids = Vec::new();
+        for circuit in self.circuits() {
+            let ledgers: HashSet<String> = self
+                .visible_programs(circuit.id)
+                .iter()
+                .flat_map(|program| program.declarations.iter())
+                .filter_map(|declaration| match declaration {
+                    Declaration::Ledger(ledger) => Some(ledger.name()),
+                    _ => None,
+                })
+                .collect();
+            if ledgers.is_empty() {
+                continue;
+            }
+            let mut locals: Vec<(String, u32)> = circuit
+                .arguments
+                .iter()
+                .filter_map(|argument| argument.name())
+                .map(|name| (name, circuit.location.offset_start))
+                .collect();
+            for statement in circuit.body.iter().flat_map(|body| body.all_statements()) {
+                let local = match &statement {
+                    Statement::Const(const_stmt) => match &const_stmt.pattern {
+                        Pattern::Identifier(identifier) => Some(identifier.name.clone()),
+                        _ => None,
+                    },
+                    Statement::Var(var) => Some(var.ident.name.clone()),
+                    Statement::For(for_stmt) => Some(for_stmt.counter.name.clone()),
+                    _ => None,
+                };
+                if let Some(name) = local {
+                    locals.push((name, statement.location().offset_start));
+                }
+            }
+            for node in self.circuit_nodes(circuit.id) {
+                let NodeType::Statement(Statement::Assign(assign)) = node else {
+                    continue;
+                };
+                let Some(target) = root_identifier(&assign.target) else {
+                    continue;
+                };
+                if ledgers.contains(&target.name)
+                    && locals.iter().any(|(name, offset)| {
+                        *name == target.name && *offset < assign.location.offset_start
+                    })
+                {
+                    ids.push(assign.id);
+                }
+            }
+        }
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+
+    /// Returns the ids of the circuit parameters whose name collides with a ledger field visible
+    /// from the circuit, declared in its file or in a file it imports, ordered by id. Inside the
+    /// circuit the parameter hides the ledger field.
+    ///
+    /// Compact has no module level constants, so ledger fields are the only outer bindings a
+    /// parameter can shadow.
+    #[must_use = "Use this function to find parameters shadowing ledger fields"]
+    pub fn parameters_shadowing_outer(&self) -> Vec<u32> {
+        let mut ids = Vec::new();
+        for circuit in self.circuits() {
+            let ledgers: HashSet<String> = self
+                .visible_programs(circuit.id)
+                .iter()
+                .flat_map(|program| program.declarations.iter())
+                .filter_map(|declaration| match declaration {
+                    Declaration::Ledger(ledger) => Some(ledger.name()),
+                    _ => None,
+                })
+                .collect();
+            ids.extend(
+                circuit
+                    .arguments
+                    .iter()
+                    .filter(|argument| argument.name().is_some_and(|name| ledgers.contains(&name)))
+                    .map(|argument| argument.id),
+            );
+        }
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+
+    /// Returns the ids of the exported circuits that write a ledger field but contain no `assert`
+    /// at all, which usually means the write is not access controlled.
+    #[must_use = "Use this function to find exported circuits writing the ledger without checks"]
+    pub fn circuits_writing_ledger_without_assert(&self) -> Vec<u32> {
+        let mut ids: Vec<u32> = self
+            .circuits()
+            .into_iter()
+            .filter(|circuit| circuit.is_exported && circuit.assert_count() == 0)
+            .filter(|circuit| {
+                self.ledger_accesses(circuit)
+                    .iter()
+                    .any(|access| access.is_write)
+            })
+            .map(|circuit| circuit.id)
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+
+    /// Returns the ids of the asserts that dominate the ledger write with the given id, i.e. the
+    /// asserts executed on every path from the start of the circuit to the write, in source order.
+    ///
+    /// Asserts in an `if` branch or a `for` body are only considered when the write is in the
+    /// same branch or body.
+    #[must_use = "Use this function to find the asserts guarding a ledger write"]
+    pub fn guarding_asserts(&self, write_id: u32) -> Vec<u32> {
+        self.dominating_statements(write_id)
+            .into_iter()
+            .filter_map(|statement| match statement {
+                Statement::Assert(assert) => Some(assert.id),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns the statements executed before the node with the given id on every path from the
+    /// start of its circuit, in source order. Preceding `if` and `for` statements are returned
+    /// as a whole, their branches and bodies are only entered when they contain the node.
+    fn dominating_statements(&self, id: u32) -> Vec<Statement> {
+        let Some(node) = self.storage.find_node_ref(id) else {
+            return Vec::new();
+        };
+        let Some(NodeType::Definition(Definition::Circuit(circuit))) =
+            self.get_parent_container(id)
+        else {
+            return Vec::new();
+        };
+        let Some(body) = &circuit.body else {
+            return Vec::new();
+        };
+        let mut dominators = Vec::new();
+        collect_dominators(
+            &body.statements,
+            node.location().offset_start,
+            &mut dominators,
+        );
+        dominators
+    }
+
+    /// Returns the id of the first ledger write of the circuit with the given id that precedes
+    /// the first `assert` of the circuit, in source order. If the circuit has no assert, its first
+    /// ledger write is returned.
+    #[must_use = "Use this function to find a ledger write performed before any check"]
+    pub fn write_before_any_assert(&self, circuit_id: u32) -> Option<u32> {
+        let Some(NodeType::Definition(Definition::Circuit(circuit))) =
+            self.storage.find_node_ref(circuit_id)
+        else {
+            return None;
+        };
+        let first_assert = circuit
+            .body
+            .iter()
+            .flat_map(|body| body.all_statements())
+            .find_map(|statement| match statement {
+                Statement::Assert(assert) => Some(assert.location.offset_start),
+                _ => None,
+            })
+            .unwrap_or(u32::MAX);
+        self.ledger_accesses(circuit)
+            .into_iter()
+            .find(|access| access.is_write && access.offset < first_assert)
+            .map(|access| access.id)
+    }
+
+    /// Returns `(assert id, write id)` pairs of asserts that follow a ledger write in the same
+    /// circuit, violating the checks-before-effects pattern.
+    ///
+    /// Each assert is paired with the first ledger write executed before it on every path, i.e.
+    /// contained in one of its dominating statements. Writes inside a preceding `if` or `for`
+    /// statement do not dominate the assert and are ignored.
+    #[must_use = "Use this function to find asserts placed after ledger writes"]
+    pub fn asserts_after_write(&self) -> Vec<(u32, u32)> {
+        let mut pairs = Vec::new();
+        for circuit in self.circuits() {
+            let writes: Vec<LedgerAccess> = self
+                .ledger_accesses(&circuit)
+                .into_iter()
+                .filter(|access| access.is_write)
+                .collect();
+            if writes.is_empty() {
+                continue;
+            }
+            for node in self.circuit_nodes(circuit.id) {
+                let NodeType::Statement(Statement::Assert(assert)) = node else {
+                    continue;
+                };
+                let dominators: Vec<Location> = self
+                    .dominating_statements(assert.id)
+                    .iter()
+                    .filter(|statement| !matches!(statement, Statement::If(_) | Statement::For(_)))
+                    .map(Statement::location)
+                    .collect();
+                if let Some(write) = writes.iter().find(|write| {
+                    dominators.iter().any(|location| {
+                        location.offset_start <= write.offset && write.offset < location.offset_end
+                    })
+                }) {
+                    pairs.push((assert.id, write.id));
+                }
+            }
+        }
+        pairs
+    }
+
+    /// Returns `(witness call id, sink id)` pairs of witness results reaching a ledger write or a
+    /// `return` statement of the same circuit without being checked by an `assert` first, ordered
+    /// by witness call id, then by sink id.
+    ///
+    /// Witness results are tracked through `const`, `var` and local assignments in source order,
+    /// so a name tainted in an `if` branch stays tainted in the statements following the `if`. A
+    /// name is checked at a sink when it appears in the condition of an `assert` dominating the
+    /// sink (see `guarding_asserts`).
+    #[must_use = "Use this function to find unchecked witness results"]
+    pub fn unchecked_witness_flows(&self) -> Vec<(u32, u32)> {
+        let mut flows: Vec<(u32, u32)> = self
+            .circuits()
+            .iter()
+            .flat_map(|circuit| self.circuit_witness_flows(circuit))
+            .collect();
+        flows.sort_unstable();
+        flows.dedup();
+        flows
+    }
+
+    /// Returns the unchecked witness flows of a single circuit, see `unchecked_witness_flows`.
+    fn circuit_witness_flows(&self, circuit: &Circuit) -> Vec<(u32, u32)> {
+        let witnesses: HashSet<String> = self
+            .visible_programs(circuit.id)
+            .iter()
+            .flat_map(|program| program.declarations.iter())
+            .filter_map(|declaration| match declaration {
+                Declaration::Witness(witness) => Some(witness.name()),
+                _ => None,
+            })
+            .collect();
+        let ledger_writes: HashSet<u32> = self
+            .ledger_accesses(circuit)
+            .into_iter()
+            .filter(|access| access.is_write)
+            .map(|access| access.id)
+            .collect();
+        let nodes = self.circuit_nodes(circuit.id);
+        let mut tainted: HashMap<String, u32> = HashMap::new();
+        let mut flows = Vec::new();
+        for node in &nodes {
+            let (taint, sink) = match node {
+                NodeType::Statement(Statement::Const(const_stmt)) => match &const_stmt.pattern {
+                    Pattern::Identifier(identifier) => {
+                        (Some((&identifier.name, const_stmt.value.location())), None)
+                    }
+                    _ => (None, None),
+                },
+                NodeType::Statement(Statement::Var(var)) => {
+                    (Some((&var.ident.name, var.value.location())), None)
+                }
+                NodeType::Statement(Statement::Assign(assign))
+                    if ledger_writes.contains(&assign.id) =>
+                {
+                    (None, Some((assign.id, assign.value.location())))
+                }
+                NodeType::Statement(Statement::Assign(assign)) => match &assign.target {
+                    Expression::Identifier(target) => {
+                        (Some((&target.name, assign.value.location())), None)
+                    }
+                    _ => (None, None),
+                },
+                NodeType::Expression(Expression::MemberAccess(member_access))
+                    if ledger_writes.contains(&member_access.id) =>
+                {
+                    (
+                        None,
+                        Some((member_access.id, member_access.location.clone())),
+                    )
+                }
+                NodeType::Statement(Statement::Return(ret)) => (
+                    None,
+                    ret.value.as_ref().map(|value| (ret.id, value.location())),
+                ),
+                _ => (None, None),
+            };
+            if let Some((name, value)) = taint {
+                let sources = witness_sources(&nodes, &witnesses, &tainted, &value);
+                if let Some((source, _)) = sources.first() {
+                    tainted.insert(name.clone(), *source);
+                }
+            }
+            if let Some((sink_id, value)) = sink {
+                let checked = checked_names(&nodes, &self.guarding_asserts(sink_id));
+                flows.extend(
+                    witness_sources(&nodes, &witnesses, &tainted, &value)
+                        .into_iter()
+                        .filter(|(_, name)| {
+                            name.as_ref().is_none_or(|name| !checked.contains(name))
+                        })
+                        .map(|(source, _)| (source, sink_id)),
+                );
+            }
+        }
+        flows
+    }
+
+    /// Returns the ids of the parameters of the circuit with the given id that no identifier
+    /// resolves to (see `find_references`), in declaration order. Parameters bound by tuple or
+    /// struct patterns and parameters of circuits without a body are not reported.
+    #[must_use = "Use this function to find the unused parameters of a circuit"]
+    pub fn unused_parameters(&self, circuit_id: u32) -> Vec<u32> {
+        let Some(NodeType::Definition(Definition::Circuit(circuit))) =
+            self.storage.find_node_ref(circuit_id)
+        else {
+            return Vec::new();
+        };
+        if circuit.body.is_none() {
+            return Vec::new();
+        }
+        circuit
+            .arguments
+            .iter()
+            .filter(|argument| matches!(argument.pattern, Pattern::Identifier(_)))
+            .filter(|argument| self.find_references(argument.id).is_empty())
+            .map(|argument| argument.id)
+            .collect()
+    }
+
+    /// Returns the number of distinct circuits calling the circuit with the given id.
+    #[must_use = "Use this function to get the number of callers of a circuit"]
+    pub fn fan_in(&self, circuit_id: u32) -> usize {
+        self.call_edges()
+            .iter()
+            .filter(|(_, callee)| *callee == circuit_id)
+            .count()
+    }
+
+    /// Returns the number of distinct circuits called by the circuit with the given id.
+    #[must_use = "Use this function to get the number of callees of a circuit"]
+    pub fn fan_out(&self, circuit_id: u32) -> usize {
+        self.call_edges()
+            .iter()
+            .filter(|(caller, _)| *caller == circuit_id)
+            .count()
+    }
+
+    /// Checks if the circuit with the given id depends on private inputs, i.e. it calls a witness
+    /// directly or through any of the circuits it calls.
+    #[must_use = "Use this function to check if a circuit depends on a witness"]
+    pub fn uses_witness(&self, circuit_id: u32) -> bool {
+        let edges = self.call_edges();
+        let mut visited = HashSet::from([circuit_id]);
+        let mut pending = vec![circuit_id];
+        while let Some(current) = pending.pop() {
+            if !self.circuit_effects(current).witness_calls.is_empty() {
+                return true;
+            }
+            for (_, callee) in edges.iter().filter(|(caller, _)| *caller == current) {
+                if visited.insert(*callee) {
+                    pending.push(*callee);
+                }
+            }
+        }
+        false
+    }
+
+    /// Returns the non-exported circuits that cannot be reached through the call graph from an
+    /// entry point, i.e. an exported circuit or the constructor. External circuits, which have no
+    /// body, are not reported. Circuits are ordered by id.
+    #[must_use = "Use this function to find circuits that are never called from an entry point"]
+    pub fn unreachable_circuits(&self) -> Vec<Rc<Circuit>> {
+        let edges = self.call_edges();
+        let mut circuits = self.circuits();
+        circuits.sort_by_key(|circuit| circuit.id);
+        circuits.dedup_by_key(|circuit| circuit.id);
+        let mut reachable: HashSet<u32> = circuits
+            .iter()
+            .filter(|circuit| circuit.is_exported)
+            .map(|circuit| circuit.id)
+            .collect();
+        reachable.extend(self.list_nodes_cmp(|node| match node {
+            NodeType::Expression(Expression::FunctionCall(function_call)) => {
+                let callee = function_call.reference.as_ref()?;
+                matches!(
+                    self.get_parent_container(function_call.id),
+                    Some(NodeType::Declaration(Declaration::Constructor(_)))
+                )
+                .then_some(callee.id)
+            }
+            _ => None,
+        }));
+        let mut pending: Vec<u32> = reachable.iter().copied().collect();
+        while let Some(current) = pending.pop() {
+            for (_, callee) in edges.iter().filter(|(caller, _)| *caller == current) {
+                if reachable.insert(*callee) {
+                    pending.push(*callee);
+                }
+            }
+        }
+        circuits
+            .into_iter()
+            .filter(|circuit| {
+                !circuit.is_exported && !circuit.is_external() && !reachable.contains(&circuit.id)
+            })
+            .collect()
+    }
+
+    /// Checks if the body of the circuit with the given id contains a `for` loop, at any depth.
+    /// Loops in the circuits it calls are not considered.
+    #[must_use = "Use this function to check if a circuit contains a loop"]
+    pub fn circuit_contains_loop(&self, circuit_id: u32) -> bool {
+        self.circuit_nodes(circuit_id)
+            .into_iter()
+            .any(|node| matches!(node, NodeType::Statement(Statement::For(_))))
+    }
+
+    /// Returns the distinct `(caller, callee)` circuit id pairs of the resolved function calls.
+    fn call_edges(&self) -> HashSet<(u32, u32)> {
+        self.list_nodes_cmp(|node| match node {
+            NodeType::Expression(Expression::FunctionCall(function_call)) => function_call
+                .reference
+                .as_ref()
+                .map(|callee| (function_call.id, callee.id)),
+            _ => None,
+        })
+        .filter_map(
+            |(call_id, callee_id)| match self.get_parent_container(call_id) {
+                Some(NodeType::Definition(Definition::Circuit(caller))) => {
+                    Some((caller.id, callee_id))
+                }
+                _ => None,
+            },
+        )
+        .collect()
+    }
+
+    /// Returns all circuits of the codebase, including circuits declared in modules.
+    fn circuits(&self) -> Vec<Rc<Circuit>> {
+        self.list_nodes_cmp(|node| {
+            if let NodeType::Definition(Definition::Circuit(circuit)) = node {
+                Some(circuit.clone())
+            } else {
+                None
+            }
+        })
+        .collect()
+    }
+
+    /// Returns the stored nodes of the subtree of the circuit with the given id, ordered by their
+    /// position in the source.
+    fn circuit_nodes(&self, circuit_id: u32) -> Vec<&NodeType> {
+        let mut seen = HashSet::new();
+        let mut nodes: Vec<&NodeType> = self
+            .descendants_of(circuit_id)
+            .filter(|node| seen.insert(node.id()))
+            .collect();
+        nodes.sort_by_key(|node| node.location().offset_start);
+        nodes
+    }
+
+    /// Returns the program of the file declaring the node and the programs it imports.
+    fn visible_programs(&self, id: u32) -> Vec<Rc<Program>> {
+        let Some(file) = self.find_node_file(id) else {
+            return Vec::new();
+        };
+        std::iter::once(file.ast.clone())
+            .chain(
+                self.imported_files(&file.file_path)
+                    .into_iter()
+                    .map(|imported| imported.ast.clone()),
+            )
+            .collect()
+    }
+
+    /// Returns the ledger reads and writes of a circuit, ordered by their position in the source.
+    fn ledger_accesses(&self, circuit: &Circuit) -> Vec<LedgerAccess> {
+        let ledgers: HashSet<String> = self
+            .visible_programs(circuit.id)
+            .iter()
+            .flat_map(|program| program.declarations.iter())
+            .filter_map(|declaration| match declaration {
+                Declaration::Ledger(ledger) => Some(ledger.name()),
+                _ => None,
+            })
+            .collect();
+        let locals = circuit_local_names(circuit);
+        let is_ledger = |identifier: &Identifier| {
+            ledgers.contains(&identifier.name) && !locals.contains(&identifier.name)
+        };
+        let nodes = self.circuit_nodes(circuit.id);
+        let mut accesses = Vec::new();
+        let mut not_reads: HashSet<u32> = HashSet::new();
+        for node in &nodes {
+            match node {
+                NodeType::Statement(Statement::Assign(assign)) => {
+                    let Some(target) = root_identifier(&assign.target) else {
+                        continue;
+                    };
+                    if matches!(assign.operator, AssignOperator::Simple) {
+                        not_reads.insert(target.id);
+                    }
+                    if is_ledger(target) {
+                        accesses.push(LedgerAccess {
+                            id: assign.id,
+                            name: target.name.clone(),
+                            is_write: true,
+                            offset: assign.location.offset_start,
+                        });
+                    }
+                }
+                NodeType::Expression(Expression::MemberAccess(member_access)) => {
+                    not_reads.insert(member_access.member.id);
+                    if member_access.arguments.is_none()
+                        || !LEDGER_WRITE_OPERATIONS.contains(&member_access.member.name.as_str())
+                    {
+                        continue;
+                    }
+                    let Some(base) = root_identifier(&member_access.base) else {
+                        continue;
+                    };
+                    not_reads.insert(base.id);
+                    if is_ledger(base) {
+                        accesses.push(LedgerAccess {
+                            id: member_access.id,
+                            name: base.name.clone(),
+                            is_write: true,
+                            offset: member_access.location.offset_start,
+                        });
+                    }
+                }
+                NodeType::Expression(Expression::Function(Function::Named(function))) => {
+                    not_reads.insert(function.name.id);
+                }
+                NodeType::StructExprArg(StructExprArg::NamedField(field)) => {
+                    not_reads.insert(field.name.id);
+                }
+                _ => {}
+            }
+        }
+        for node in &nodes {
+            if let NodeType::Expression(Expression::Identifier(identifier)) = node {
+                if is_ledger(identifier) && !not_reads.contains(&identifier.id) {
+                    accesses.push(LedgerAccess {
+                        id: identifier.id,
+                        name: identifier.name.clone(),
+                        is_write: false,
+                        offset: identifier.location.offset_start,
+                    });
+                }
+            }
+        }
+        accesses.sort_by_key(|access| access.offset);
+        accesses
+    }
+
+    /// Returns the ids of the declarations whose name is one of the `reserved` words, e.g. words
+    /// expected to become keywords in future versions of the language.
+    ///
+    /// Checked declarations are definitions, ledgers, witnesses, parameters, struct fields,
+    /// `const` and `var` bindings and loop counters.
+    #[must_use = "Use this function to find declarations named after reserved words"]
+    pub fn identifiers_matching_reserved(&self, reserved: &[&str]) -> Vec<u32> {
+        let mut ids = self
+            .list_nodes_cmp(|node| {
+                let name = match node {
+                    NodeType::Statement(Statement::Const(const_stmt)) => {
+                        match &const_stmt.pattern {
+                            Pattern::Identifier(identifier) => Some(identifier.name.clone()),
+                            _ => None,
+                        }
+                    }
+                    NodeType::Statement(Statement::Var(var)) => Some(var.ident.name.clone()),
+                    NodeType::Statement(Statement::For(for_stmt)) => {
+                        Some(for_stmt.counter.name.clone())
+                    }
+                    NodeType::Expression(_) => None,
+                    _ => node_name(node),
+                }?;
+                reserved.contains(&name.as_str()).then_some(node.id())
+            })
+            .collect::<Vec<_>>();
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+
+    /// Returns the type of the expression, if it can be determined.
+    ///
+    /// Types are known for identifiers resolved by the symbol table, casts, boolean and string
+    /// literals and single-element sequences.
+    #[must_use = "Use this function to get the type of an expression"]
+    pub fn type_of_expression(&self, expression: &Expression) -> Option<Type> {
+        match expression {
+            Expression::Cast(cast) => Some(cast.target_type.clone()),
+            Expression::Literal(Literal::Bool(value)) => {
+                Some(Type::Boolean(Rc::new(TypeBool::new(value))))
+            }
+            Expression::Literal(Literal::Str(value)) => {
+                Some(Type::String(Rc::new(TypeString::new(value))))
+            }
+            Expression::Sequence(sequence) if sequence.expressions.len() == 1 => {
+                self.type_of_expression(&sequence.expressions[0])
+            }
+            _ => self.get_symbol_type_by_id(expression.id()),
+        }
+    }
+
+    /// Returns the ids of the casts whose operand already has the target type, ordered by id.
+    #[must_use = "Use this function to find redundant casts"]
+    pub fn redundant_casts(&self) -> Vec<u32> {
+        let mut ids: Vec<u32> = self
+            .list_nodes_cmp(|node| match node {
+                NodeType::Expression(Expression::Cast(cast))
+                    if self
+                        .type_of_expression(&cast.expression)
+                        .is_some_and(|ty| ty.structurally_eq(&cast.target_type)) =>
+                {
+                    Some(cast.id)
+                }
+                _ => None,
+            })
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+
+    /// Returns the ids of the `return` statements whose value has a known type that does not match
+    /// the declared return type of their circuit (see `Type::matches`), ordered by id. Values of
+    /// unknown type, e.g. number literals, are not reported.
+    #[must_use = "Use this function to find returns mismatching the circuit return type"]
+    pub fn mismatched_returns(&self) -> Vec<u32> {
+        let mut ids = Vec::new();
+        for circuit in self.circuits() {
+            let (Some(return_type), Some(body)) = (circuit.return_type(), &circuit.body) else {
+                continue;
+            };
+            for statement in body.all_statements() {
+                let Statement::Return(return_stmt) = statement else {
+                    continue;
+                };
+                if return_stmt
+                    .value
+                    .as_ref()
+                    .and_then(|value| self.type_of_expression(value))
+                    .is_some_and(|ty| !ty.matches(&return_type))
+                {
+                    ids.push(return_stmt.id);
+                }
+            }
+        }
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+
+    /// Returns the ids of the `Vector<N, T>` and `Bytes<N>` types, wherever they are written,
+    /// whose literal size `N` is greater than `max_elems`, ordered by id. Vectors sized by a
+    /// generic parameter are not reported.
+    #[must_use = "Use this function to find oversized collection types"]
+    pub fn oversized_collections(&self, max_elems: u64) -> Vec<u32> {
+        let mut ids: Vec<u32> = self
+            .list_nodes_cmp(|node| match node {
+                NodeType::Type(Type::Vector(vector))
+                    if vector.size_nat().is_some_and(|size| size > max_elems) =>
+                {
+                    Some(vector.id)
+                }
+                NodeType::Type(Type::Bytes(bytes)) if bytes.width() > max_elems => Some(bytes.id),
+                _ => None,
+            })
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+
+    /// Returns the ids of the division expressions whose operands both have an integer type
+    /// (`Uint`, `Field` or a natural number literal), ordered by id.
+    #[must_use = "Use this function to find integer divisions"]
+    pub fn integer_divisions(&self) -> Vec<u32> {
+        let mut ids: Vec<u32> = self
+            .list_nodes_cmp(|node| match node {
+                NodeType::Expression(Expression::Binary(binary))
+                    if binary.operator == BinaryExpressionOperator::Div
+                        && self.is_integer_operand(&binary.left)
+                        && self.is_integer_operand(&binary.right) =>
+                {
+                    Some(binary.id)
+                }
+                _ => None,
+            })
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+
+    /// Returns the minimum number of bits needed to represent every result of the binary
+    /// expression with the given id, when its operands are `Uint` values or natural number
+    /// literals, e.g. 9 for the sum of two `Uint<8>` values.
+    ///
+    /// Additions need one bit more than their widest operand, multiplications the sum of the
+    /// operand widths, and subtractions, divisions and remainders no more than their widest
+    /// operand. Operands that are themselves arithmetic expressions are resolved recursively.
+    /// Returns `None` for other operators or when an operand width is unknown.
+    #[must_use = "Use this function to get the width of the result of an arithmetic expression"]
+    pub fn expression_result_width(&self, id: u32) -> Option<u32> {
+        let NodeType::Expression(Expression::Binary(binary)) = self.storage.find_node(id)? else {
+            return None;
+        };
+        self.binary_result_width(&binary)
+    }
+
+    fn binary_result_width(&self, binary: &Binary) -> Option<u32> {
+        let left = self.operand_width(&binary.left)?;
+        let right = self.operand_width(&binary.right)?;
+        match binary.operator {
+            BinaryExpressionOperator::Add => left.max(right).checked_add(1),
+            BinaryExpressionOperator::Mul => left.checked_add(right),
+            BinaryExpressionOperator::Sub
+            | BinaryExpressionOperator::Div
+            | BinaryExpressionOperator::Mod => Some(left.max(right)),
+            _ => None,
+        }
+    }
+
+    /// Returns the number of bits needed by the values of an arithmetic operand.
+    fn operand_width(&self, expression: &Expression) -> Option<u32> {
+        match expression {
+            Expression::Binary(binary) => self.binary_result_width(binary),
+            Expression::Literal(Literal::Nat(nat)) => {
+                Some((u64::BITS - nat.value.leading_zeros()).max(1))
+            }
+            Expression::Sequence(sequence) if sequence.expressions.len() == 1 => {
+                self.operand_width(&sequence.expressions[0])
+            }
+            _ => match self.type_of_expression(expression)? {
+                Type::Uint(uint) => Some(uint.bit_width()),
+                _ => None,
+            },
+        }
+    }
+
+    /// Returns the ids of the `==` and `!=` expressions with a boolean literal operand, e.g.
+    /// `flag == true`, ordered by id.
+    #[must_use = "Use this function to find comparisons against boolean literals"]
+    pub fn boolean_literal_comparisons(&self) -> Vec<u32> {
+        let mut ids: Vec<u32> = self
+            .list_nodes_cmp(|node| match node {
+                NodeType::Expression(Expression::Binary(binary))
+                    if matches!(
+                        binary.operator,
+                        BinaryExpressionOperator::Eq | BinaryExpressionOperator::Ne
+                    ) && [&binary.left, &binary.right].iter().any(|operand| {
+                        matches!(operand, Expression::Literal(Literal::Bool(_)))
+                    }) =>
+                {
+                    Some(binary.id)
+                }
+                _ => None,
+            })
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+
+    /// Checks if the expression is known to have an integer type.
+    fn is_integer_operand(&self, expression: &Expression) -> bool {
+        match expression {
+            Expression::Literal(Literal::Nat(_)) => true,
+            _ => matches!(
+                self.type_of_expression(expression),
+                Some(Type::Uint(_) | Type::Field(_))
+            ),
+        }
+    }
+
+    /// Checks if the expression with the given id only depends on literals and `const` bindings
+    /// with constant values.
+    ///
+    /// Identifiers are resolved to the closest preceding `const` declaration of the enclosing
+    /// circuit. Function calls, member and index accesses and parameters are not constant.
+    #[must_use = "Use this function to check if an expression is constant"]
+    pub fn is_constant_expression(&self, id: u32) -> bool {
+        let Some(NodeType::Expression(expression)) = self.storage.find_node(id) else {
+            return false;
+        };
+        let circuit = match self.get_parent_container(id) {
+            Some(NodeType::Definition(Definition::Circuit(circuit))) => Some(circuit),
+            _ => None,
+        };
+        is_constant(&expression, circuit.as_deref())
+    }
+
+    /// Returns the name shadowed by the declaration with the given id, if the declared name is a
+    /// builtin function or the name of a circuit declared in the codebase.
+    ///
+    /// Supported declarations are parameters, `const` and `var` bindings, struct fields, ledgers,
+    /// witnesses, circuits and declaring identifiers.
+    #[must_use = "Use this function to check if a declaration shadows a builtin or a circuit"]
+    pub fn shadows_builtin(&self, decl_id: u32) -> Option<String> {
+        let name = match self.storage.find_node(decl_id)? {
+            NodeType::Declaration(Declaration::PatternArgument(argument)) => argument.name()?,
+            NodeType::Declaration(Declaration::Argument(argument)) => argument.name(),
+            NodeType::Declaration(Declaration::Ledger(ledger)) => ledger.name(),
+            NodeType::Declaration(Declaration::Witness(witness)) => witness.name(),
+            NodeType::Definition(Definition::Circuit(circuit)) => circuit.name(),
+            NodeType::Statement(Statement::Const(const_stmt)) => match &const_stmt.pattern {
+                Pattern::Identifier(identifier) => identifier.name.clone(),
+                _ => return None,
+            },
+            NodeType::Statement(Statement::Var(var)) => var.ident.name.clone(),
+            NodeType::Expression(Expression::Identifier(identifier)) => identifier.name.clone(),
+            _ => return None,
+        };
+        if BUILTIN_FUNCTIONS.contains(&name.as_str()) {
+            return Some(name);
+        }
+        self.list_nodes_cmp(|node| {
+            if let NodeType::Definition(Definition::Circuit(circuit)) = node {
+                Some(circuit.clone())
+            } else {
+                None
+            }
+        })
+        .any(|circuit| circuit.id != decl_id && circuit.name() == name)
+        .then_some(name)
+    }
+
+    /// Returns the source lines of the node with the given id together with `context_lines`
+    /// lines before and after it. Every line of the node is followed by a caret underline of
+    /// the node's span on that line.
+    #[must_use = "Use this function to render a source snippet for a node"]
+    pub fn snippet(&self, id: u32, context_lines: usize) -> Option<String> {
+        let node = self.storage.find_node(id)?;
+        let file = self.find_node_file(id)?;
+        let location = node.location();
+        let lines: Vec<&str> = file.source.lines().collect();
+        let start_line = location.start_line as usize;
+        let end_line = (location.end_line as usize).min(lines.len());
+        if start_line == 0 || start_line > end_line {
+            return None;
+        }
+        let first = start_line.saturating_sub(context_lines).max(1);
+        let last = (end_line + context_lines).min(lines.len());
+        let width = last.to_string().len();
+        let mut snippet = Vec::new();
+        for line_number in first..=last {
+            let line = lines[line_number - 1];
+            snippet.push(
+                format!("{line_number:>width$} | {line}")
+                    .trim_end()
+                    .to_string(),
+            );
+            if line_number < start_line || line_number > end_line {
+                continue;
+            }
+            let start = if line_number == start_line {
+                (location.start_column as usize - 1).min(line.len())
+            } else {
+                0
+            };
+            let end = if line_number == end_line {
+                (location.end_column as usize - 1).min(line.len())
+            } else {
+                line.len()
+            };
+            let (Some(prefix), Some(span)) = (line.get(..start), line.get(start..end.max(start)))
+            else {
+                continue;
+            };
+            let indent: String = prefix
+                .chars()
+                .map(|c| if c == '\t' { '\t' } else { ' ' })
+                .collect();
+            let carets = "^".repeat(span.chars().count().max(1));
+            snippet.push(format!("{:width$} | {indent}{carets}", ""));
+        }
+        Some(snippet.join("\n"))
+    }
+
+    /// Returns the number of asserts per statement in the circuit with the given id.
+    ///
+    /// Nested statements (inside `if` branches, `for` bodies and blocks) are counted, blocks
+    /// themselves are not. Returns `0.0` if the id is not a circuit or the circuit has no body.
+    #[must_use = "Use this function to get the assert density of a circuit"]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn assert_density(&self, circuit_id: u32) -> f32 {
+        let Some(NodeType::Definition(Definition::Circuit(circuit))) =
+            self.storage.find_node(circuit_id)
+        else {
+            return 0.0;
+        };
+        let statements = circuit.statement_count();
+        if statements == 0 {
+            0.0
+        } else {
+            circuit.assert_count() as f32 / statements as f32
+        }
+    }
+
+    /// Counts the statements of all circuit bodies by kind: `assign`, `assert`, `block`, `const`,
+    /// `var`, `expression`, `if`, `for` and `return`. Circuit body blocks are counted.
+    #[must_use = "Use this function to get statement metrics of the codebase"]
+    pub fn statement_histogram(&self) -> HashMap<&'static str, usize> {
+        let mut histogram = HashMap::new();
+        for circuit in self.circuits() {
+            let Some(body) = &circuit.body else {
+                continue;
+            };
+            for statement in
+                std::iter::once(Statement::Block(body.clone())).chain(body.all_statements())
+            {
+                *histogram.entry(statement.kind()).or_insert(0) += 1;
+            }
+        }
+        histogram
+    }
+
+    /// Computes the metrics of the codebase in a single pass over its nodes.
+    #[must_use = "Use this function to get the metrics of the codebase"]
+    pub fn stats(&self) -> CodebaseStats {
+        let mut stats = CodebaseStats {
+            files: self.files.len(),
+            ..CodebaseStats::default()
+        };
+        let mut seen = HashSet::new();
+        for node in &self.storage.nodes {
+            if !seen.insert(node.id()) {
+                continue;
+            }
+            stats.total_nodes += 1;
+            match node {
+                NodeType::Definition(Definition::Circuit(_)) => stats.circuits += 1,
+                NodeType::Definition(Definition::Module(_)) => stats.modules += 1,
+                NodeType::Declaration(Declaration::Ledger(_)) => stats.ledgers += 1,
+                NodeType::Statement(Statement::Assert(_)) => stats.asserts += 1,
+                NodeType::Statement(Statement::For(_)) => stats.for_loops += 1,
+                _ => {}
+            }
+        }
+        stats
+    }
+
+    /// Returns the ids of all circuits with more than `max` statements, nested statements included.
+    #[must_use = "Use this function to find oversized circuits"]
+    pub fn circuits_exceeding_statements(&self, max: usize) -> Vec<u32> {
+        self.list_nodes_cmp(|node| {
+            if let NodeType::Definition(Definition::Circuit(circuit)) = node {
+                (circuit.statement_count() > max).then_some(circuit.id)
+            } else {
+                None
+            }
+        })
+        .collect()
+    }
+
+    /// Returns the canonical signatures of all circuits in the codebase, e.g.
+    /// `export circuit set_admin(new_admin: Bytes<32>): []`.
+    #[must_use = "Use this function to get an overview of the circuits interface"]
+    pub fn circuit_signatures(&self) -> Vec<String> {
+        self.list_nodes_cmp(|node| {
+            if let NodeType::Definition(Definition::Circuit(circuit)) = node {
+                Some(circuit.signature())
+            } else {
+                None
+            }
+        })
+        .collect()
+    }
+
+    /// Renders the AST of the file as an indented tree, one node per line with its type, position
+    /// and the first line of its source. Returns `None` if the file is not part of the codebase.
+    #[must_use = "Use this function to inspect the AST of a file"]
+    pub fn dump_ast(&self, file_path: &str) -> Option<String> {
+        fn dump(
+            codebase: &Codebase<SealedState>,
+            node: &NodeType,
+            depth: usize,
+            lines: &mut Vec<String>,
+        ) {
+            let location = node.location();
+            let source = location.source.lines().next().unwrap_or_default().trim();
+            lines.push(
+                format!(
+                    "{}{} [{}:{}] {}",
+                    "  ".repeat(depth),
+                    node.kind_name(),
+                    location.start_line,
+                    location.start_column,
+                    source
+                )
+                .trim_end()
+                .to_string(),
+            );
+            for child in codebase.children_of(node.id()) {
+                dump(codebase, child, depth + 1, lines);
+            }
+        }
+
+        let file = self.files.iter().find(|file| file.file_path == file_path)?;
+        let mut lines = Vec::new();
+        dump(self, &NodeType::Program(file.ast.clone()), 0, &mut lines);
+        Some(lines.join("\n"))
+    }
+
+    /// Renders a ctags `tags` file listing the circuits (`c`), ledgers (`l`), structs (`s`), enums
+    /// (`e`) and witnesses (`w`) of the codebase. Each tag has the file path, the declaration line
+    /// as a search pattern and the line number, e.g.
+    /// `set_admin\tsrc/admin.compact\t/^export circuit set_admin(): [] {$/;"\tc\tline:3`.
+    #[must_use = "Use this function to export the codebase symbols for editors"]
+    pub fn to_ctags(&self) -> String {
+        let mut tags = Vec::new();
+        for file in &self.files {
+            let mut seen = HashSet::new();
+            for node in self.descendants_of(file.ast.id) {
+                let (name, kind) = match node {
+                    NodeType::Definition(Definition::Circuit(circuit)) => (circuit.name(), 'c'),
+                    NodeType::Declaration(Declaration::Ledger(ledger)) => (ledger.name(), 'l'),
+                    NodeType::Definition(Definition::Structure(structure)) => {
+                        (structure.name(), 's')
+                    }
+                    NodeType::Definition(Definition::Enum(enum_def)) => (enum_def.name(), 'e'),
+                    NodeType::Declaration(Declaration::Witness(witness)) => (witness.name(), 'w'),
+                    _ => continue,
+                };
+                if !seen.insert(node.id()) {
+                    continue;
+                }
+                let line = node.location().start_line;
+                let text = file
+                    .source
+                    .lines()
+                    .nth((line as usize).saturating_sub(1))
+                    .unwrap_or_default()
+                    .trim_end_matches('\r');
+                let pattern = text.replace('\\', "\\\\").replace('/', "\\/");
+                tags.push(format!(
+                    "{name}\t{}\t/^{pattern}$/;\"\t{kind}\tline:{line}",
+                    file.file_path
+                ));
+            }
+        }
+        tags.sort();
+        let mut lines = vec![
+            "!_TAG_FILE_FORMAT\t2\t/extended format/".to_string(),
+            "!_TAG_FILE_SORTED\t1\t/0=unsorted, 1=sorted/".to_string(),
+        ];
+        lines.extend(tags);
+        lines.join("\n") + "\n"
+    }
+
+    /// Returns the 1-based line where the node with the given id starts.
+    #[must_use = "Use this function to get the line of a node"]
+    pub fn line_of(&self, id: u32) -> Option<usize> {
+        self.node_line_col(id).map(|(line, _)| line)
+    }
+
+    /// Returns the 1-based column, in characters, where the node with the given id starts, like
+    /// the columns of `line_col`.
+    #[must_use = "Use this function to get the column of a node"]
+    pub fn column_of(&self, id: u32) -> Option<usize> {
+        self.node_line_col(id).map(|(_, column)| column)
+    }
+
+    fn node_line_col(&self, id: u32) -> Option<(usize, usize)> {
+        let offset = self.storage.find_node_ref(id)?.location().offset_start as usize;
+        let file = self.find_node_file(id)?;
+        file.source_map.line_col(&file.source, offset)
+    }
+
+    /// Returns the source text of the node with the given id with its whitespace runs, including
+    /// line breaks, collapsed to single spaces and leading and trailing whitespace trimmed, so
+    /// that texts differing only in formatting compare equal.
+    #[must_use = "Use this function to get the normalized source text of a node"]
+    pub fn normalized_source(&self, id: u32) -> Option<String> {
+        self.storage.find_node_ref(id).map(|node| {
+            node.location()
+                .source
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+    }
+
+    /// Returns the comments of the file with the given path, in source order.
+    #[must_use = "Use this function to get the comments of a file"]
+    pub fn comments_of_file(&self, file_path: &str) -> &[Comment] {
+        self.files
+            .iter()
+            .find(|file| file.file_path == file_path)
+            .map_or(&[], |file| &file.comments)
+    }
+
+    /// Returns the line and block comments leading the node with the given id, in source order:
+    /// the comments separated from the node, and from each other, by whitespace only.
+    #[must_use = "Use this function to get the comments leading a node"]
+    pub fn comments_for(&self, id: u32) -> Vec<Comment> {
+        let (Some(node), Some(file)) = (self.storage.find_node_ref(id), self.find_node_file(id))
+        else {
+            return Vec::new();
+        };
+        let mut start = node.location().offset_start as usize;
+        let mut comments = Vec::new();
+        for comment in file.comments.iter().rev() {
+            let end = comment.location.offset_end as usize;
+            if end > start {
+                continue;
+            }
+            if !file.source[end..start].trim().is_empty() {
+                break;
+            }
+            comments.push(comment.clone());
+            start = comment.location.offset_start as usize;
+        }
+        comments.reverse();
+        comments
+    }
+
+    /// Returns the documentation of the declaration with the given id: the text of its leading
+    /// comments (see `comments_for`), one line per comment line, or `None` if it has none.
+    ///
+    /// Doc comment markers (`///`, `/**`, leading `*` of block comment lines) are stripped, and
+    /// suppression comments (`compact-disable-...`) are not documentation.
+    #[must_use = "Use this function to get the documentation of a declaration"]
+    pub fn doc_comment(&self, decl_id: u32) -> Option<String> {
+        let comments = self.comments_for(decl_id);
+        let lines: Vec<&str> = comments
+            .iter()
+            .map(Comment::text)
+            .filter(|text| !text.starts_with("compact-disable"))
+            .flat_map(str::lines)
+            .map(|line| line.trim().trim_start_matches(['/', '*', '!']).trim())
+            .skip_while(|line| line.is_empty())
+            .collect();
+        let text = lines.join("\n");
+        let text = text.trim_end();
+        (!text.is_empty()).then(|| text.to_string())
+    }
+
+    /// Checks if a result of the detector with the given id is suppressed by a comment, either a
+    /// `// compact-disable-next-line <id>` on the line before the result start, or a
+    /// `// compact-disable-line <id>` on the same line. Several ids can be separated by spaces or
+    /// commas, a directive without ids suppresses every detector.
+    #[must_use = "Use this function to check if a detector result is suppressed"]
+    pub fn is_suppressed(&self, detector_id: &str, result: &DetectorResult) -> bool {
+        let Some((line, _)) = self.line_col(&result.file_path, result.offset_start as usize) else {
+            return false;
+        };
+        self.comments_of_file(&result.file_path)
+            .iter()
+            .any(|comment| {
+                let comment_line = comment.location.start_line as usize;
+                let ids = if comment_line == line {
+                    suppression_ids(comment, true)
+                } else if comment_line + 1 == line {
+                    suppression_ids(comment, false)
+                } else {
+                    None
+                };
+                ids.is_some_and(|ids| ids.is_empty() || ids.contains(&detector_id))
+            })
+    }
+
+    /// Drops the results of the detector with the given id that are suppressed by a comment, see
+    /// `is_suppressed`.
+    #[must_use = "Use this function to filter out suppressed detector results"]
+    pub fn unsuppressed_results(
+        &self,
+        detector_id: &str,
+        results: Vec<DetectorResult>,
+    ) -> Vec<DetectorResult> {
+        results
+            .into_iter()
+            .filter(|result| !self.is_suppressed(detector_id, result))
+            .collect()
+    }
+
+    /// Returns the 1-based line and column of a byte offset in the file with the given path.
+    /// Columns are counted in characters. The offset right after the end of the file is valid.
+    #[must_use = "Use this function to get the line and column of an offset"]
+    pub fn line_col(&self, file_path: &str, offset: usize) -> Option<(usize, usize)> {
+        let file = self.files.iter().find(|file| file.file_path == file_path)?;
+        file.source_map.line_col(&file.source, offset)
+    }
+
+    /// Returns the chain of nodes containing `offset` in `file_path`, from the program root down
+    /// to the innermost node, as `(node type, name)` pairs, e.g.
+    /// `[("Program", None), ("Circuit", Some("set_admin")), ("Block", None), ...]`.
+    ///
+    /// Names are provided for named declarations and definitions and for identifiers.
+    #[must_use = "Use this function to get the breadcrumb of a source offset"]
+    pub fn node_path(&self, file_path: &str, offset: u32) -> Vec<(String, Option<String>)> {
+        let Some(file) = self.files.iter().find(|file| file.file_path == file_path) else {
+            return Vec::new();
+        };
+        let mut node = NodeType::Program(file.ast.clone());
+        let mut path = vec![(node.kind_name(), node_name(&node))];
+        while let Some(child) = self
+            .children_of(node.id())
+            .into_iter()
+            .filter(|child| {
+                let location = child.location();
+                location.offset_start <= offset && offset < location.offset_end
+            })
+            .min_by_key(|child| {
+                let location = child.location();
+                location.offset_end - location.offset_start
+            })
+        {
+            path.push((child.kind_name(), node_name(child)));
+            node = child.clone();
+        }
+        path
+    }
+
+    /// Returns the direct children of the node with the given id, in source order.
+    ///
+    /// Children that are not stored in the codebase (e.g. synthesized types) are skipped.
+    #[must_use = "Use this function to get the direct children of a node"]
+    pub fn children_of(&self, id: u32) -> Vec<&NodeType> {
+        self.storage
+            .find_node_ref(id)
+            .map_or_else(Vec::new, |node| {
+                node.child_ids()
+                    .into_iter()
+                    .filter_map(|child_id| self.storage.find_node_ref(child_id))
+                    .collect()
+            })
+    }
+
+    /// Returns an iterator over the subtree of the node with the given id in pre-order, excluding
+    /// the node itself.
+    pub fn descendants_of(&self, id: u32) -> impl Iterator<Item = &NodeType> + '_ {
+        let mut stack: Vec<&NodeType> = self.children_of(id).into_iter().rev().collect();
+        std::iter::from_fn(move || {
+            let node = stack.pop()?;
+            stack.extend(self.children_of(node.id()).into_iter().rev());
+            Some(node)
+        })
+    }
+
+    /// Walks the ASTs of all files, ordered by file path, calling the hooks of `visitor` for every
+    /// node in pre-order, i.e. in source order, starting with the program node of each file.
+    /// `Visitor::leave_node` is called for every node in post-order.
+    pub fn walk<V: Visitor>(&self, visitor: &mut V) {
+        let mut files: Vec<&SourceCodeFile> = self.files.iter().collect();
+        files.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+        for file in files {
+            let Some(root) = self.storage.find_node_ref(file.ast.id) else {
+                continue;
+            };
+            let mut stack = vec![(root, false)];
+            while let Some((node, visited)) = stack.pop() {
+                if visited {
+                    visitor.leave_node(node);
+                    continue;
+                }
+                visit(visitor, node);
+                stack.push((node, true));
+                stack.extend(
+                    self.children_of(node.id())
+                        .into_iter()
+                        .rev()
+                        .map(|child| (child, false)),
+                );
+            }
+        }
+    }
+
+    /// Returns the nodes of the whole codebase matching the predicate, ordered by node id so the
+    /// output is stable across runs. Each node is returned once.
+    pub fn list_nodes_by_kind<F>(&self, predicate: F) -> impl Iterator<Item = &NodeType> + '_
+    where
+        F: Fn(&NodeType) -> bool,
+    {
+        let mut nodes: Vec<&NodeType> = self
+            .storage
+            .nodes
+            .iter()
+            .filter(|node| predicate(node))
+            .collect();
+        nodes.sort_by_key(|node| node.id());
+        nodes.dedup_by_key(|node| node.id());
+        nodes.into_iter()
+    }
+
+    /// Returns the node with the given id and all its descendants matching the comparator.
+    ///
+    /// The descendants are found by walking the AST children of the node, so the whole subtree is
+    /// visited even for nodes that are not registered in the node storage.
+    pub fn get_children_cmp<F>(&self, id: u32, comparator: F) -> Vec<NodeType>
+    where
+        F: Fn(&NodeType) -> bool,
+    {
+        let mut result = Vec::new();
+        let mut stack: Vec<NodeType> = Vec::new();
+
+        if let Some(root_node) = self.storage.find_node(id) {
+            stack.push(root_node.clone());
+        }
+
+        while let Some(current_node) = stack.pop() {
+            if comparator(&current_node) {
+                result.push(current_node.clone());
+            }
+            stack.extend(current_node.children());
+        }
+
+        result
+    }
+
+    fn list_nodes_cmp<'a, T, F>(&'a self, cast: F) -> impl Iterator<Item = T> + 'a
+    where
+        F: Fn(&NodeType) -> Option<T> + 'a,
+        T: Clone + 'static,
+    {
+        self.storage.nodes.iter().filter_map(cast)
+    }
+}
+
+impl<T> Codebase<T> {
+    #[must_use = "Use this function to get a Node's source file"]
+    pub fn find_node_file(&self, id: u32) -> Option<SourceCodeFile> {
+        if let Some(file) = self.files.iter().find(|file| file.ast.id == id) {
+            Some(file.clone())
+        } else {
+            let mut node_id = id;
+            while let Some(parent) = self.storage.find_parent_node(node_id) {
+                if parent == 0 {
+                    if let Some(file) = self.storage.find_node(node_id) {
+                        match file {
+                            NodeType::Program(f) => {
+                                if let Some(sf) =
+                                    self.files.iter().find(|file| Rc::ptr_eq(&file.ast, &f))
+                                {
+                                    return Some(SourceCodeFile {
+                                        file_path: sf.file_path.clone(),
+                                        ast: f.clone(),
+                                        source: sf.source.clone(),
+                                        source_map: sf.source_map.clone(),
+                                        comments: sf.comments.clone(),
+                                    });
+                                }
+                            }
+                            _ => return None,
+                        }
+                    }
+                }
+                node_id = parent;
+            }
+            None
+        }
+    }
+}
+
+/// Returns the first error or missing node of a parse tree, in source order.
+fn first_syntax_error(node: tree_sitter::Node) -> Option<tree_sitter::Node> {
+    if node.is_error() || node.is_missing() {
+        return Some(node);
+    }
+    if !node.has_error() {
+        return None;
+    }
+    let mut cursor = node.walk();
+    let children: Vec<_> = node.children(&mut cursor).collect();
+    children.into_iter().find_map(first_syntax_error)
+}
+
+/// Returns the kinds of the tokens the grammar accepts where a syntax error starts, sorted. A
+/// missing node expects its own kind.
+fn expected_tokens(language: &tree_sitter::Language, error_node: tree_sitter::Node) -> Vec<String> {
+    if error_node.is_missing() {
+        return vec![error_node.kind().to_string()];
+    }
+    let Some(lookahead) = language.lookahead_iterator(error_node.parse_state()) else {
+        return Vec::new();
+    };
+    let mut expected: Vec<String> = lookahead
+        .filter(|symbol| language.node_kind_is_visible(*symbol))
+        .filter_map(|symbol| language.node_kind_for_id(symbol))
+        .filter(|kind| *kind != "ERROR")
+        .map(ToString::to_string)
+        .collect();
+    expected.sort_unstable();
+    expected.dedup();
+    expected
+}
+
+/// Returns the comments of a syntax tree, in source order.
+fn collect_comments(root: tree_sitter::Node, source: &str) -> Vec<Comment> {
+    let mut comments = Vec::new();
+    let mut cursor = root.walk();
+    let mut pending = vec![root];
+    while let Some(node) = pending.pop() {
+        if node.kind() == "comment" {
+            comments.push(Comment {
+                location: location(&node, source),
+            });
+            continue;
+        }
+        let children: Vec<_> = node.children(&mut cursor).collect();
+        pending.extend(children.into_iter().rev());
+    }
+    comments
+}
+
+/// Returns the detector ids listed by a suppression comment, if the comment is a
+/// `compact-disable-line` (`same_line`) or `compact-disable-next-line` directive.
+fn suppression_ids(comment: &Comment, same_line: bool) -> Option<Vec<&str>> {
+    let directive = if same_line {
+        "compact-disable-line"
+    } else {
+        "compact-disable-next-line"
+    };
+    let ids = comment.text().strip_prefix(directive)?;
+    if !ids.is_empty() && !ids.starts_with(char::is_whitespace) {
+        return None;
+    }
+    Some(
+        ids.split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|id| !id.is_empty())
+            .collect(),
+    )
+}
+
+/// Returns the name of a named declaration, definition or identifier node.
+fn node_name(node: &NodeType) -> Option<String> {
+    match node {
+        NodeType::Definition(Definition::Circuit(circuit)) => Some(circuit.name()),
+        NodeType::Definition(Definition::Module(module)) => Some(module.name()),
+        NodeType::Definition(Definition::Structure(structure)) => Some(structure.name()),
+        NodeType::Definition(Definition::Enum(enum_def)) => Some(enum_def.name()),
+        NodeType::Declaration(Declaration::Ledger(ledger)) => Some(ledger.name()),
+        NodeType::Declaration(Declaration::Witness(witness)) => Some(witness.name()),
+        NodeType::Declaration(Declaration::PatternArgument(argument)) => argument.name(),
+        NodeType::Declaration(Declaration::Argument(argument)) => Some(argument.name()),
+        NodeType::Expression(Expression::Identifier(identifier)) => Some(identifier.name.clone()),
+        _ => None,
+    }
+}
+
+/// Returns the innermost identifier an assignment target or a member access is rooted at,
+/// e.g. `balances` for `balances.insert(...)`.
+fn root_identifier(expression: &Expression) -> Option<&Rc<Identifier>> {
+    match expression {
+        Expression::Identifier(identifier) => Some(identifier),
+        Expression::MemberAccess(member_access) => root_identifier(&member_access.base),
+        Expression::IndexAccess(index_access) => root_identifier(&index_access.base),
+        _ => None,
+    }
+}
+
+/// Returns the witness calls within `location` and the sources of the tainted identifiers within
+/// it, ordered by source id. Tainted identifiers come with their name, witness calls without.
+fn witness_sources(
+    nodes: &[&NodeType],
+    witnesses: &HashSet<String>,
+    tainted: &HashMap<String, u32>,
+    location: &Location,
+) -> Vec<(u32, Option<String>)> {
+    let mut sources: Vec<(u32, Option<String>)> = nodes
+        .iter()
+        .filter(|node| {
+            let node_location = node.location();
+            location.offset_start <= node_location.offset_start
+                && node_location.offset_end <= location.offset_end
+        })
+        .filter_map(|node| match node {
+            NodeType::Expression(Expression::FunctionCall(function_call)) => {
+                match &function_call.function {
+                    Expression::Function(Function::Named(function))
+                        if witnesses.contains(function.name()) =>
+                    {
+                        Some((function_call.id, None))
+                    }
+                    _ => None,
+                }
+            }
+            NodeType::Expression(Expression::Identifier(identifier)) => tainted
+                .get(&identifier.name)
+                .map(|source| (*source, Some(identifier.name.clone()))),
+            _ => None,
+        })
+        .collect();
+    sources.sort_unstable();
+    sources.dedup();
+    sources
+}
+
+/// Returns the names of the identifiers appearing in the conditions of the given asserts, among
+/// `nodes`.
+fn checked_names(nodes: &[&NodeType], asserts: &[u32]) -> HashSet<String> {
+    let conditions: Vec<Location> = nodes
+        .iter()
+        .filter_map(|node| match node {
+            NodeType::Statement(Statement::Assert(assert)) if asserts.contains(&assert.id) => {
+                Some(assert.condition.location())
+            }
+            _ => None,
+        })
+        .collect();
+    nodes
+        .iter()
+        .filter_map(|node| match node {
+            NodeType::Expression(Expression::Identifier(identifier))
+                if conditions.iter().any(|condition| {
+                    condition.offset_start <= identifier.location.offset_start
+                        && identifier.location.offset_end <= condition.offset_end
+                }) =>
+            {
+                Some(identifier.name.clone())
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Collects the statements executed before the statement containing `offset` on every path
+/// through `statements`, entering the block, branch or loop body that contains `offset`.
+fn collect_dominators(statements: &[Statement], offset: u32, dominators: &mut Vec<Statement>) {
+    for stmt in statements {
+        let location = stmt.location();
+        if location.offset_start <= offset && offset < location.offset_end {
+            match stmt {
+                Statement::Block(block) => {
+                    collect_dominators(&block.statements, offset, dominators);
+                }
+                Statement::If(if_stmt) => {
+                    if let Some(branch) = std::iter::once(&if_stmt.then_branch)
+                        .chain(if_stmt.else_branch.as_ref())
+                        .find(|branch| {
+                            let location = branch.location();
+                            location.offset_start <= offset && offset < location.offset_end
+                        })
+                    {
+                        collect_dominators(std::slice::from_ref(branch), offset, dominators);
+                    }
+                }
+                Statement::For(for_stmt) => {
+                    collect_dominators(&for_stmt.body.statements, offset, dominators);
+                }
+                _ => {}
+            }
+            return;
+        }
+        dominators.push(stmt.clone());
+    }
+}
+
+/// Checks if the end of a statement list can be reached without executing a `return`.
+fn falls_through(statements: &[Statement]) -> bool {
+    match statements.last() {
+        Some(Statement::Return(_)) => false,
+        Some(Statement::Block(block)) => falls_through(&block.statements),
+        Some(Statement::If(if_stmt)) => {
+            falls_through(std::slice::from_ref(&if_stmt.then_branch))
+                || if_stmt
+                    .else_branch
+                    .as_ref()
+                    .is_none_or(|else_branch| falls_through(std::slice::from_ref(else_branch)))
+        }
+        _ => true,
+    }
+}
+
+/// Evaluates a boolean expression made of literals, returning `None` if it is not constant.
+fn constant_bool(expression: &Expression) -> Option<bool> {
+    match expression {
+        Expression::Literal(Literal::Bool(value)) => Some(value.value),
+        Expression::Unary(unary) if unary.operator == UnaryExpressionOperator::Not => {
+            constant_bool(&unary.operand).map(|value| !value)
+        }
+        Expression::Binary(binary) => {
+            let left = constant_bool(&binary.left);
+            let right = constant_bool(&binary.right);
+            match (&binary.operator, left, right) {
+                (BinaryExpressionOperator::And, Some(false), _)
+                | (BinaryExpressionOperator::And, _, Some(false))
+                | (BinaryExpressionOperator::Or, Some(false), Some(false)) => Some(false),
+                (BinaryExpressionOperator::Or, Some(true), _)
+                | (BinaryExpressionOperator::Or, _, Some(true))
+                | (BinaryExpressionOperator::And, Some(true), Some(true)) => Some(true),
+                (BinaryExpressionOperator::Eq, Some(l), Some(r)) => Some(l == r),
+                (BinaryExpressionOperator::Ne, Some(l), Some(r)) => Some(l != r),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn is_constant(expression: &Expression, circuit: Option<&Circuit>) -> bool {
+    match expression {
+        Expression::Literal(_) | Expression::Default(_) => true,
+        Expression::Binary(binary) => {
+            is_constant(&binary.left, circuit) && is_constant(&binary.right, circuit)
+        }
+        Expression::Unary(unary) => is_constant(&unary.operand, circuit),
+        Expression::Cast(cast) => is_constant(&cast.expression, circuit),
+        Expression::Disclose(disclose) => is_constant(&disclose.expression, circuit),
+        Expression::Conditional(conditional) => {
+            is_constant(&conditional.condition, circuit)
+                && is_constant(&conditional.then_branch, circuit)
+                && is_constant(&conditional.else_branch, circuit)
+        }
+        Expression::Sequence(sequence) => sequence
+            .expressions
+            .iter()
+            .all(|expression| is_constant(expression, circuit)),
+        Expression::Identifier(identifier) => {
+            let Some(body) = circuit.and_then(|circuit| circuit.body.as_ref()) else {
+                return false;
+            };
+            let binding = body
+                .all_statements()
+                .into_iter()
+                .filter_map(|statement| match statement {
+                    Statement::Const(const_stmt) => match &const_stmt.pattern {
+                        Pattern::Identifier(name)
+                            if name.name == identifier.name
+                                && const_stmt.location.offset_end
+                                    <= identifier.location.offset_start =>
+                        {
+                            Some(const_stmt)
+                        }
+                        _ => None,
+                    },
+                    _ => None,
+                })
+                .max_by_key(|const_stmt| const_stmt.location.offset_start);
+            binding.is_some_and(|const_stmt| is_constant(&const_stmt.value, circuit))
+        }
+        _ => false,
+    }
+}
+
+/// Returns the names bound by the circuit parameters and `const` declarations.
+fn circuit_local_names(circuit: &Circuit) -> HashSet<String> {
+    let parameters = circuit
+        .arguments
+        .iter()
+        .filter_map(|argument| argument.name());
+    let constants = circuit
+        .body
+        .iter()
+        .flat_map(|body| body.all_statements())
+        .filter_map(|statement| match statement {
+            Statement::Const(const_stmt) => match &const_stmt.pattern {
+                Pattern::Identifier(identifier) => Some(identifier.name.clone()),
+                _ => None,
+            },
+            _ => None,
+        });
+    parameters.chain(constants).collect()
+}
+
+/// Resolves an identifier of a circuit to the innermost parameter, `const`, `var` or loop counter
+/// binding its name at its position.
+fn local_definition(circuit: &Circuit, identifier: &Identifier) -> Option<u32> {
+    struct Binding {
+        scope: (u32, u32),
+        visible_from: u32,
+        declared_at: u32,
+        id: u32,
+    }
+    let offset = identifier.location.offset_start;
+    let mut bindings: Vec<Binding> = circuit
+        .arguments
+        .iter()
+        .filter_map(|argument| match &argument.pattern {
+            Pattern::Identifier(name) if name.name == identifier.name => Some(Binding {
+                scope: (circuit.location.offset_start, circuit.location.offset_end),
+                visible_from: circuit.location.offset_start,
+                declared_at: name.location.offset_start,
+                id: argument.id,
+            }),
+            _ => None,
+        })
+        .collect();
+    if let Some(body) = &circuit.body {
+        let statements = body.all_statements();
+        let mut scopes = vec![(body.location.offset_start, body.location.offset_end)];
+        for statement in &statements {
+            match statement {
+                Statement::Block(block) => {
+                    scopes.push((block.location.offset_start, block.location.offset_end));
+                }
+                Statement::For(for_stmt) => {
+                    let body = &for_stmt.body.location;
+                    scopes.push((body.offset_start, body.offset_end));
+                }
+                _ => {}
+            }
+        }
+        for statement in &statements {
+            let (name, id) = match statement {
+                Statement::Const(const_stmt) => match &const_stmt.pattern {
+                    Pattern::Identifier(name) => (name, const_stmt.id),
+                    _ => continue,
+                },
+                Statement::Var(var) => (&var.ident, var.id),
+                Statement::For(for_stmt) => {
+                    if for_stmt.counter.name == identifier.name {
+                        bindings.push(Binding {
+                            scope: (for_stmt.location.offset_start, for_stmt.location.offset_end),
+                            visible_from: for_stmt.counter.location.offset_end,
+                            declared_at: for_stmt.counter.location.offset_start,
+                            id: for_stmt.counter.id,
+                        });
+                    }
+                    continue;
+                }
+                _ => continue,
+            };
+            if name.name != identifier.name {
+                continue;
+            }
+            let location = statement.location();
+            let Some(scope) = scopes
+                .iter()
+                .filter(|(start, end)| {
+                    *start <= location.offset_start && location.offset_end <= *end
+                })
+                .max_by_key(|(start, _)| *start)
+            else {
+                continue;
+            };
+            bindings.push(Binding {
+                scope: *scope,
+                visible_from: location.offset_end,
+                declared_at: name.location.offset_start,
+                id,
+            });
+        }
+    }
+    bindings
+        .into_iter()
+        .filter(|binding| binding.scope.0 <= offset && offset < binding.scope.1)
+        .filter(|binding| binding.visible_from <= offset || binding.declared_at == offset)
+        .max_by_key(|binding| (binding.scope.0, binding.visible_from))
+        .map(|binding| binding.id)
+}
+
+/// Checks if the import path `import_name`, relative to the directory of `importing_file`, names
+/// the file `file_path`. The `.compact` extension of the imported file may be omitted, and `.`
+/// and `..` components are resolved, so `"../../Test"` imported from `a/b/c/main.compact` names
+/// `a/Test.compact`.
+fn import_resolves_to(importing_file: &str, import_name: &str, file_path: &str) -> bool {
+    let directory = std::path::Path::new(importing_file)
+        .parent()
+        .unwrap_or_else(|| std::path::Path::new(""));
+    let imported = normalize_path(&directory.join(import_name));
+    let file = normalize_path(std::path::Path::new(file_path));
+    imported == file || imported.with_extension("compact") == file
+}
+
+/// Removes the `.` components of a path and resolves its `..` components lexically.
+fn normalize_path(path: &std::path::Path) -> std::path::PathBuf {
+    let mut normalized = std::path::PathBuf::new();
+    for component in path.components() {
+        match component {
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir => {
+                if normalized.file_name().is_some() {
+                    normalized.pop();
+                } else {
+                    normalized.push("..");
+                }
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Returns the id of the identifier naming a declaration resolved by `get_symbol_definition`.
+fn declared_name_id(declaration: &NodeType) -> Option<u32> {
+    match declaration {
+        NodeType::Declaration(Declaration::Ledger(ledger)) => Some(ledger.name.id),
+        NodeType::Declaration(Declaration::Witness(witness)) => Some(witness.name.id),
+        NodeType::Declaration(Declaration::PatternArgument(argument)) => match &argument.pattern {
+            Pattern::Identifier(name) => Some(name.id),
+            _ => None,
+        },
+        NodeType::Definition(Definition::Circuit(circuit)) => Some(circuit.name.id),
+        NodeType::Definition(Definition::Structure(structure)) => Some(structure.name.id),
+        NodeType::Definition(Definition::Enum(enum_def)) => Some(enum_def.name.id),
+        NodeType::Statement(Statement::Const(const_stmt)) => match &const_stmt.pattern {
+            Pattern::Identifier(name) => Some(name.id),
+            _ => None,
+        },
+        NodeType::Statement(Statement::Var(var)) => Some(var.ident.id),
+        NodeType::Expression(Expression::Identifier(counter)) => Some(counter.id),
+        _ => None,
+    }
+}
+
+/// Returns the id of the ledger field, witness, circuit, struct or enum declared with the given
+/// name at the top level of a program.
+fn program_definition(program: &Program, name: &str) -> Option<u32> {
+    program
+        .declarations
+        .iter()
+        .find_map(|declaration| match declaration {
+            Declaration::Ledger(ledger) if ledger.name.name == name => Some(ledger.id),
+            Declaration::Witness(witness) if witness.name.name == name => Some(witness.id),
+            _ => None,
+        })
+        .or_else(|| {
+            program
+                .definitions
+                .iter()
+                .find_map(|definition| match definition {
+                    Definition::Circuit(circuit) if circuit.name() == name => Some(circuit.id),
+                    Definition::Structure(structure) if structure.name() == name => {
+                        Some(structure.id)
+                    }
+                    Definition::Enum(enum_def) if enum_def.name() == name => Some(enum_def.id),
+                    _ => None,
+                })
+        })
+}
+
+fn exported_names(program: &Program) -> Vec<String> {
+    program
+        .declarations
+        .iter()
+        .filter_map(|declaration| match declaration {
+            Declaration::Export(export) => {
+                Some(export.values.iter().map(|value| value.name.clone()))
+            }
+            _ => None,
+        })
+        .flatten()
+        .collect()
+}
+
+fn declares_symbol(program: &Program, name: &str) -> bool {
+    program
+        .definitions
+        .iter()
+        .any(|definition| match definition {
+            Definition::Circuit(circuit) => circuit.name() == name,
+            Definition::Structure(structure) => structure.name() == name,
+            Definition::Enum(enum_def) => enum_def.name() == name,
+            Definition::Module(module) => module.name() == name,
+        })
+        || program
+            .declarations
+            .iter()
+            .any(|declaration| match declaration {
+                Declaration::Ledger(ledger) => ledger.name() == name,
+                Declaration::Witness(witness) => witness.name() == name,
+                _ => false,
+            })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the first expression of the codebase whose source is `source`.
+    fn expression_with_source(codebase: &Codebase<SealedState>, source: &str) -> Expression {
+        codebase
+            .list_nodes_cmp(|node| match node {
+                NodeType::Expression(expression) if expression.location().source == source => {
+                    Some(expression.clone())
+                }
+                _ => None,
+            })
+            .next()
+            .expect("expression not found")
+    }
+
+    /// Returns the id of the identifier starting at the byte `offset` of the source.
+    fn identifier_at(codebase: &Codebase<SealedState>, offset: usize) -> u32 {
+        codebase
+            .list_nodes_cmp(|node| match node {
+                NodeType::Expression(Expression::Identifier(identifier))
+                    if identifier.location.offset_start as usize == offset =>
+                {
+                    Some(identifier.id)
+                }
+                _ => None,
+            })
+            .next()
+            .expect("identifier not found")
+    }
+
+    #[test]
+    fn test_import_reference_set_correctly() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file("./a.compact", r#"import "./b.compact";"#);
+        codebase.add_file("./b.compact", r#"import "./a.compact";"#);
+        let codebase = codebase.seal()?;
+        let imports: Vec<_> = codebase
+            .list_nodes_cmp(|node| {
+                if let NodeType::Declaration(Declaration::Import(import)) = node {
+                    Some(import.clone())
+                } else {
+                    None
+                }
+            })
+            .collect();
+        assert_eq!(imports.len(), 2);
+        for import in imports {
+            assert!(
+                import.reference.is_some(),
+                "Import reference should be set for all import nodes"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_rename_file() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "./a.compact",
+            r#"import "./b.compact";
+            circuit foo(x: Field): Field { return bar(x); }"#,
+        );
+        codebase.add_file("./b.compact", "circuit bar(x: Field): Field { return x; }");
+        let mut sealed = codebase.seal()?;
+        sealed.rename_file("./b.compact", "./lib/b.compact")?;
+        assert!(sealed.source_of_file("./b.compact").is_none());
+        assert!(sealed.source_of_file("./lib/b.compact").is_some());
+        let renamed = sealed
+            .files
+            .iter()
+            .find(|file| file.file_path == "./lib/b.compact")
+            .map(|file| file.ast.id)
+            .expect("renamed file not found");
+        let import = sealed
+            .list_nodes_cmp(|node| match node {
+                NodeType::Declaration(Declaration::Import(import)) => Some(import.clone()),
+                _ => None,
+            })
+            .next()
+            .expect("import not found");
+        assert_eq!(
+            import.reference.as_ref().map(|program| program.id),
+            Some(renamed)
+        );
+        let bar = sealed
+            .circuit_by_name_in_file("./a.compact", "bar")
+            .expect("imported circuit not resolved");
+        assert_eq!(
+            sealed.find_node_file(bar.id).map(|file| file.file_path),
+            Some("./lib/b.compact".to_string())
+        );
+        assert!(sealed.rename_file("./b.compact", "./c.compact").is_err());
+        assert!(sealed
+            .rename_file("./a.compact", "./lib/b.compact")
+            .is_err());
+
+        sealed.rename_file("./a.compact", "./app/a.compact")?;
+        let resealed = sealed.unseal().reseal()?;
+        let import = resealed
+            .list_nodes_cmp(|node| match node {
+                NodeType::Declaration(Declaration::Import(import)) => Some(import.clone()),
+                _ => None,
+            })
+            .next()
+            .expect("import not found");
+        assert_eq!(
+            import.reference.as_ref().map(|program| program.id),
+            Some(renamed)
+        );
+        assert!(resealed
+            .circuit_by_name_in_file("./app/a.compact", "bar")
+            .is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn test_imported_function_types_resolved_correctly() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        let source_a = r"
+            export pure circuit unknown_ship_def(): ShipDef {
+              return ShipDef {
+                ship: SHIP.unknown,
+                ship_cell: Coord { 0, 0 },
+                ship_v: false
+              };
+            }
+        ";
+        let source_b = r#"
+            import "./a.compact";
+            pure circuit calculate_ship_def(shot_attempt: Coord, ship_state: ShipState, updated_ship_state: ShipState, ships: Ships, player: Bytes<32>): ShotResult {
+                return unknown_ship_def();
+            }
+        "#;
+        codebase.add_file("./a.compact", source_a);
+        codebase.add_file("./b.compact", source_b);
+        let sealed = codebase.seal()?;
+        let unknown_ship_def_node_id = sealed
+            .list_nodes_cmp(|node| {
+                if let NodeType::Definition(Definition::Circuit(circuit)) = node {
+                    if circuit.name() == "unknown_ship_def" {
+                        return Some(node.id());
+                    }
+                }
+                None
+            })
+            .next()
+            .expect("unknown_ship_def node not found");
+        let ship_def_type = sealed
+            .get_symbol_type_by_id(unknown_ship_def_node_id)
+            .unwrap_or_else(|| {
+                panic!("Type for unknown_ship_def not found [{unknown_ship_def_node_id}]")
+            });
+        match ship_def_type {
+            Type::Ref(ref ty) => {
+                assert_eq!(ty.name(), "ShipDef");
+            }
+            _ => panic!("Expected a reference type for unknown_ship_def"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_function_call_single_file_reference_resolution() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        let source_a = r"
+             export pure circuit unknown_ship_def(): ShipDef {
+               return ShipDef {
+                 ship: SHIP.unknown,
+                 ship_cell: Coord { 0, 0 },
+                 ship_v: false
+               };
+             }
+
+            pure circuit calculate_ship_def(shot_attempt: Coord, ship_state: ShipState, updated_ship_state: ShipState, ships: Ships, player: Bytes<32>): ShotResult {
+               return unknown_ship_def();
+            }
+         ";
+        codebase.add_file("./a.compact", source_a);
+        let sealed = codebase.seal()?;
+
+        let unknown_ship_def_circuit_node = sealed
+            .list_nodes_cmp(|node| {
+                if let NodeType::Definition(Definition::Circuit(circuit)) = node {
+                    if circuit.name() == "unknown_ship_def" {
+                        return Some(circuit.clone());
+                    }
+                }
+                None
+            })
+            .next()
+            .expect("unknown_ship_def node not found");
+        let function_call_node = sealed
+            .list_nodes_cmp(|node| {
+                if let NodeType::Expression(Expression::FunctionCall(func_call)) = node {
+                    return Some(func_call.clone());
+                }
+                None
+            })
+            .next()
+            .expect("Function call node not found");
+        assert_eq!(
+            function_call_node.reference.as_ref().unwrap().id,
+            unknown_ship_def_circuit_node.id,
+            "Function call reference should be set to the correct circuit id, expected: {}, found: {}",
+            unknown_ship_def_circuit_node.id, function_call_node.reference.as_ref().unwrap().id
+        );
+        Ok(())
+    }
+
+    #[test]
     fn test_function_call_multi_file_reference_resolution() -> anyhow::Result<()> {
         let mut codebase = Codebase::<OpenState>::new();
-        let source_a = r"
-            export pure circuit unknown_ship_def(): ShipDef {
-              return ShipDef {
-                ship: SHIP.unknown,
-                ship_cell: Coord { 0, 0 },
-                ship_v: false
-              };
+        let source_a = r"
+            export pure circuit unknown_ship_def(): ShipDef {
+              return ShipDef {
+                ship: SHIP.unknown,
+                ship_cell: Coord { 0, 0 },
+                ship_v: false
+              };
+            }
+        ";
+        let source_b = r#"
+            import "./a.compact";
+            pure circuit calculate_ship_def(shot_attempt: Coord, ship_state: ShipState, updated_ship_state: ShipState, ships: Ships, player: Bytes<32>): ShotResult {
+                return unknown_ship_def();
+            }
+        "#;
+        codebase.add_file("./a.compact", source_a);
+        codebase.add_file("./b.compact", source_b);
+        let sealed = codebase.seal()?;
+
+        let unknown_ship_def_circuit_node = sealed
+            .list_nodes_cmp(|node| {
+                if let NodeType::Definition(Definition::Circuit(circuit)) = node {
+                    if circuit.name() == "unknown_ship_def" {
+                        return Some(circuit.clone());
+                    }
+                }
+                None
+            })
+            .next()
+            .expect("unknown_ship_def node not found");
+        let function_call_node = sealed
+            .list_nodes_cmp(|node| {
+                if let NodeType::Expression(Expression::FunctionCall(func_call)) = node {
+                    return Some(func_call.clone());
+                }
+                None
+            })
+            .next()
+            .expect("Function call node not found");
+        assert_eq!(
+            function_call_node.reference.as_ref().unwrap().id,
+            unknown_ship_def_circuit_node.id,
+            "Function call reference should be set to the correct circuit id, expected: {}, found: {}",
+            unknown_ship_def_circuit_node.id, function_call_node.reference.as_ref().unwrap().id
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_only() -> anyhow::Result<()> {
+        let mut files = HashMap::new();
+        files.insert(
+            "test.compact".to_string(),
+            "circuit foo(x: Uint<8>): Uint<8> { return x; }".to_string(),
+        );
+        let parsed = Codebase::<ParsedState>::parse_only(&files)?;
+        assert!(parsed.symbol_tables.is_empty());
+        let parsed_files: Vec<_> = parsed.files().collect();
+        assert_eq!(parsed_files.len(), 1);
+        assert_eq!(parsed_files[0].ast.circuits()[0].name(), "foo");
+        let sealed = parsed.seal()?;
+        assert_eq!(sealed.symbol_tables.len(), 1);
+        let typed_identifiers = sealed
+            .list_nodes_cmp(|node| match node {
+                NodeType::Expression(Expression::Identifier(ident)) if ident.name == "x" => {
+                    sealed.get_symbol_type_by_id(ident.id)
+                }
+                _ => None,
+            })
+            .count();
+        assert!(typed_identifiers > 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_seal_reports_all_parse_errors() {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file("./a.compact", "circuit foo(x: Field): Field { return x; }");
+        codebase.add_file(
+            "./b.compact",
+            "pragma language_version >= 0.14.0;\n%% not compact %%\n",
+        );
+        codebase.add_file("./c.compact", "circuit bar(x: Field): Field { return x; }");
+        let programs = codebase
+            .storage
+            .nodes
+            .iter()
+            .filter(|node| matches!(node, NodeType::Program(_)))
+            .count();
+        assert_eq!(programs, 2);
+        assert_eq!(codebase.files.len(), 2);
+        let Err(error) = codebase.seal() else {
+            panic!("sealing a codebase with an invalid file must fail");
+        };
+        let error = error
+            .downcast::<SealError>()
+            .expect("parse errors must be reported as a SealError");
+        assert_eq!(error.errors.len(), 1);
+        let parse_error = &error.errors[0];
+        assert_eq!(parse_error.file_path, "./b.compact");
+        assert_eq!(parse_error.location.start_line, 2);
+        assert!(parse_error.location.source.contains("%%"));
+        assert!(error.to_string().contains("./b.compact:2:"));
+    }
+
+    #[test]
+    fn test_seal_with_diagnostics() -> anyhow::Result<()> {
+        let invalid = "pragma language_version >= 0.14.0;\n%% not compact %%\n";
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file("./a.compact", "circuit foo(x: Field): Field { return x; }");
+        codebase.add_file("./b.compact", invalid);
+        let (sealed, diagnostics) = codebase.seal_with_diagnostics()?;
+        let files: Vec<String> = sealed.files().map(|file| file.file_path).collect();
+        assert_eq!(files, vec!["./a.compact"]);
+        assert!(sealed
+            .circuit_by_name_in_file("./a.compact", "foo")
+            .is_some());
+        assert_eq!(diagnostics.len(), 1);
+        let diagnostic = &diagnostics[0];
+        assert_eq!(diagnostic.file, "./b.compact");
+        assert!(diagnostic.offset as usize > invalid.find(';').unwrap());
+        assert!(!diagnostic.message.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_list_if_statement_nodes() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "test.compact",
+            "circuit foo(a: Boolean, b: Boolean): Field {
+                if (a) {
+                    if (b) {
+                        return 1;
+                    }
+                } else {
+                    return 2;
+                }
+                return 3;
+            }",
+        );
+        let sealed = codebase.seal()?;
+        let mut seen = HashSet::new();
+        let mut ifs: Vec<Rc<If>> = sealed
+            .list_if_statement_nodes()
+            .filter(|if_stmt| seen.insert(if_stmt.id))
+            .collect();
+        ifs.sort_by_key(|if_stmt| if_stmt.location.offset_start);
+        assert_eq!(ifs.len(), 2);
+        let (outer, inner) = (&ifs[0], &ifs[1]);
+        assert!(outer.else_branch_id().is_some());
+        assert_eq!(inner.else_branch_id(), None);
+        assert_eq!(inner.condition_id(), inner.condition.id());
+        assert!(matches!(
+            sealed.storage.find_node(outer.then_branch_id()),
+            Some(NodeType::Statement(Statement::Block(_)))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_list_return_nodes() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "test.compact",
+            "circuit empty(): [] {
+                return [];
+            }
+            circuit zero(): Field {
+                return 0;
+            }",
+        );
+        let sealed = codebase.seal()?;
+        let mut returns: Vec<Rc<Return>> = sealed.list_return_nodes().collect();
+        returns.sort_by_key(|ret| ret.location.offset_start);
+        returns.dedup_by_key(|ret| ret.id);
+        assert_eq!(returns.len(), 2);
+        assert!(returns[0].is_empty_tuple());
+        assert!(!returns[1].is_empty_tuple());
+        Ok(())
+    }
+
+    #[test]
+    fn test_assert_density() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "test.compact",
+            "export circuit set_admin(new_admin: Bytes<32>): [] {
+                const current_proof = generate_key_proof(sigCounter as Field as Bytes<32>);
+                assert admin == pad(32, \"\") \"\";
+                admin = new_admin;
+                return [];
+            }",
+        );
+        let sealed = codebase.seal()?;
+        let set_admin = sealed
+            .list_nodes_cmp(|node| {
+                if let NodeType::Definition(Definition::Circuit(circuit)) = node {
+                    Some(circuit.clone())
+                } else {
+                    None
+                }
+            })
+            .next()
+            .expect("set_admin node not found");
+        assert_eq!(set_admin.assert_count(), 1);
+        assert!((sealed.assert_density(set_admin.id) - 0.25).abs() < f32::EPSILON);
+        Ok(())
+    }
+
+    #[test]
+    fn test_statement_histogram() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "test.compact",
+            "export circuit set_admin(new_admin: Bytes<32>): [] {
+                const current_proof = generate_key_proof(sigCounter as Field as Bytes<32>);
+                assert admin == pad(32, \"\") \"\";
+                admin = new_admin;
+                return [];
+            }",
+        );
+        let sealed = codebase.seal()?;
+        let histogram = sealed.statement_histogram();
+        assert_eq!(histogram.get("const"), Some(&1));
+        assert_eq!(histogram.get("assign"), Some(&1));
+        assert_eq!(histogram.get("assert"), Some(&1));
+        assert_eq!(histogram.get("return"), Some(&1));
+        assert_eq!(histogram.get("block"), Some(&1));
+        assert_eq!(histogram.get("if"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_stats() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "a.compact",
+            "export ledger counter: Counter;
+            export ledger admin: Bytes<32>;
+            export circuit increment(): [] {
+                assert admin == pad(32, \"\") \"not initialized\";
+                for (const i of 0 .. 3) {
+                    counter.increment(1);
+                }
+            }",
+        );
+        codebase.add_file(
+            "b.compact",
+            "module M {
+                export circuit check(x: Field): [] {
+                    assert x == 1 \"x must be 1\";
+                }
+            }
+            circuit external(x: Field): Field;",
+        );
+        let sealed = codebase.seal()?;
+        let stats = sealed.stats();
+        assert_eq!(stats.files, 2);
+        assert_eq!(stats.circuits, 3);
+        assert_eq!(stats.modules, 1);
+        assert_eq!(stats.ledgers, 2);
+        assert_eq!(stats.asserts, 2);
+        assert_eq!(stats.for_loops, 1);
+        let distinct: HashSet<u32> = sealed.storage.nodes.iter().map(NodeType::id).collect();
+        assert_eq!(stats.total_nodes, distinct.len());
+        Ok(())
+    }
+
+    #[test]
+    fn test_circuits_exceeding_statements() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "test.compact",
+            "circuit small(x: Field): Field {
+                return x;
+            }
+            circuit large(x: Field): Field {
+                const a = x + 1;
+                const b = a + 1;
+                if (b == 3) {
+                    const c = b + 1;
+                    return c;
+                }
+                return b;
+            }",
+        );
+        let sealed = codebase.seal()?;
+        let large = sealed
+            .list_nodes_cmp(|node| {
+                if let NodeType::Definition(Definition::Circuit(circuit)) = node {
+                    (circuit.name() == "large").then(|| circuit.clone())
+                } else {
+                    None
+                }
+            })
+            .next()
+            .expect("large node not found");
+        assert_eq!(large.statement_count(), 6);
+        assert_eq!(sealed.circuits_exceeding_statements(3), vec![large.id]);
+        assert!(sealed.circuits_exceeding_statements(6).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_circuit_signatures() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "test.compact",
+            "export circuit set_admin(new_admin: Bytes<32>): [] {
+                admin = new_admin;
+                return [];
+            }
+            pure circuit double(x: Field, y: Uint<64>): Field {
+                return x + x;
+            }",
+        );
+        let sealed = codebase.seal()?;
+        let signatures = sealed.circuit_signatures();
+        assert_eq!(
+            signatures,
+            vec![
+                "export circuit set_admin(new_admin: Bytes<32>): []",
+                "pure circuit double(x: Field, y: Uint<64>): Field",
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_every_node_is_found_by_id() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "test.compact",
+            "export ledger admin: Bytes<32>;
+            export circuit set_admin(new_admin: Bytes<32>): [] {
+                assert admin == pad(32, \"\") \"\";
+                admin = new_admin;
+                return [];
+            }",
+        );
+        let sealed = codebase.seal()?;
+        let mut kinds = HashSet::new();
+        for node in &sealed.storage.nodes {
+            let (kind, id, location) = match node {
+                NodeType::Declaration(Declaration::Ledger(ledger)) => {
+                    ("ledger", ledger.id, &ledger.location)
+                }
+                NodeType::Definition(Definition::Circuit(circuit)) => {
+                    ("circuit", circuit.id, &circuit.location)
+                }
+                NodeType::Statement(Statement::Assert(assert)) => {
+                    ("assert", assert.id, &assert.location)
+                }
+                NodeType::Expression(Expression::Identifier(identifier)) => {
+                    ("identifier", identifier.id, &identifier.location)
+                }
+                _ => continue,
+            };
+            kinds.insert(kind);
+            assert_eq!(node.id(), id);
+            assert_eq!(&node.location(), location);
+            let found = sealed.storage.find_node(id).expect("node not found by id");
+            assert_eq!(&found.location(), location);
+        }
+        assert_eq!(kinds.len(), 4);
+        Ok(())
+    }
+
+    #[test]
+    fn test_dump_ast() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "test.compact",
+            "export ledger admin: Bytes<32>;
+            export circuit get_admin(): Bytes<32> {
+                return admin;
+            }",
+        );
+        let sealed = codebase.seal()?;
+        let dump = sealed.dump_ast("test.compact").expect("file not found");
+        let lines: Vec<&str> = dump.lines().collect();
+        assert!(lines[0].starts_with("Program [1:1]"));
+        assert!(lines
+            .iter()
+            .any(|line| line.starts_with("  Ledger [1:") && line.contains("admin")));
+        assert!(lines
+            .iter()
+            .any(|line| line.starts_with("  Circuit [2:") && line.contains("get_admin")));
+        assert!(lines
+            .iter()
+            .any(|line| line.trim_start().starts_with("Return [3:17] return admin")));
+        assert!(sealed.dump_ast("missing.compact").is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_line_of_column_of() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "test.compact",
+            "circuit foo(x: Field): Field {
+    const y = x + 1;
+    return y;
+}",
+        );
+        let sealed = codebase.seal()?;
+        let return_id = sealed
+            .list_nodes_cmp(|node| match node {
+                NodeType::Statement(Statement::Return(ret)) => Some(ret.id),
+                _ => None,
+            })
+            .next()
+            .expect("return statement not found");
+        assert_eq!(sealed.line_of(return_id), Some(3));
+        assert_eq!(sealed.column_of(return_id), Some(5));
+        assert_eq!(sealed.line_of(u32::MAX), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_column_of_counts_characters() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "test.compact",
+            "circuit foo(x: Field): Field {
+    assert(x != 0, \"é\"); return x;
+}",
+        );
+        let sealed = codebase.seal()?;
+        let return_id = sealed
+            .list_return_nodes()
+            .next()
+            .expect("return statement not found")
+            .id;
+        assert_eq!(sealed.line_of(return_id), Some(2));
+        assert_eq!(sealed.column_of(return_id), Some(26));
+        Ok(())
+    }
+
+    #[test]
+    fn test_normalized_source() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "test.compact",
+            "circuit foo(v: Vector<2, Field>): Field {
+    const a = v[ 0 ];
+    const b = v[  0
+    ];
+    return a + b;
+}",
+        );
+        let sealed = codebase.seal()?;
+        let mut index_accesses: Vec<u32> = sealed
+            .list_nodes_cmp(|node| match node {
+                NodeType::Expression(Expression::IndexAccess(index_access)) => {
+                    Some(index_access.id)
+                }
+                _ => None,
+            })
+            .collect();
+        index_accesses.sort_unstable();
+        index_accesses.dedup();
+        assert_eq!(index_accesses.len(), 2);
+        let first = sealed.normalized_source(index_accesses[0]);
+        let second = sealed.normalized_source(index_accesses[1]);
+        assert_eq!(first.as_deref(), Some("v[ 0 ]"));
+        assert_eq!(first, second);
+        assert_eq!(sealed.normalized_source(u32::MAX), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_round_trip() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "test.compact",
+            "export ledger total: Field;
+            export circuit sum(v: Vector<3, Field>): Field {
+                for (const x of v) {
+                    assert(x != 0, \"zero\");
+                }
+                for (const i of 0..3) {
+                    total = total + 1;
+                }
+                return total;
+            }
+            circuit add_one(x: Field): Field {
+                return x + 1;
+            }
+            export circuit next(): Field {
+                return add_one(total);
+            }",
+        );
+        let sealed = codebase.seal()?;
+        let json = sealed.to_json()?;
+        let restored = Codebase::<SealedState>::from_json(&json)?;
+        assert_eq!(
+            restored.list_for_statement_nodes().count(),
+            sealed.list_for_statement_nodes().count()
+        );
+        assert!(restored.list_for_statement_nodes().count() > 0);
+        let return_id = sealed
+            .list_return_nodes()
+            .next()
+            .expect("return not found")
+            .id;
+        assert_eq!(
+            restored
+                .find_node_file(return_id)
+                .map(|file| file.file_path),
+            Some("test.compact".to_string())
+        );
+        assert_eq!(
+            restored.find_node_by_id(return_id).map(NodeType::id),
+            Some(return_id)
+        );
+        let program = restored.files[0].ast.clone();
+        let add_one = program
+            .circuits()
+            .into_iter()
+            .find(|circuit| circuit.name() == "add_one")
+            .expect("circuit not found");
+        let mut stack = vec![NodeType::Program(program)];
+        let mut call_id = None;
+        while let Some(node) = stack.pop() {
+            if let NodeType::Expression(Expression::FunctionCall(call)) = &node {
+                call_id = Some(call.id);
+            }
+            stack.extend(node.children());
+        }
+        let Some(NodeType::Expression(Expression::FunctionCall(call))) =
+            restored.find_node_by_id(call_id.expect("call not found"))
+        else {
+            panic!("call is not a node of the restored codebase");
+        };
+        assert!(call
+            .reference
+            .as_ref()
+            .is_some_and(|circuit| Rc::ptr_eq(circuit, &add_one)));
+        assert!(matches!(
+            restored.find_node_by_id(add_one.id),
+            Some(NodeType::Definition(Definition::Circuit(circuit))) if Rc::ptr_eq(circuit, &add_one)
+        ));
+        assert!(Codebase::<SealedState>::from_json("{}").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_walk_visitor() -> anyhow::Result<()> {
+        #[derive(Default)]
+        struct IndexAccessCollector {
+            circuits: Vec<String>,
+            accesses: Vec<(String, String)>,
+        }
+        impl Visitor for IndexAccessCollector {
+            fn visit_definition(&mut self, definition: &Definition) {
+                if let Definition::Circuit(circuit) = definition {
+                    self.circuits.push(circuit.name());
+                }
+            }
+
+            fn visit_expression(&mut self, expression: &Expression) {
+                if let Expression::IndexAccess(index_access) = expression {
+                    let circuit = self.circuits.last().cloned().unwrap_or_default();
+                    self.accesses
+                        .push((circuit, index_access.location.source.clone()));
+                }
+            }
+
+            fn leave_node(&mut self, node: &NodeType) {
+                if matches!(node, NodeType::Definition(Definition::Circuit(_))) {
+                    self.circuits.pop();
+                }
+            }
+        }
+
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "a.compact",
+            "circuit first(v: Vector<3, Field>): Field {
+                return v[0] + v[2];
+            }",
+        );
+        codebase.add_file(
+            "b.compact",
+            "circuit second(v: Vector<2, Field>): Field {
+                const x = v[1];
+                return x;
+            }",
+        );
+        let sealed = codebase.seal()?;
+        let mut collector = IndexAccessCollector::default();
+        sealed.walk(&mut collector);
+        let access = |circuit: &str, source: &str| (circuit.to_string(), source.to_string());
+        assert_eq!(
+            collector.accesses,
+            vec![
+                access("first", "v[0]"),
+                access("first", "v[2]"),
+                access("second", "v[1]"),
+            ]
+        );
+        assert!(collector.circuits.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_doc_comment() -> anyhow::Result<()> {
+        let source = "export ledger supply: Uint<64>;
+
+/// Mints new tokens.
+/// The amount must be positive.
+export circuit mint(amount: Uint<64>): [] {
+    supply = supply + amount;
+}
+
+/**
+ * Burns tokens.
+ */
+export circuit burn(amount: Uint<64>): [] {
+    supply = supply - amount;
+}
+
+export circuit total(): Uint<64> {
+    return supply;
+}";
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file("test.compact", source);
+        let sealed = codebase.seal()?;
+        let doc = |name: &str| {
+            let circuit = sealed
+                .circuit_by_name_in_file("test.compact", name)
+                .expect("circuit not found");
+            sealed.doc_comment(circuit.id)
+        };
+        assert_eq!(
+            doc("mint").as_deref(),
+            Some("Mints new tokens.\nThe amount must be positive.")
+        );
+        assert_eq!(doc("burn").as_deref(), Some("Burns tokens."));
+        assert_eq!(doc("total"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_comments_for() -> anyhow::Result<()> {
+        let source = "export ledger admin: Bytes<32>;
+
+// Sets the admin.
+/* Only callable once. */
+export circuit set_admin(new_admin: Bytes<32>): [] {
+    admin = new_admin;
+}
+export circuit get_admin(): Bytes<32> {
+    return admin;
+}";
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file("test.compact", source);
+        let sealed = codebase.seal()?;
+        let circuit_id = |name: &str| {
+            sealed
+                .circuit_by_name_in_file("test.compact", name)
+                .map(|circuit| circuit.id)
+                .expect("circuit not found")
+        };
+        let comments = sealed.comments_for(circuit_id("set_admin"));
+        assert_eq!(comments.len(), 2);
+        assert_eq!(comments[0].text(), "Sets the admin.");
+        assert_eq!(
+            comments[0].location.offset_start as usize,
+            source.find("// Sets").unwrap()
+        );
+        assert_eq!(comments[1].location.source, "/* Only callable once. */");
+        assert_eq!(
+            comments[1].location.offset_start as usize,
+            source.find("/* Only").unwrap()
+        );
+        assert_eq!(comments[1].location.start_line, 4);
+        assert!(sealed.comments_for(circuit_id("get_admin")).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_suppression_comments() -> anyhow::Result<()> {
+        let source = "export ledger admin: Bytes<32>;
+// compact-disable-next-line unused-ledger, other-detector
+export ledger owner: Bytes<32>;
+export ledger counter: Bytes<32>; /* compact-disable-line */
+// compact-disable-next-line other-detector
+export ledger nonce: Bytes<32>;";
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file("test.compact", source);
+        let sealed = codebase.seal()?;
+        assert_eq!(sealed.comments_of_file("test.compact").len(), 3);
+        assert_eq!(
+            sealed.comments_of_file("test.compact")[1].text(),
+            "compact-disable-line"
+        );
+        let result = |name: &str| {
+            let offset = u32::try_from(source.find(name).unwrap()).unwrap();
+            DetectorResult {
+                file_path: "test.compact".to_string(),
+                offset_start: offset,
+                offset_end: offset + 5,
+                extra: None,
+            }
+        };
+        assert!(!sealed.is_suppressed("unused-ledger", &result("admin")));
+        assert!(sealed.is_suppressed("unused-ledger", &result("owner")));
+        assert!(sealed.is_suppressed("unused-ledger", &result("counter")));
+        assert!(!sealed.is_suppressed("unused-ledger", &result("nonce")));
+        let results = ["admin", "owner", "counter", "nonce"].map(result).to_vec();
+        let kept = sealed.unsuppressed_results("unused-ledger", results);
+        assert_eq!(kept, vec![result("admin"), result("nonce")]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_clone_shallow() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "test.compact",
+            "export ledger admin: Bytes<32>;
+            export circuit set_admin(new_admin: Bytes<32>): [] {
+                admin = new_admin;
+            }",
+        );
+        let sealed = codebase.seal()?;
+        let copy = sealed.clone_shallow();
+        let original_file = sealed.files().next().expect("file not found");
+        let copied_file = copy.files().next().expect("file not found");
+        assert!(Rc::ptr_eq(original_file.ast(), copied_file.ast()));
+        assert_eq!(
+            copy.circuits_writing_ledger_without_assert(),
+            sealed.circuits_writing_ledger_without_assert()
+        );
+        let edited = copy.replace_file("other.compact", "export ledger owner: Bytes<32>;")?;
+        assert_eq!(edited.files().count(), 2);
+        assert_eq!(sealed.files().count(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_update_file_keeps_other_node_ids() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file("a.compact", "export ledger admin: Bytes<32>;");
+        codebase.add_file(
+            "b.compact",
+            "export circuit set_admin(new_admin: Bytes<32>): [] {
+                admin = new_admin;
+            }",
+        );
+        codebase.add_file("c.compact", "export ledger nonce: Uint<64>;");
+        let sealed = codebase.seal()?;
+        let node_ids = |codebase: &Codebase<SealedState>, fname: &str| -> Vec<u32> {
+            let file = codebase
+                .files()
+                .find(|file| file.file_path == fname)
+                .expect("file not found");
+            let mut ids: Vec<u32> = codebase
+                .descendants_of(file.ast().id)
+                .map(NodeType::id)
+                .collect();
+            ids.push(file.ast().id);
+            ids.sort_unstable();
+            ids.dedup();
+            ids
+        };
+        let (b_ids, c_ids) = (
+            node_ids(&sealed, "b.compact"),
+            node_ids(&sealed, "c.compact"),
+        );
+        let a_ids = node_ids(&sealed, "a.compact");
+        let sealed_writes = sealed.circuits_writing_ledger_without_assert();
+
+        let mut codebase = sealed.unseal();
+        codebase.update_file(
+            "a.compact",
+            "export ledger admin: Bytes<32>;\nledger owner: Field;",
+        );
+        let resealed = codebase.reseal()?;
+        assert_eq!(resealed.files().count(), 3);
+        assert_eq!(node_ids(&resealed, "b.compact"), b_ids);
+        assert_eq!(node_ids(&resealed, "c.compact"), c_ids);
+        assert_ne!(node_ids(&resealed, "a.compact"), a_ids);
+        assert_eq!(
+            resealed.circuits_writing_ledger_without_assert(),
+            sealed_writes
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_before_any_assert() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "test.compact",
+            "export ledger admin: Bytes<32>;
+            export circuit set_admin(new_admin: Bytes<32>): [] {
+                admin = new_admin;
+                assert(new_admin != pad(32, \"\"), \"empty admin\");
+            }
+            export circuit checked(new_admin: Bytes<32>): [] {
+                assert(new_admin != pad(32, \"\"), \"empty admin\");
+                admin = new_admin;
+            }",
+        );
+        let sealed = codebase.seal()?;
+        let circuit = |name: &str| {
+            sealed
+                .circuit_by_name_in_file("test.compact", name)
+                .expect("circuit not found")
+        };
+        let set_admin = circuit("set_admin");
+        assert_eq!(set_admin.first_statement_kind(), Some("assign"));
+        let write_id = sealed
+            .write_before_any_assert(set_admin.id)
+            .expect("write before assert not found");
+        match sealed.find_node_by_id(write_id) {
+            Some(NodeType::Statement(Statement::Assign(assign))) => {
+                assert!(assign.location.source.starts_with("admin = new_admin"));
+            }
+            _ => panic!("Expected an assignment"),
+        }
+        let checked = circuit("checked");
+        assert_eq!(checked.first_statement_kind(), Some("assert"));
+        assert_eq!(sealed.write_before_any_assert(checked.id), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_ctags() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "src/admin.compact",
+            "export ledger admin: Bytes<32>;
+export circuit set_admin(new_admin: Bytes<32>): [] {
+    admin = new_admin;
+}
+witness secret_key(): Bytes<32>;",
+        );
+        let sealed = codebase.seal()?;
+        let ctags = sealed.to_ctags();
+        let lines: Vec<&str> = ctags.lines().collect();
+        assert!(lines[0].starts_with("!_TAG_FILE_FORMAT"));
+        assert!(lines.contains(
+            &"set_admin\tsrc/admin.compact\t/^export circuit set_admin(new_admin: Bytes<32>): [] {$/;\"\tc\tline:2"
+        ));
+        assert!(lines.contains(
+            &"admin\tsrc/admin.compact\t/^export ledger admin: Bytes<32>;$/;\"\tl\tline:1"
+        ));
+        assert!(lines
+            .iter()
+            .any(|line| line.starts_with("secret_key\tsrc/admin.compact\t")
+                && line.ends_with("\tw\tline:5")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_line_col() -> anyhow::Result<()> {
+        let source = "circuit foo(): [] {\r\n    assert(true, \"é€\");\r\n}";
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file("test.compact", source);
+        let sealed = codebase.seal()?;
+        let assert_offset = source.find("assert").unwrap();
+        assert_eq!(sealed.line_col("test.compact", assert_offset), Some((2, 5)));
+        let closing_quote = source.find("\");").unwrap();
+        assert_eq!(
+            sealed.line_col("test.compact", closing_quote),
+            Some((2, 21))
+        );
+        assert_eq!(sealed.line_col("test.compact", source.len()), Some((3, 2)));
+        assert_eq!(sealed.line_col("test.compact", source.len() + 1), None);
+        assert_eq!(sealed.line_col("missing.compact", 0), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_node_path() -> anyhow::Result<()> {
+        let source = "export circuit set_admin(new_admin: Bytes<32>): [] {
+                assert admin == pad(32, \"\") \"\";
+                admin = new_admin;
+            }";
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file("test.compact", source);
+        let sealed = codebase.seal()?;
+        let offset = u32::try_from(source.find("admin ==").expect("assert not found") + 2)?;
+        let path = sealed.node_path("test.compact", offset);
+        let kinds: Vec<&str> = path.iter().map(|(kind, _)| kind.as_str()).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                "Program",
+                "Circuit",
+                "Block",
+                "Assert",
+                "Binary",
+                "Identifier"
+            ]
+        );
+        assert_eq!(path[1].1.as_deref(), Some("set_admin"));
+        assert_eq!(path[5].1.as_deref(), Some("admin"));
+        assert!(sealed.node_path("missing.compact", offset).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_children_of_block() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "test.compact",
+            "export circuit set_admin(new_admin: Bytes<32>): [] {
+                assert admin == pad(32, \"\") \"\";
+                admin = new_admin;
+                return [];
+            }",
+        );
+        let sealed = codebase.seal()?;
+        let block = sealed
+            .list_nodes_cmp(|node| {
+                if let NodeType::Statement(Statement::Block(block)) = node {
+                    Some(block.clone())
+                } else {
+                    None
+                }
+            })
+            .next()
+            .expect("block node not found");
+        let children = sealed.children_of(block.id);
+        assert_eq!(children.len(), 3);
+        assert!(matches!(
+            children[0],
+            NodeType::Statement(Statement::Assert(_))
+        ));
+        assert!(matches!(
+            children[1],
+            NodeType::Statement(Statement::Assign(_))
+        ));
+        assert!(matches!(
+            children[2],
+            NodeType::Statement(Statement::Return(_))
+        ));
+        let ids: Vec<u32> = children.iter().map(|child| child.id()).collect();
+        let expected: Vec<u32> = block.statements.iter().map(Statement::id).collect();
+        assert_eq!(ids, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_list_nodes_by_kind() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "test.compact",
+            "circuit foo(x: Uint<8>): Uint<8> {
+                for (const i of 0 .. 2) {
+                    for (const j of 0 .. 3) { }
+                }
+                return x;
+            }",
+        );
+        codebase.add_file(
+            "other.compact",
+            "circuit bar(): [] { for (const k of 0 .. 1) { } }",
+        );
+        let sealed = codebase.seal()?;
+        let by_kind: Vec<u32> = sealed
+            .list_nodes_by_kind(|node| matches!(node, NodeType::Statement(Statement::For(_))))
+            .map(NodeType::id)
+            .collect();
+        let mut expected: Vec<u32> = sealed
+            .list_for_statement_nodes()
+            .map(|for_stmt| for_stmt.id)
+            .collect();
+        expected.sort_unstable();
+        expected.dedup();
+        assert_eq!(by_kind.len(), 3);
+        assert_eq!(by_kind, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_conditional_branches_reachable() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "test.compact",
+            "circuit pick(flag: Boolean, a: Field, b: Field): Field {
+                const x = flag ? a : b;
+                return x;
+            }",
+        );
+        let sealed = codebase.seal()?;
+        let conditional = sealed
+            .list_nodes_cmp(|node| match node {
+                NodeType::Expression(Expression::Conditional(conditional)) => {
+                    Some(conditional.clone())
+                }
+                _ => None,
+            })
+            .next()
+            .expect("conditional expression not found");
+        let reachable: Vec<u32> = sealed
+            .get_children_cmp(conditional.id, |_| true)
+            .iter()
+            .map(NodeType::id)
+            .collect();
+        for branch in [
+            &conditional.condition,
+            &conditional.then_branch,
+            &conditional.else_branch,
+        ] {
+            assert!(reachable.contains(&branch.id()));
+        }
+        let symbol = sealed
+            .list_nodes_cmp(|node| match node {
+                NodeType::Expression(Expression::Identifier(ident)) if ident.name == "x" => {
+                    sealed.get_symbol_type_by_id(ident.id)
+                }
+                _ => None,
+            })
+            .next();
+        assert!(matches!(symbol, Some(Type::Field(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_descendants_of_circuit() -> anyhow::Result<()> {
+        fn subtree_size(codebase: &Codebase<SealedState>, id: u32) -> usize {
+            1 + codebase
+                .children_of(id)
+                .iter()
+                .map(|child| subtree_size(codebase, child.id()))
+                .sum::<usize>()
+        }
+
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "test.compact",
+            "export circuit set_admin(new_admin: Bytes<32>): [] {
+                assert admin == pad(32, \"\") \"\";
+                admin = new_admin;
+                return [];
+            }",
+        );
+        let sealed = codebase.seal()?;
+        let circuit = sealed
+            .list_nodes_cmp(|node| {
+                if let NodeType::Definition(Definition::Circuit(circuit)) = node {
+                    Some(circuit.clone())
+                } else {
+                    None
+                }
+            })
+            .next()
+            .expect("set_admin node not found");
+        let descendants: Vec<&NodeType> = sealed.descendants_of(circuit.id).collect();
+        assert_eq!(descendants.len(), subtree_size(&sealed, circuit.id) - 1);
+        assert!(descendants.iter().all(|node| node.id() != circuit.id));
+        assert!(descendants
+            .iter()
+            .any(|node| matches!(node, NodeType::Statement(Statement::Assert(_)))));
+        let first_statement = descendants
+            .iter()
+            .position(|node| matches!(node, NodeType::Statement(Statement::Block(_))))
+            .expect("block not found");
+        assert!(matches!(
+            descendants[first_statement + 1],
+            NodeType::Statement(Statement::Assert(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_snippet_with_context() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "test.compact",
+            "export circuit set_admin(new_admin: Bytes<32>): [] {
+    const current_proof = generate_key_proof(sigCounter as Field as Bytes<32>);
+    assert admin == pad(32, \"\") \"\";
+    admin = new_admin;
+    return [];
+}",
+        );
+        let sealed = codebase.seal()?;
+        let assert_node = sealed.list_assert_nodes().next().expect("assert not found");
+        let snippet = sealed
+            .snippet(assert_node.id, 1)
+            .expect("snippet not rendered");
+        let lines: Vec<&str> = snippet.lines().collect();
+        assert_eq!(lines.len(), 4, "{snippet}");
+        assert!(lines[0].starts_with("2 | "), "{snippet}");
+        assert!(lines[1].starts_with("3 | "), "{snippet}");
+        assert!(lines[3].starts_with("4 | "), "{snippet}");
+        let underline = lines[2];
+        assert!(underline.starts_with("  | "), "{snippet}");
+        assert_eq!(underline.find('^'), lines[1].find("assert"), "{snippet}");
+        assert!(underline
+            .trim_start_matches(['|', ' '])
+            .chars()
+            .all(|c| c == '^'));
+        Ok(())
+    }
+
+    #[test]
+    fn test_circuits_calling() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "test.compact",
+            "export circuit set_admin(new_admin: Bytes<32>): [] {
+                assert admin == pad(32, \"\") \"Admin is already set\";
+                admin = new_admin;
+            }
+
+            export circuit reset_admin(): [] {
+                admin = pad(32, \"\");
+            }
+
+            export circuit get_admin(): Bytes<32> {
+                return admin;
+            }",
+        );
+        let sealed = codebase.seal()?;
+        let mut callers: Vec<String> = sealed
+            .circuits_calling("pad")
+            .iter()
+            .map(|circuit| circuit.name())
+            .collect();
+        callers.sort();
+        assert_eq!(callers, vec!["reset_admin", "set_admin"]);
+        assert!(sealed.circuits_calling("unknown").is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_register_pass() -> anyhow::Result<()> {
+        struct CircuitCount(usize);
+        struct CountCircuits;
+        impl AnalysisPass for CountCircuits {
+            fn run(&self, codebase: &Codebase<SealedState>) -> Box<dyn Any> {
+                let count = codebase.files().map(|f| f.ast().circuits().len()).sum();
+                Box::new(CircuitCount(count))
+            }
+        }
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.register_pass(Box::new(CountCircuits));
+        codebase.add_file(
+            "test.compact",
+            "circuit foo(): Uint<8> { return 1; }
+            circuit bar(): Uint<8> { return 2; }",
+        );
+        let sealed = codebase.seal()?;
+        assert_eq!(sealed.pass_result::<CircuitCount>().map(|c| c.0), Some(2));
+        assert!(sealed.pass_result::<String>().is_none());
+        let copy = sealed.clone_shallow();
+        assert_eq!(copy.pass_result::<CircuitCount>().map(|c| c.0), Some(2));
+        let edited = copy.replace_file("other.compact", "circuit baz(): Uint<8> { return 3; }")?;
+        assert_eq!(edited.pass_result::<CircuitCount>().map(|c| c.0), Some(3));
+        Ok(())
+    }
+
+    #[test]
+    fn test_source_of_file() -> anyhow::Result<()> {
+        let source = "circuit foo(): Uint<8> { return 1; }";
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file("test.compact", source);
+        let sealed = codebase.seal()?;
+        assert_eq!(sealed.source_of_file("test.compact"), Some(source));
+        assert_eq!(sealed.source_of_file("missing.compact"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_compact_version_report() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file("a.compact", "pragma language_version >= 0.14.0;");
+        codebase.add_file("b.compact", "pragma language_version >= 0.15.0;");
+        codebase.add_file("c.compact", "pragma language_version >= 0.14.0;");
+        codebase.add_file(
+            "d.compact",
+            "circuit foo(x: Field): Field {
+                return x;
+            }",
+        );
+        let sealed = codebase.seal()?;
+        let report = sealed.compact_version_report();
+        assert_eq!(report.len(), 3);
+        assert_eq!(report[">= 0.14.0"], vec!["a.compact", "c.compact"]);
+        assert_eq!(report[">= 0.15.0"], vec!["b.compact"]);
+        assert_eq!(report["unspecified"], vec!["d.compact"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_calls_with_literal_args() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "test.compact",
+            "circuit scale(x: Field, y: Field): Field {
+                return x * y;
+            }
+            export circuit run(x: Field): Bytes<32> {
+                const z = scale(x, 2);
+                return pad(32, \"\");
+            }",
+        );
+        let sealed = codebase.seal()?;
+        let pads = sealed.calls_with_literal_args("pad");
+        assert_eq!(pads.len(), 1);
+        let arguments = &pads[0].1;
+        assert!(matches!(&arguments[0], Some(Literal::Nat(nat)) if nat.value == 32));
+        assert!(matches!(&arguments[1], Some(Literal::Str(str)) if str.value == "\"\""));
+        let scales = sealed.calls_with_literal_args("scale");
+        assert_eq!(scales.len(), 1);
+        assert!(scales[0].1[0].is_none());
+        assert!(matches!(&scales[0].1[1], Some(Literal::Nat(nat)) if nat.value == 2));
+        assert!(sealed.calls_with_literal_args("missing").is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_calls_to_deprecated() -> anyhow::Result<()> {
+        fn version(major: u64, minor: u64, bugfix: u64) -> Version {
+            let nat = |value| {
+                Rc::new(crate::ast::literal::Nat {
+                    id: 0,
+                    location: crate::ast::node::Location::default(),
+                    value,
+                })
+            };
+            Version {
+                id: 0,
+                location: crate::ast::node::Location::default(),
+                major: nat(major),
+                minor: Some(nat(minor)),
+                bugfix: Some(nat(bugfix)),
+                operator: crate::ast::literal::VersionOperator::Eq,
+            }
+        }
+
+        let source = |language_version: &str| {
+            format!(
+                "pragma language_version >= {language_version};
+                circuit oldFn(x: Field): Field {{
+                    return x;
+                }}
+                circuit caller(x: Field): Field {{
+                    return oldFn(x);
+                }}"
+            )
+        };
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file("new.compact", &source("1.3.0"));
+        codebase.add_file("old.compact", &source("1.1.0"));
+        let sealed = codebase.seal()?;
+        let deprecated = HashMap::from([("oldFn".to_string(), version(1, 2, 0))]);
+        let calls = sealed.calls_to_deprecated(&deprecated);
+        assert_eq!(calls.len(), 1);
+        let file = sealed.find_node_file(calls[0]).expect("file not found");
+        assert_eq!(file.file_path, "new.compact");
+        Ok(())
+    }
+
+    #[test]
+    fn test_for_statements_with_files() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "a.compact",
+            "circuit foo(x: Uint<8>): Uint<8> {
+                for (const i of 0 .. 1) { }
+                return x;
+            }",
+        );
+        codebase.add_file(
+            "b.compact",
+            "circuit bar(x: Uint<8>): Uint<8> {
+                for (const i of 0 .. 2) { }
+                for (const j of 0 .. 3) { }
+                return x;
+            }",
+        );
+        let sealed = codebase.seal()?;
+        let loops: Vec<(String, &str)> = sealed
+            .for_statements_with_files()
+            .map(|(for_stmt, file_path)| (for_stmt.location.source.clone(), file_path))
+            .collect();
+        assert_eq!(
+            loops,
+            vec![
+                ("for (const i of 0 .. 1) { }".to_string(), "a.compact"),
+                ("for (const i of 0 .. 2) { }".to_string(), "b.compact"),
+                ("for (const j of 0 .. 3) { }".to_string(), "b.compact"),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_list_interpolated_strings() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "test.compact",
+            "circuit check(x: Field): [] {
+                assert x == 1 \"plain message\";
+                assert x != 2 \"unexpected value ${x}\";
+            }",
+        );
+        let sealed = codebase.seal()?;
+        let strings: Vec<_> = sealed.list_interpolated_strings().collect();
+        assert_eq!(strings.len(), 1);
+        assert!(strings[0].value.contains("${x}"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_dependency_graph() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file("./a.compact", r#"import "./b.compact";"#);
+        codebase.add_file("./b.compact", r#"import "./a.compact";"#);
+        codebase.add_file(
+            "./c.compact",
+            r#"import "./a.compact";
+            import CompactStandardLibrary;"#,
+        );
+        let sealed = codebase.seal()?;
+        let graph = sealed.file_dependency_graph();
+        assert_eq!(graph.dependencies_of("./a.compact"), vec!["./b.compact"]);
+        assert_eq!(graph.dependencies_of("./c.compact"), vec!["./a.compact"]);
+        assert_eq!(
+            graph.cycles(),
+            vec![vec!["./a.compact".to_string(), "./b.compact".to_string()]]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_circuit_by_name_in_file() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "./a.compact",
+            "import \"./b.compact\";
+            circuit helper(x: Field): Field {
+                return x;
+            }",
+        );
+        codebase.add_file(
+            "./b.compact",
+            "circuit helper(x: Field): Field {
+                return x + 1;
+            }
+            circuit only_in_b(x: Field): Field {
+                return x;
+            }",
+        );
+        let sealed = codebase.seal()?;
+        let file_of = |circuit: Rc<Circuit>| {
+            sealed
+                .find_node_file(circuit.id)
+                .map(|file| file.file_path)
+                .expect("circuit file not found")
+        };
+        let local = sealed
+            .circuit_by_name_in_file("./a.compact", "helper")
+            .expect("helper not found");
+        assert_eq!(file_of(local), "./a.compact");
+        let imported = sealed
+            .circuit_by_name_in_file("./a.compact", "only_in_b")
+            .expect("only_in_b not found");
+        assert_eq!(file_of(imported), "./b.compact");
+        let from_b = sealed
+            .circuit_by_name_in_file("./b.compact", "helper")
+            .expect("helper not found");
+        assert_eq!(file_of(from_b), "./b.compact");
+        assert!(sealed
+            .circuit_by_name_in_file("./b.compact", "missing")
+            .is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_reexported_symbols() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "./a.compact",
+            "import \"./b.compact\";
+            export { helper };
+            export { local };
+            circuit local(x: Field): Field {
+                return x;
+            }",
+        );
+        codebase.add_file(
+            "./b.compact",
+            "circuit helper(x: Field): Field {
+                return x;
+            }",
+        );
+        let sealed = codebase.seal()?;
+        assert_eq!(
+            sealed.reexported_symbols("./a.compact"),
+            vec![("helper".to_string(), "./b.compact".to_string())]
+        );
+        assert!(sealed.reexported_symbols("./b.compact").is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_exit_points() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "test.compact",
+            "circuit pick(flag: Boolean, a: Field, b: Field): Field {
+                if (flag) {
+                    return a;
+                } else {
+                    return b;
+                }
+            }
+            circuit noop(): [] {
+                const x = 1;
+            }",
+        );
+        let sealed = codebase.seal()?;
+        let pick = sealed
+            .circuit_by_name_in_file("test.compact", "pick")
+            .expect("pick circuit not found");
+        let returns: Vec<u32> = pick
+            .body
+            .as_ref()
+            .expect("pick has a body")
+            .all_statements()
+            .iter()
+            .filter_map(|stmt| match stmt {
+                Statement::Return(ret) => Some(ret.id),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(returns.len(), 2);
+        assert_eq!(sealed.exit_points(pick.id), returns);
+        let noop = sealed
+            .circuit_by_name_in_file("test.compact", "noop")
+            .expect("noop circuit not found");
+        let noop_body = noop.body.as_ref().expect("noop has a body");
+        assert_eq!(sealed.exit_points(noop.id), vec![noop_body.id]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_circuit_effects() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "test.compact",
+            "export ledger admin: Bytes<32>;
+            witness generate_key_proof(value: Bytes<32>): Bytes<32>;
+            export circuit set_admin(new_admin: Bytes<32>): [] {
+                const current_proof = generate_key_proof(new_admin);
+                assert admin == pad(32, \"\") \"\";
+                verify_proof(current_proof);
+                admin = new_admin;
+                return [];
+            }",
+        );
+        let sealed = codebase.seal()?;
+        let set_admin = sealed
+            .list_nodes_cmp(|node| {
+                if let NodeType::Definition(Definition::Circuit(circuit)) = node {
+                    Some(circuit.clone())
+                } else {
+                    None
+                }
+            })
+            .next()
+            .expect("set_admin node not found");
+        let effects = sealed.circuit_effects(set_admin.id);
+        assert_eq!(effects.ledger_reads, vec!["admin"]);
+        assert_eq!(effects.ledger_writes, vec!["admin"]);
+        assert_eq!(effects.witness_calls, vec!["generate_key_proof"]);
+        assert_eq!(effects.external_calls, vec!["verify_proof"]);
+        assert_eq!(effects.asserts, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_weak_asserts() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "test.compact",
+            "circuit foo(x: Boolean): [] {
+                assert true;
+                assert x \"  \";
+                assert !(true && false) || x \"never fails\";
+                assert x \"x must be set\";
+                return [];
+            }",
+        );
+        let sealed = codebase.seal()?;
+        let weak_asserts = sealed.weak_asserts();
+        let reasons: Vec<_> = weak_asserts
+            .iter()
+            .map(|weak_assert| weak_assert.reasons.clone())
+            .collect();
+        assert_eq!(
+            reasons,
+            vec![
+                vec![
+                    WeakAssertReason::MissingMessage,
+                    WeakAssertReason::AlwaysTrue
+                ],
+                vec![WeakAssertReason::BlankMessage],
+                vec![WeakAssertReason::AlwaysTrue],
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_ledger_write_shadowed_by_local() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "test.compact",
+            "export ledger admin: Field;
+            export circuit shadowed(): [] {
+                const admin = 1;
+                admin = 2;
+            }
+            export circuit direct(): [] {
+                admin = 2;
+            }",
+        );
+        let sealed = codebase.seal()?;
+        let mut assigns: Vec<(u32, u32)> = sealed
+            .list_nodes_cmp(|node| match node {
+                NodeType::Statement(Statement::Assign(assign)) => {
+                    Some((assign.location.offset_start, assign.id))
+                }
+                _ => None,
+            })
+            .collect();
+        assigns.sort_unstable();
+        assigns.dedup();
+        assert_eq!(assigns.len(), 2);
+        assert_eq!(sealed.ledger_write_shadowed_by_local(), vec![assigns[0].1]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parameters_shadowing_outer() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "test.compact",
+            "export ledger admin: Bytes<32>;
+            export circuit set_admin(admin: Bytes<32>, owner: Bytes<32>): [] {
+                assert(admin != owner, \"same\");
+            }",
+        );
+        let sealed = codebase.seal()?;
+        let circuit = sealed
+            .circuit_by_name_in_file("test.compact", "set_admin")
+            .expect("circuit not found");
+        assert_eq!(
+            sealed.parameters_shadowing_outer(),
+            vec![circuit.arguments[0].id]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_circuits_writing_ledger_without_assert() -> anyhow::Result<()> {
+        let source = |check: &str| {
+            format!(
+                "export ledger admin: Bytes<32>;
+                export circuit set_admin(new_admin: Bytes<32>): [] {{
+                    {check}
+                    admin = new_admin;
+                }}"
+            )
+        };
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file("test.compact", &source(""));
+        let sealed = codebase.seal()?;
+        let set_admin = sealed
+            .circuit_by_name_in_file("test.compact", "set_admin")
+            .expect("set_admin circuit not found");
+        assert_eq!(
+            sealed.circuits_writing_ledger_without_assert(),
+            vec![set_admin.id]
+        );
+
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "test.compact",
+            &source("assert admin == pad(32, \"\") \"admin already set\";"),
+        );
+        let sealed = codebase.seal()?;
+        assert!(sealed.circuits_writing_ledger_without_assert().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_guarding_asserts() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "test.compact",
+            "export ledger admin: Bytes<32>;
+            export circuit set_admin(new_admin: Bytes<32>, force: Boolean): [] {
+                assert admin == pad(32, \"\") \"admin already set\";
+                if (force) {
+                    assert new_admin != pad(32, \"\") \"forced admin must be set\";
+                }
+                assert new_admin != pad(32, \"\") \"admin must be set\";
+                admin = new_admin;
+            }",
+        );
+        let sealed = codebase.seal()?;
+        let write_id = sealed
+            .list_nodes_cmp(|node| match node {
+                NodeType::Statement(Statement::Assign(assign)) => Some(assign.id),
+                _ => None,
+            })
+            .next()
+            .expect("ledger write not found");
+        let mut guards: Vec<Rc<Assert>> = sealed
+            .list_assert_nodes()
+            .filter(|assert| {
+                assert
+                    .message()
+                    .is_some_and(|message| !message.starts_with("forced"))
+            })
+            .collect();
+        guards.sort_by_key(|assert| assert.location.offset_start);
+        guards.dedup_by_key(|assert| assert.id);
+        let expected: Vec<u32> = guards.iter().map(|assert| assert.id).collect();
+        assert_eq!(expected.len(), 2);
+        assert_eq!(sealed.guarding_asserts(write_id), expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_asserts_after_write() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "test.compact",
+            "export ledger admin: Bytes<32>;
+            export circuit write_first(new_admin: Bytes<32>): [] {
+                admin = new_admin;
+                assert new_admin != pad(32, \"\") \"\";
+            }
+            export circuit check_first(new_admin: Bytes<32>): [] {
+                assert new_admin != pad(32, \"\") \"\";
+                admin = new_admin;
+            }
+            export circuit write_in_branch(new_admin: Bytes<32>, force: Boolean): [] {
+                if (force) {
+                    admin = new_admin;
+                } else {
+                    assert new_admin != pad(32, \"\") \"\";
+                }
+            }",
+        );
+        let sealed = codebase.seal()?;
+        let pairs = sealed.asserts_after_write();
+        assert_eq!(pairs.len(), 1);
+        let (assert_id, write_id) = pairs[0];
+        let Some(NodeType::Definition(Definition::Circuit(circuit))) =
+            sealed.get_parent_container(assert_id)
+        else {
+            panic!("assert is not inside a circuit");
+        };
+        assert_eq!(circuit.name(), "write_first");
+        assert!(matches!(
+            sealed.storage.find_node(write_id),
+            Some(NodeType::Statement(Statement::Assign(_)))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_unchecked_witness_flows() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "test.compact",
+            "export ledger admin: Bytes<32>;
+            export ledger owner: Bytes<32>;
+            witness get_admin(): Bytes<32>;
+            witness get_owner(): Bytes<32>;
+            export circuit set_admin(): [] {
+                const new_admin = get_admin();
+                admin = new_admin;
+            }
+            export circuit set_owner(): [] {
+                const new_owner = get_owner();
+                assert new_owner != pad(32, \"\") \"\";
+                owner = new_owner;
+            }
+            export circuit set_owner_if(flag: Boolean): [] {
+                const new_owner = get_owner();
+                if (flag) {
+                    assert new_owner != pad(32, \"\") \"\";
+                }
+                owner = new_owner;
+            }",
+        );
+        let sealed = codebase.seal()?;
+        let flows = sealed.unchecked_witness_flows();
+        assert_eq!(flows.len(), 2);
+        assert!(flows.windows(2).all(|pair| pair[0] < pair[1]));
+        let Some(NodeType::Statement(Statement::Assign(assign))) =
+            sealed.storage.find_node(flows[1].1)
+        else {
+            panic!("sink is not an assignment");
+        };
+        assert!(assign.location.source.starts_with("owner = new_owner"));
+        let (source, sink) = flows[0];
+        let Some(NodeType::Expression(Expression::FunctionCall(call))) =
+            sealed.storage.find_node(source)
+        else {
+            panic!("source is not a function call");
+        };
+        assert_eq!(call.location.source, "get_admin()");
+        let Some(NodeType::Statement(Statement::Assign(assign))) = sealed.storage.find_node(sink)
+        else {
+            panic!("sink is not an assignment");
+        };
+        assert!(assign.location.source.starts_with("admin = new_admin"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_ancestors() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "test.compact",
+            "circuit foo(flag: Boolean): Field {
+                if (flag) {
+                    return 1;
+                }
+                return 0;
+            }",
+        );
+        let sealed = codebase.seal()?;
+        let program_id = sealed.files[0].ast.id;
+        assert!(sealed.get_ancestors(program_id).is_empty());
+        let nested_return = sealed
+            .list_return_nodes()
+            .min_by_key(|ret| ret.location.offset_start)
+            .expect("return statement not found");
+        let ancestors = sealed.get_ancestors(nested_return.id);
+        let block_position = ancestors
+            .iter()
+            .position(|node| matches!(node, NodeType::Statement(Statement::Block(_))))
+            .expect("enclosing block not found");
+        let circuit_position = ancestors
+            .iter()
+            .position(|node| matches!(node, NodeType::Definition(Definition::Circuit(_))))
+            .expect("enclosing circuit not found");
+        assert!(block_position < circuit_position);
+        assert!(
+            matches!(ancestors.last(), Some(NodeType::Program(program)) if program.id == program_id)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_module_members_and_ancestors() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "test.compact",
+            "module Math {
+                export circuit add(a: Field, b: Field): Field {
+                    return a + b;
+                }
+                export circuit double(a: Field): Field {
+                    return add(a, a);
+                }
+            }",
+        );
+        let sealed = codebase.seal()?;
+        let module = sealed
+            .list_nodes_cmp(|node| match node {
+                NodeType::Definition(Definition::Module(module)) => Some(module.clone()),
+                _ => None,
+            })
+            .next()
+            .expect("module not found");
+        assert_eq!(module.name(), "Math");
+        let circuits: Vec<Rc<Circuit>> = sealed
+            .circuits()
+            .into_iter()
+            .filter(|circuit| module.members().contains(&circuit.id))
+            .collect();
+        assert_eq!(module.members().len(), 2);
+        let mut names: Vec<String> = circuits.iter().map(|circuit| circuit.name()).collect();
+        names.sort();
+        assert_eq!(names, vec!["add", "double"]);
+        let ancestors = sealed.get_ancestors(circuits[0].id);
+        assert!(matches!(
+            ancestors.first(),
+            Some(NodeType::Definition(Definition::Module(parent))) if parent.id == module.id
+        ));
+        assert!(matches!(ancestors.last(), Some(NodeType::Program(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_redundant_casts() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "test.compact",
+            "circuit foo(x: Uint<8>): Uint<16> {
+                const y = x as Uint<8>;
+                return y as Uint<16>;
+            }",
+        );
+        let sealed = codebase.seal()?;
+        let casts: Vec<(u32, String)> = sealed
+            .list_nodes_cmp(|node| match node {
+                NodeType::Expression(Expression::Cast(cast)) => {
+                    Some((cast.id, cast.location.source.clone()))
+                }
+                _ => None,
+            })
+            .collect();
+        let redundant: Vec<u32> = casts
+            .iter()
+            .filter(|(_, source)| source.ends_with("Uint<8>"))
+            .map(|(id, _)| *id)
+            .collect();
+        assert_eq!(redundant.len(), 1);
+        assert_eq!(sealed.redundant_casts(), redundant);
+        Ok(())
+    }
+
+    #[test]
+    fn test_mismatched_returns() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "test.compact",
+            "circuit c(): Uint<8> {
+                return true;
             }
-        ";
-        let source_b = r#"
-            import "./a.compact";
-            pure circuit calculate_ship_def(shot_attempt: Coord, ship_state: ShipState, updated_ship_state: ShipState, ships: Ships, player: Bytes<32>): ShotResult {
-                return unknown_ship_def();
+            circuit d(): Uint<8> {
+                return 0;
+            }",
+        );
+        let sealed = codebase.seal()?;
+        let returns: Vec<(u32, String)> = sealed
+            .list_return_nodes()
+            .map(|return_stmt| (return_stmt.id, return_stmt.location.source.clone()))
+            .collect();
+        let mut mismatched: Vec<u32> = returns
+            .iter()
+            .filter(|(_, source)| source.contains("true"))
+            .map(|(id, _)| *id)
+            .collect();
+        mismatched.dedup();
+        assert_eq!(mismatched.len(), 1);
+        assert_eq!(sealed.mismatched_returns(), mismatched);
+        Ok(())
+    }
+
+    #[test]
+    fn test_oversized_collections() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "test.compact",
+            "ledger big: Vector<100000, Field>;
+            ledger small: Vector<4, Field>;
+            ledger key: Bytes<32>;",
+        );
+        let sealed = codebase.seal()?;
+        let big = sealed
+            .list_nodes_cmp(|node| match node {
+                NodeType::Type(Type::Vector(vector)) if vector.size_nat() == Some(100_000) => {
+                    Some(vector.id)
+                }
+                _ => None,
+            })
+            .next()
+            .expect("vector type not found");
+        assert_eq!(sealed.oversized_collections(1024), vec![big]);
+        assert_eq!(sealed.oversized_collections(16).len(), 2);
+        assert!(sealed.oversized_collections(100_000).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_integer_divisions() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "test.compact",
+            "circuit foo(a: Uint<8>, b: Uint<8>): Uint<16> {
+                return a * b;
+            }",
+        );
+        let sealed = codebase.seal()?;
+        assert!(sealed.integer_divisions().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_integer_divisions_of_literals() -> anyhow::Result<()> {
+        let literal = |id, value| -> Expression {
+            Expression::Literal(Literal::Nat(Rc::new(crate::ast::literal::Nat {
+                id,
+                location: Location::default(),
+                value,
+            })))
+        };
+        let division = |id, right| {
+            NodeType::Expression(Expression::Binary(Rc::new(Binary {
+                id,
+                location: Location::default(),
+                left: literal(id + 1, 6),
+                right,
+                operator: BinaryExpressionOperator::Div,
+            })))
+        };
+        let boolean = Expression::Literal(Literal::Bool(Rc::new(crate::ast::literal::Bool {
+            id: 7,
+            location: Location::default(),
+            value: true,
+        })));
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_node(division(1, literal(3, 3)), 0);
+        codebase.add_node(division(4, boolean), 0);
+        let sealed = codebase.seal()?;
+        assert_eq!(sealed.integer_divisions(), vec![1]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_seal_overlapping_uint_ranges() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "test.compact",
+            "circuit foo(a: Uint<0..100>, b: Uint<50..300>): Uint<0..400> {
+                const sum = a + b;
+                return sum;
+            }",
+        );
+        let sealed = codebase.seal()?;
+        assert_eq!(sealed.list_return_nodes().count(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_expression_result_width() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "test.compact",
+            "circuit foo(a: Uint<8>, b: Uint<8>, c: Uint<16>): Uint<32> {
+                const sum = a + b;
+                const product = a * b;
+                const difference = c - a;
+                return sum + product + difference;
+            }",
+        );
+        let sealed = codebase.seal()?;
+        let width = |source: &str| {
+            sealed.expression_result_width(expression_with_source(&sealed, source).id())
+        };
+        assert_eq!(width("a + b"), Some(9));
+        assert_eq!(width("a * b"), Some(16));
+        assert_eq!(width("c - a"), Some(16));
+        Ok(())
+    }
+
+    #[test]
+    fn test_struct_field_types() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "test.compact",
+            "struct Point { x: Uint<8>, y: Uint<8> }
+            struct Segment { start: Point, end: Point }
+            circuit foo(p: Point, s: Segment): Uint<8> {
+                return p.x + s.end.y;
+            }",
+        );
+        let sealed = codebase.seal()?;
+        let member_access = |source: &str| expression_with_source(&sealed, source);
+        let is_uint8 =
+            |ty: Option<Type>| matches!(ty, Some(Type::Uint(uint)) if uint.start.value == 8);
+
+        let Expression::MemberAccess(p_x) = member_access("p.x") else {
+            panic!("p.x is not a member access");
+        };
+        assert!(is_uint8(sealed.get_symbol_type_by_id(p_x.id)));
+        assert!(is_uint8(sealed.get_symbol_type_by_id(p_x.member.id)));
+        assert!(matches!(
+            sealed.get_symbol_type_by_id(member_access("s.end").id()),
+            Some(Type::Ref(reference)) if reference.name.name == "Point"
+        ));
+        assert!(is_uint8(
+            sealed.get_symbol_type_by_id(member_access("s.end.y").id())
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_boolean_literal_comparisons() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "test.compact",
+            "circuit foo(flag: Boolean, otherFlag: Boolean): Boolean {
+                const a = flag == true;
+                const b = flag == false;
+                return flag == otherFlag;
+            }",
+        );
+        let sealed = codebase.seal()?;
+        let comparison_id = |source: &str| expression_with_source(&sealed, source).id();
+        let mut expected = vec![
+            comparison_id("flag == true"),
+            comparison_id("flag == false"),
+        ];
+        expected.sort_unstable();
+        assert_eq!(sealed.boolean_literal_comparisons(), expected);
+        assert!(!expected.contains(&comparison_id("flag == otherFlag")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_constant_expression() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "test.compact",
+            "circuit foo(x: Field): Field {
+                const MAX = 3;
+                const a = 2 + MAX;
+                const b = 2 + x;
+                return a + b;
+            }",
+        );
+        let sealed = codebase.seal()?;
+        let binary_id = |source: &str| expression_with_source(&sealed, source).id();
+        assert!(sealed.is_constant_expression(binary_id("2 + MAX")));
+        assert!(!sealed.is_constant_expression(binary_id("2 + x")));
+        assert!(!sealed.is_constant_expression(binary_id("a + b")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_uses_witness() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "test.compact",
+            "witness secret_key(): Bytes<32>;
+            circuit b(): Bytes<32> {
+                return secret_key();
             }
-        "#;
-        codebase.add_file("./a.compact", source_a);
-        codebase.add_file("./b.compact", source_b);
+            circuit a(): Bytes<32> {
+                return b();
+            }
+            circuit c(x: Field): Field {
+                return x;
+            }",
+        );
         let sealed = codebase.seal()?;
+        let circuit_id = |name: &str| {
+            sealed
+                .circuit_by_name_in_file("test.compact", name)
+                .map(|circuit| circuit.id)
+                .expect("circuit not found")
+        };
+        assert!(sealed.uses_witness(circuit_id("a")));
+        assert!(sealed.uses_witness(circuit_id("b")));
+        assert!(!sealed.uses_witness(circuit_id("c")));
+        Ok(())
+    }
 
-        let unknown_ship_def_circuit_node = sealed
-            .list_nodes_cmp(|node| {
-                if let NodeType::Definition(Definition::Circuit(circuit)) = node {
-                    if circuit.name() == "unknown_ship_def" {
-                        return Some(circuit.clone());
+    #[test]
+    fn test_find_node_by_id() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "test.compact",
+            "circuit foo(v: Vector<2, Field>): Field {
+                return v[1];
+            }",
+        );
+        let sealed = codebase.seal()?;
+        let base_id = sealed
+            .storage
+            .nodes
+            .iter()
+            .find_map(|node| match node {
+                NodeType::Expression(Expression::IndexAccess(index_access)) => {
+                    Some(index_access.base.id())
+                }
+                _ => None,
+            })
+            .expect("index access not found");
+        match sealed.find_node_by_id(base_id) {
+            Some(NodeType::Expression(Expression::Identifier(identifier))) => {
+                assert_eq!(identifier.name, "v");
+            }
+            _ => panic!("Expected an identifier node"),
+        }
+        assert!(sealed
+            .storage
+            .nodes
+            .iter()
+            .all(|node| sealed.storage.node_index.contains_key(&node.id())));
+        assert!(sealed.find_node_by_id(u32::MAX).is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_unreachable_circuits() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "test.compact",
+            "circuit b(x: Field): Field {
+                return x;
+            }
+            export circuit a(x: Field): Field {
+                return b(x);
+            }
+            circuit c(x: Field): Field {
+                return x;
+            }",
+        );
+        let sealed = codebase.seal()?;
+        let names: Vec<String> = sealed
+            .unreachable_circuits()
+            .iter()
+            .map(|circuit| circuit.name())
+            .collect();
+        assert_eq!(names, vec!["c"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_symbol_definition_parameter() -> anyhow::Result<()> {
+        let source = "ledger counter: Uint<64>;
+circuit add(amount: Uint<64>): Uint<64> {
+    return counter + amount;
+}";
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file("test.compact", source);
+        let sealed = codebase.seal()?;
+        let circuit = sealed
+            .circuit_by_name_in_file("test.compact", "add")
+            .expect("circuit not found");
+        let amount = identifier_at(&sealed, source.find("+ amount").unwrap() + 2);
+        assert_eq!(
+            sealed.get_symbol_definition(amount),
+            Some(circuit.arguments[0].id)
+        );
+        let ledger_id = sealed
+            .list_nodes_cmp(|node| match node {
+                NodeType::Declaration(Declaration::Ledger(ledger)) => Some(ledger.id),
+                _ => None,
+            })
+            .next()
+            .expect("ledger not found");
+        let counter = identifier_at(&sealed, source.find("counter +").unwrap());
+        assert_eq!(sealed.get_symbol_definition(counter), Some(ledger_id));
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_symbol_definition_shadowed_const() -> anyhow::Result<()> {
+        let source = "circuit pick(flag: Boolean): Field {
+    const value = 1;
+    if (flag) {
+        const value = 2;
+        return value;
+    }
+    return value;
+}";
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file("test.compact", source);
+        let sealed = codebase.seal()?;
+        let const_at = |offset: usize| {
+            sealed
+                .list_nodes_cmp(|node| match node {
+                    NodeType::Statement(Statement::Const(const_stmt))
+                        if const_stmt.location.offset_start as usize == offset =>
+                    {
+                        Some(const_stmt.id)
                     }
+                    _ => None,
+                })
+                .next()
+                .expect("const not found")
+        };
+        let outer = const_at(source.find("const value = 1").unwrap());
+        let inner = const_at(source.find("const value = 2").unwrap());
+        let inner_use = identifier_at(&sealed, source.find("return value").unwrap() + 7);
+        let outer_use = identifier_at(&sealed, source.rfind("return value").unwrap() + 7);
+        assert_eq!(sealed.get_symbol_definition(inner_use), Some(inner));
+        assert_eq!(sealed.get_symbol_definition(outer_use), Some(outer));
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_symbol_definition_unresolved() -> anyhow::Result<()> {
+        let source = "circuit free(x: Field): Field {
+    return missing;
+}";
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file("test.compact", source);
+        let sealed = codebase.seal()?;
+        let offset = source.find("missing").unwrap();
+        let missing = sealed
+            .list_nodes_cmp(|node| match node {
+                NodeType::Expression(Expression::Identifier(identifier))
+                    if identifier.location.offset_start as usize == offset =>
+                {
+                    Some(identifier.id)
                 }
-                None
+                _ => None,
             })
             .next()
-            .expect("unknown_ship_def node not found");
-        let function_call_node = sealed
+            .expect("identifier not found");
+        assert_eq!(sealed.get_symbol_definition(missing), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_references() -> anyhow::Result<()> {
+        let source = "export ledger value: Field;
+export circuit get(): Field {
+    return value;
+}
+export circuit set(x: Field): [] {
+    value = x;
+}
+export circuit next(): Field {
+    return value + 1;
+}";
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file("test.compact", source);
+        let sealed = codebase.seal()?;
+        let ledger_id = sealed
+            .list_nodes_cmp(|node| match node {
+                NodeType::Declaration(Declaration::Ledger(ledger)) => Some(ledger.id),
+                _ => None,
+            })
+            .next()
+            .expect("ledger not found");
+        let declaration_offset = source.find("value").unwrap();
+        let mut uses: Vec<u32> = sealed
+            .list_nodes_cmp(|node| match node {
+                NodeType::Expression(Expression::Identifier(identifier))
+                    if identifier.name == "value"
+                        && identifier.location.offset_start as usize != declaration_offset =>
+                {
+                    Some(identifier.id)
+                }
+                _ => None,
+            })
+            .collect();
+        uses.sort_unstable();
+        uses.dedup();
+        assert_eq!(uses.len(), 3);
+        assert_eq!(sealed.find_references(ledger_id), uses);
+        assert!(sealed.find_references(u32::MAX).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_import_resolves_to() {
+        assert!(import_resolves_to(
+            "a/b/c/main.compact",
+            "../../Test",
+            "a/Test.compact"
+        ));
+        assert!(import_resolves_to(
+            "./main.compact",
+            "./lib/Token.compact",
+            "lib/Token.compact"
+        ));
+        assert!(import_resolves_to(
+            "main.compact",
+            "../Test",
+            "../Test.compact"
+        ));
+        assert!(!import_resolves_to(
+            "a/b/main.compact",
+            "../../Test",
+            "a/Test.compact"
+        ));
+    }
+
+    #[test]
+    fn test_module_qualified_resolution() -> anyhow::Result<()> {
+        let token = "export ledger balance: Uint<64>;
+export ledger x: Uint<64>;
+export circuit mint(amount: Uint<64>): Uint<64> {
+    return amount;
+}";
+        let main = "import \"../lib/Token\";
+export circuit run(x: Uint<64>): Uint<64> {
+    return Token.mint(x);
+}
+export circuit total(): Uint<64> {
+    return Token.balance;
+}
+export circuit shadowed(Token: Point): Uint<64> {
+    return Token.x;
+}";
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file("project/lib/Token.compact", token);
+        codebase.add_file("project/contracts/Main.compact", main);
+        let sealed = codebase.seal()?;
+        let member = |name: &str| {
+            sealed
+                .list_nodes_cmp(|node| match node {
+                    NodeType::Expression(Expression::MemberAccess(member_access))
+                        if member_access.member.name == name =>
+                    {
+                        Some(member_access.member.id)
+                    }
+                    _ => None,
+                })
+                .next()
+                .expect("member access not found")
+        };
+        let mint = sealed
+            .circuit_by_name_in_file("project/lib/Token.compact", "mint")
+            .expect("circuit not found");
+        assert_eq!(sealed.get_symbol_definition(member("mint")), Some(mint.id));
+        assert!(matches!(
+            sealed.get_symbol_type_by_id(member("balance")),
+            Some(Type::Uint(_))
+        ));
+        assert_eq!(sealed.get_symbol_definition(member("x")), None);
+        assert!(sealed.get_symbol_type_by_id(member("x")).is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_circuit_contains_loop() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "test.compact",
+            "circuit looping(v: Vector<4, Field>): Field {
+                const total = 0;
+                if (true) {
+                    for (const x of v) {
+                        assert(x != 0, \"zero\");
+                    }
+                }
+                return total;
+            }
+            circuit straight(x: Field): Field {
+                return looping([x, x, x, x]);
+            }",
+        );
+        let sealed = codebase.seal()?;
+        let circuit_id = |name: &str| {
+            sealed
+                .circuit_by_name_in_file("test.compact", name)
+                .map(|circuit| circuit.id)
+                .expect("circuit not found")
+        };
+        assert!(sealed.circuit_contains_loop(circuit_id("looping")));
+        assert!(!sealed.circuit_contains_loop(circuit_id("straight")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_unused_parameters() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "test.compact",
+            "circuit scale(value: Field, factor: Field): Field {
+                return value + value;
+            }",
+        );
+        let sealed = codebase.seal()?;
+        let circuit = sealed
+            .circuit_by_name_in_file("test.compact", "scale")
+            .expect("circuit not found");
+        assert_eq!(
+            sealed.unused_parameters(circuit.id),
+            vec![circuit.arguments[1].id]
+        );
+        assert!(sealed.unused_parameters(u32::MAX).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_fan_in_fan_out() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "test.compact",
+            "circuit leaf(x: Field): Field {
+                return x;
+            }
+            circuit hub(x: Field): Field {
+                return leaf(x);
+            }
+            circuit first(x: Field): Field {
+                const y = hub(x);
+                return hub(y);
+            }
+            circuit second(x: Field): Field {
+                return hub(x);
+            }",
+        );
+        let sealed = codebase.seal()?;
+        let circuit_id = |name: &str| {
+            sealed
+                .circuit_by_name_in_file("test.compact", name)
+                .map(|circuit| circuit.id)
+                .expect("circuit not found")
+        };
+        assert_eq!(sealed.fan_in(circuit_id("hub")), 2);
+        assert_eq!(sealed.fan_out(circuit_id("hub")), 1);
+        assert_eq!(sealed.fan_in(circuit_id("leaf")), 1);
+        assert_eq!(sealed.fan_out(circuit_id("leaf")), 0);
+        assert_eq!(sealed.fan_out(circuit_id("first")), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_identifiers_matching_reserved() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "test.compact",
+            "circuit foo(x: Field): Field {
+                const ledger = x + 1;
+                const total = ledger + 1;
+                return total;
+            }",
+        );
+        let sealed = codebase.seal()?;
+        let ids = sealed.identifiers_matching_reserved(&["ledger", "async"]);
+        assert_eq!(ids.len(), 1);
+        let Some(NodeType::Statement(Statement::Const(const_stmt))) =
+            sealed.storage.find_node(ids[0])
+        else {
+            panic!("reserved identifier is not a const declaration");
+        };
+        assert!(const_stmt.location.source.starts_with("const ledger"));
+        assert!(sealed.identifiers_matching_reserved(&["async"]).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_shadows_builtin() -> anyhow::Result<()> {
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file(
+            "test.compact",
+            "circuit foo(pad: Field, value: Field): Field { return value; }
+            circuit bar(foo: Field): Field { return foo; }",
+        );
+        let sealed = codebase.seal()?;
+        let parameters: HashMap<String, u32> = sealed
             .list_nodes_cmp(|node| {
-                if let NodeType::Expression(Expression::FunctionCall(func_call)) = node {
-                    return Some(func_call.clone());
+                if let NodeType::Declaration(Declaration::PatternArgument(argument)) = node {
+                    argument.name().map(|name| (name, argument.id))
+                } else {
+                    None
                 }
-                None
             })
-            .next()
-            .expect("Function call node not found");
+            .collect();
         assert_eq!(
-            function_call_node.reference.as_ref().unwrap().id,
-            unknown_ship_def_circuit_node.id,
-            "Function call reference should be set to the correct circuit id, expected: {}, found: {}",
-            unknown_ship_def_circuit_node.id, function_call_node.reference.as_ref().unwrap().id
+            sealed.shadows_builtin(parameters["pad"]),
+            Some("pad".to_string())
+        );
+        assert_eq!(
+            sealed.shadows_builtin(parameters["foo"]),
+            Some("foo".to_string())
         );
+        assert_eq!(sealed.shadows_builtin(parameters["value"]), None);
         Ok(())
     }
 }
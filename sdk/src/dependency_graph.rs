@@ -0,0 +1,162 @@
+//! # File dependency graph
+//!
+//! `DependencyGraph` maps every file of a codebase to the files it imports and detects import
+//! cycles. It is built with `Codebase::file_dependency_graph`.
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+/// A directed graph of imports between files, keyed by file path.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct DependencyGraph {
+    edges: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl DependencyGraph {
+    /// Adds a file to the graph, without dependencies.
+    pub fn add_file(&mut self, file_path: &str) {
+        self.edges.entry(file_path.to_string()).or_default();
+    }
+
+    /// Adds an import of `to` by `from`, both files are added to the graph if missing.
+    pub fn add_dependency(&mut self, from: &str, to: &str) {
+        self.add_file(to);
+        self.edges
+            .entry(from.to_string())
+            .or_default()
+            .insert(to.to_string());
+    }
+
+    /// Returns the paths of all files in the graph.
+    pub fn files(&self) -> impl Iterator<Item = &String> + '_ {
+        self.edges.keys()
+    }
+
+    /// Returns the files imported by `file_path`.
+    #[must_use = "Use this method to get the files imported by a file"]
+    pub fn dependencies_of(&self, file_path: &str) -> Vec<&String> {
+        self.edges
+            .get(file_path)
+            .map(|deps| deps.iter().collect())
+            .unwrap_or_default()
+    }
+
+    /// Returns the import cycles of the graph.
+    ///
+    /// Every cycle is reported once as the sorted list of the files taking part in it (a strongly
+    /// connected component), including files importing themselves.
+    #[must_use = "Use this method to detect import cycles"]
+    pub fn cycles(&self) -> Vec<Vec<String>> {
+        let mut tarjan = Tarjan {
+            graph: self,
+            index: 0,
+            indices: HashMap::new(),
+            low_links: HashMap::new(),
+            stack: Vec::new(),
+            on_stack: BTreeSet::new(),
+            components: Vec::new(),
+        };
+        for file in self.edges.keys() {
+            if !tarjan.indices.contains_key(file.as_str()) {
+                tarjan.visit(file);
+            }
+        }
+        let mut cycles: Vec<Vec<String>> = tarjan
+            .components
+            .into_iter()
+            .filter(|component| {
+                component.len() > 1
+                    || self.edges[component[0].as_str()].contains(component[0].as_str())
+            })
+            .map(|mut component| {
+                component.sort();
+                component
+            })
+            .collect();
+        cycles.sort();
+        cycles
+    }
+
+    /// Checks if the graph contains at least one import cycle.
+    #[must_use = "Use this method to check for import cycles"]
+    pub fn has_cycles(&self) -> bool {
+        !self.cycles().is_empty()
+    }
+}
+
+struct Tarjan<'a> {
+    graph: &'a DependencyGraph,
+    index: usize,
+    indices: HashMap<&'a str, usize>,
+    low_links: HashMap<&'a str, usize>,
+    stack: Vec<&'a str>,
+    on_stack: BTreeSet<&'a str>,
+    components: Vec<Vec<String>>,
+}
+
+impl<'a> Tarjan<'a> {
+    fn visit(&mut self, file: &'a str) {
+        self.indices.insert(file, self.index);
+        self.low_links.insert(file, self.index);
+        self.index += 1;
+        self.stack.push(file);
+        self.on_stack.insert(file);
+        for dependency in &self.graph.edges[file] {
+            let dependency = dependency.as_str();
+            if !self.indices.contains_key(dependency) {
+                self.visit(dependency);
+                let low = self.low_links[file].min(self.low_links[dependency]);
+                self.low_links.insert(file, low);
+            } else if self.on_stack.contains(dependency) {
+                let low = self.low_links[file].min(self.indices[dependency]);
+                self.low_links.insert(file, low);
+            }
+        }
+        if self.low_links[file] == self.indices[file] {
+            let mut component = Vec::new();
+            while let Some(member) = self.stack.pop() {
+                self.on_stack.remove(member);
+                component.push(member.to_string());
+                if member == file {
+                    break;
+                }
+            }
+            self.components.push(component);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cycles() {
+        let mut graph = DependencyGraph::default();
+        graph.add_dependency("a", "b");
+        graph.add_dependency("b", "c");
+        graph.add_dependency("c", "a");
+        graph.add_dependency("c", "d");
+        graph.add_dependency("e", "e");
+        graph.add_file("f");
+        assert_eq!(
+            graph.cycles(),
+            vec![
+                vec!["a".to_string(), "b".to_string(), "c".to_string()],
+                vec!["e".to_string()],
+            ]
+        );
+        assert_eq!(graph.dependencies_of("c"), vec!["a", "d"]);
+        assert!(graph.dependencies_of("f").is_empty());
+        assert_eq!(graph.files().count(), 6);
+    }
+
+    #[test]
+    fn test_acyclic_graph() {
+        let mut graph = DependencyGraph::default();
+        graph.add_dependency("a", "b");
+        graph.add_dependency("a", "c");
+        graph.add_dependency("b", "c");
+        assert!(!graph.has_cycles());
+    }
+}
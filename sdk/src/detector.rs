@@ -3,16 +3,21 @@
 //!
 //! # Public members
 //!
-//! - `detector!` macro for defining a detector. It automatically creates the structure for the provided `type_name` in the arrtibute and implements `Detector` trait. It can be applied to a single function with `type_name` attribute and follows `check` function signature.
+//! - `detector!` macro for defining a detector. It automatically creates the structure for the provided `type_name` in the arrtibute and implements `Detector` trait. Report metadata (`id`, `severity`, `tags`, ...) can be declared with attributes to also implement `DetectorReportTemplate`. It can be applied to a single function with `type_name` attribute and follows `check` function signature.
 //! - `detectors!` macro for defining multiple detectors at once. It can be applied to a list of functions with `type_name` attribute and follows `check` function signature.
-//! - `Detector` trait for implementing a detector. It has a single method `check` that takes a `Codebase` and returns an optional vector of `DetectorResult`.
-//! - `DetectorResult` struct for representing the result of a detector. It contains the file path, start and end offsets, and an optional map of extra information. Extra information is used to store a map of symbol replacements in the detector template. \
+//! - `Detector` trait for implementing a detector. It has a method `check` that takes a `Codebase` and returns an optional vector of `DetectorResult`, and a `dedup_key` method that defines which results are duplicates.
+//! - `DetectorResult` struct for representing the result of a detector. It contains the file path, start and end offsets, and an optional map of extra information. Results can be compared and hashed, e.g. to deduplicate them in a `HashSet`. Extra information is used to store a map of symbol replacements in the detector template. \
 //!   For example, if the detector template contains a symbol `$NAME`, the extra information can be used to replace it with the actual name.
 //! - `DetectorReportTemplate` trait for implementing a detector report template. It has methods for generating the report title, body, and closing.
+//! - `Severity` enum for the typed severity of a detector, parsed from the report template severity.
 //! - `CombinedDetector` a union trait to force the implementor to implement both `Detector` and `DetectorReportTemplate` traits.
 //! - `CompactDetector` a boxed version of `CombinedDetector`.
 //! - `DetectorOpaque` a struct that is used to wrap a raw pointer to a detector. It is used to operate with detectors using C API.
-use std::{collections::HashMap, fmt::Display};
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    hash::{Hash, Hasher},
+};
 
 use crate::codebase::{Codebase, SealedState};
 
@@ -21,11 +26,137 @@ use crate::codebase::{Codebase, SealedState};
 /// The function signature must follow the `check` function signature from the `Detector` trait.
 /// It automatically creates the structure for the provided `type_name` in the attribute and implements the `Detector` trait.
 /// The `DetectorReportTemplate` trait should be implemented to satisfy the `ComdinedDetector` contract.
+///
+/// Alternatively, the report metadata can be declared inline with attributes next to `type_name`,
+/// in any order: `#[id = "..."]` (required), `#[uid = "..."]`, `#[description = "..."]`,
+/// `#[severity = "..."]`, `#[tags("...", ...)]`, `#[category = "..."]` and
+/// `#[enabled_by_default = ...]`. The macro then also implements `DetectorReportTemplate`, with
+/// `uid` defaulting to the id, `severity` to `medium`, `category` to `general`,
+/// `enabled_by_default` to `true`, the description and tags to empty values, and the report texts
+/// and template to empty strings. Other attributes are ignored.
 #[macro_export]
 macro_rules! detector {
     (
-        #[type_name = $tname:ident]
-        $(#[$attr:meta])*
+        @parse [$($tname:ident)?] [$($id:literal)?] [$($uid:literal)?] [$($description:literal)?]
+        [$($severity:literal)?] [$($tag:literal),*] [$($category:literal)?]
+        [$($enabled:literal)?]
+        #[type_name = $new:ident]
+        $($rest:tt)*
+    ) => {
+        $crate::detector! {
+            @parse [$new] [$($id)?] [$($uid)?] [$($description)?] [$($severity)?] [$($tag),*]
+            [$($category)?] [$($enabled)?]
+            $($rest)*
+        }
+    };
+    (
+        @parse [$($tname:ident)?] [$($id:literal)?] [$($uid:literal)?] [$($description:literal)?]
+        [$($severity:literal)?] [$($tag:literal),*] [$($category:literal)?]
+        [$($enabled:literal)?]
+        #[id = $new:literal]
+        $($rest:tt)*
+    ) => {
+        $crate::detector! {
+            @parse [$($tname)?] [$new] [$($uid)?] [$($description)?] [$($severity)?] [$($tag),*]
+            [$($category)?] [$($enabled)?]
+            $($rest)*
+        }
+    };
+    (
+        @parse [$($tname:ident)?] [$($id:literal)?] [$($uid:literal)?] [$($description:literal)?]
+        [$($severity:literal)?] [$($tag:literal),*] [$($category:literal)?]
+        [$($enabled:literal)?]
+        #[uid = $new:literal]
+        $($rest:tt)*
+    ) => {
+        $crate::detector! {
+            @parse [$($tname)?] [$($id)?] [$new] [$($description)?] [$($severity)?] [$($tag),*]
+            [$($category)?] [$($enabled)?]
+            $($rest)*
+        }
+    };
+    (
+        @parse [$($tname:ident)?] [$($id:literal)?] [$($uid:literal)?] [$($description:literal)?]
+        [$($severity:literal)?] [$($tag:literal),*] [$($category:literal)?]
+        [$($enabled:literal)?]
+        #[description = $new:literal]
+        $($rest:tt)*
+    ) => {
+        $crate::detector! {
+            @parse [$($tname)?] [$($id)?] [$($uid)?] [$new] [$($severity)?] [$($tag),*]
+            [$($category)?] [$($enabled)?]
+            $($rest)*
+        }
+    };
+    (
+        @parse [$($tname:ident)?] [$($id:literal)?] [$($uid:literal)?] [$($description:literal)?]
+        [$($severity:literal)?] [$($tag:literal),*] [$($category:literal)?]
+        [$($enabled:literal)?]
+        #[severity = $new:literal]
+        $($rest:tt)*
+    ) => {
+        $crate::detector! {
+            @parse [$($tname)?] [$($id)?] [$($uid)?] [$($description)?] [$new] [$($tag),*]
+            [$($category)?] [$($enabled)?]
+            $($rest)*
+        }
+    };
+    (
+        @parse [$($tname:ident)?] [$($id:literal)?] [$($uid:literal)?] [$($description:literal)?]
+        [$($severity:literal)?] [$($tag:literal),*] [$($category:literal)?]
+        [$($enabled:literal)?]
+        #[tags($($new:literal),* $(,)?)]
+        $($rest:tt)*
+    ) => {
+        $crate::detector! {
+            @parse [$($tname)?] [$($id)?] [$($uid)?] [$($description)?] [$($severity)?] [$($new),*]
+            [$($category)?] [$($enabled)?]
+            $($rest)*
+        }
+    };
+    (
+        @parse [$($tname:ident)?] [$($id:literal)?] [$($uid:literal)?] [$($description:literal)?]
+        [$($severity:literal)?] [$($tag:literal),*] [$($category:literal)?]
+        [$($enabled:literal)?]
+        #[category = $new:literal]
+        $($rest:tt)*
+    ) => {
+        $crate::detector! {
+            @parse [$($tname)?] [$($id)?] [$($uid)?] [$($description)?] [$($severity)?] [$($tag),*]
+            [$new] [$($enabled)?]
+            $($rest)*
+        }
+    };
+    (
+        @parse [$($tname:ident)?] [$($id:literal)?] [$($uid:literal)?] [$($description:literal)?]
+        [$($severity:literal)?] [$($tag:literal),*] [$($category:literal)?]
+        [$($enabled:literal)?]
+        #[enabled_by_default = $new:literal]
+        $($rest:tt)*
+    ) => {
+        $crate::detector! {
+            @parse [$($tname)?] [$($id)?] [$($uid)?] [$($description)?] [$($severity)?] [$($tag),*]
+            [$($category)?] [$new]
+            $($rest)*
+        }
+    };
+    (
+        @parse [$($tname:ident)?] [$($id:literal)?] [$($uid:literal)?] [$($description:literal)?]
+        [$($severity:literal)?] [$($tag:literal),*] [$($category:literal)?]
+        [$($enabled:literal)?]
+        #[$attr:meta]
+        $($rest:tt)*
+    ) => {
+        $crate::detector! {
+            @parse [$($tname)?] [$($id)?] [$($uid)?] [$($description)?] [$($severity)?] [$($tag),*]
+            [$($category)?] [$($enabled)?]
+            $($rest)*
+        }
+    };
+    (
+        @parse [$tname:ident] [$($id:literal)?] [$($uid:literal)?] [$($description:literal)?]
+        [$($severity:literal)?] [$($tag:literal),*] [$($category:literal)?]
+        [$($enabled:literal)?]
         $vis:vis fn $name:ident $(< $($gen:tt)* >)? ( $($params:tt)* )
         $(-> $ret:ty)?
         $(where $($where:tt)*)?
@@ -42,6 +173,84 @@ macro_rules! detector {
                 $body
             }
         }
+
+        $crate::detector! {
+            @template $tname [$($id)?] [$($uid)?] [$($description)?] [$($severity)?] [$($tag),*]
+            [$($category)?] [$($enabled)?]
+        }
+    };
+    (@template $tname:ident [] $($rest:tt)*) => {};
+    (
+        @template $tname:ident [$id:literal] [$($uid:literal)?] [$($description:literal)?]
+        [$($severity:literal)?] [$($tag:literal),*] [$($category:literal)?]
+        [$($enabled:literal)?]
+    ) => {
+        impl $crate::detector::DetectorReportTemplate for $tname {
+            fn id(&self) -> String {
+                String::from($id)
+            }
+            fn uid(&self) -> String {
+                $crate::detector!(@first $($uid,)? $id)
+            }
+            fn description(&self) -> String {
+                $crate::detector!(@first $($description,)? "")
+            }
+            fn severity(&self) -> String {
+                $crate::detector!(@first $($severity,)? "medium")
+            }
+            fn tags(&self) -> Vec<String> {
+                vec![$(String::from($tag)),*]
+            }
+            fn category(&self) -> String {
+                $crate::detector!(@first $($category,)? "general")
+            }
+            fn enabled_by_default(&self) -> bool {
+                $crate::detector!(@first_literal $($enabled,)? true)
+            }
+            fn title_single_instance(&self) -> String {
+                String::new()
+            }
+            fn title_multiple_instance(&self) -> String {
+                String::new()
+            }
+            fn opening(&self) -> String {
+                String::new()
+            }
+            fn body_single_file_single_instance(&self) -> String {
+                String::new()
+            }
+            fn body_single_file_multiple_instance(&self) -> String {
+                String::new()
+            }
+            fn body_multiple_file_multiple_instance(&self) -> String {
+                String::new()
+            }
+            fn body_list_item_single_file(&self) -> String {
+                String::new()
+            }
+            fn body_list_item_multiple_file(&self) -> String {
+                String::new()
+            }
+            fn closing(&self) -> String {
+                String::new()
+            }
+            fn template(&self) -> String {
+                String::new()
+            }
+        }
+    };
+    (@first $first:literal $(, $rest:literal)*) => {
+        String::from($first)
+    };
+    (@first_literal $first:literal $(, $rest:literal)*) => {
+        $first
+    };
+    (#[$($attr:tt)*] $($rest:tt)*) => {
+        $crate::detector! {
+            @parse [] [] [] [] [] [] [] []
+            #[$($attr)*]
+            $($rest)*
+        }
     };
     () => {};
 }
@@ -53,8 +262,7 @@ macro_rules! detector {
 macro_rules! detectors {
     (
         $(
-            #[type_name = $tname:ident]
-            $(#[$attr:meta])*
+            $(#[$($attr:tt)*])+
             $vis:vis fn $name:ident $(< $($gen:tt)* >)? ( $($params:tt)* )
             $(-> $ret:ty)?
             $(where $($where:tt)*)?
@@ -62,9 +270,8 @@ macro_rules! detectors {
         )*
     ) => {
         $(
-            detector! {
-                #[type_name = $tname]
-                $(#[$attr])*
+            $crate::detector! {
+                $(#[$($attr)*])+
                 $vis fn $name $(< $($gen)* >)? ( $($params)* )
                 $(-> $ret)?
                 $(where $($where)*)?
@@ -101,7 +308,11 @@ pub type CompactDetector = Box<dyn CombinedDetector>;
 /// - `offset_start`: The start offset of the issue in the file.
 /// - `offset_end`: The end offset of the issue in the file.
 /// - `extra`: An optional map of extra information. This can be used to store symbol replacements for the report template substitution.
-#[derive(Debug, Clone)]
+///
+/// Two results are equal if they have the same location (`file_path`, `offset_start`, `offset_end`)
+/// and the same `extra` entries. The report message and severity come from the detector template
+/// and are not part of the result identity.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct DetectorResult {
     pub file_path: String,
     pub offset_start: u32,
@@ -109,13 +320,38 @@ pub struct DetectorResult {
     pub extra: Option<HashMap<String, String>>,
 }
 
+impl Hash for DetectorResult {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.file_path.hash(state);
+        self.offset_start.hash(state);
+        self.offset_end.hash(state);
+        self.extra
+            .as_ref()
+            .map(|extra| {
+                let mut entries: Vec<_> = extra.iter().collect();
+                entries.sort();
+                entries
+            })
+            .hash(state);
+    }
+}
+
 /// `Detector` trait
 /// The base `Detector` functional interface.
 ///
 /// # Functions
 /// - `check`: The main function that takes a `Codebase` and returns an optional vector of `DetectorResult`.
+/// - `dedup_key`: Returns the key used to deduplicate the detector results. Results with equal keys are reported once.
+///   Defaults to the result span (file path and offsets).
 pub trait Detector {
     fn check(&self, codebase: &Codebase<SealedState>) -> Option<Vec<DetectorResult>>;
+
+    fn dedup_key(&self, result: &DetectorResult) -> String {
+        format!(
+            "{}:{}:{}",
+            result.file_path, result.offset_start, result.offset_end
+        )
+    }
 }
 
 /// `DetectorReportTemplate` trait
@@ -128,6 +364,9 @@ pub trait Detector {
 /// - `description`: Returns a description of the detector.
 /// - `severity`: Returns the severity of the issue found by a detector.
 /// - `tags`: Returns a list of tags associated with the detector. Compact detectorrs always have the `compact` tag.
+/// - `category`: Returns the category of the detector, e.g. `access-control` or `arithmetic`. Defaults to `general`.
+/// - `enabled_by_default`: Returns whether the detector runs when no detectors are selected. Opt-in detectors return
+///   `false`. Defaults to `true`.
 /// - `title_single_instance`: Returns the title for a single instance of the issue.
 /// - `title_multiple_instance`: Returns the title for multiple instances of the issue.
 /// - `opening`: Returns the opening message for the report.
@@ -145,6 +384,8 @@ pub trait Detector {
 ///  id: assertion-error-message-verbose
 ///  uid: 3HgyHb
 ///  description: Detects assert statements that expose overly verbose or technical error messages directly to users, which can leak sensitive implementation details or confuse end users. Ensuring concise, user-friendly error messages helps maintain security and usability.
+///  category: error-handling
+///  enabled-by-default: true
 ///  report:
 ///    severity: low
 ///    tags:
@@ -168,6 +409,12 @@ pub trait DetectorReportTemplate {
     fn description(&self) -> String;
     fn severity(&self) -> String;
     fn tags(&self) -> Vec<String>;
+    fn category(&self) -> String {
+        String::from("general")
+    }
+    fn enabled_by_default(&self) -> bool {
+        true
+    }
     fn title_single_instance(&self) -> String;
     fn title_multiple_instance(&self) -> String;
     fn opening(&self) -> String;
@@ -180,6 +427,54 @@ pub trait DetectorReportTemplate {
     fn template(&self) -> String;
 }
 
+/// Typed severity of a detector, parsed from `DetectorReportTemplate::severity`.
+///
+/// Severities are ordered from `Informational` to `Critical`. Unknown severities are parsed as
+/// `Informational`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Informational,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    /// All severities, from the most to the least severe.
+    pub const ALL: [Severity; 5] = [
+        Severity::Critical,
+        Severity::High,
+        Severity::Medium,
+        Severity::Low,
+        Severity::Informational,
+    ];
+}
+
+impl From<&str> for Severity {
+    fn from(severity: &str) -> Self {
+        match severity.to_ascii_lowercase().as_str() {
+            "critical" => Severity::Critical,
+            "high" => Severity::High,
+            "medium" => Severity::Medium,
+            "low" => Severity::Low,
+            _ => Severity::Informational,
+        }
+    }
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Critical => write!(f, "critical"),
+            Severity::High => write!(f, "high"),
+            Severity::Medium => write!(f, "medium"),
+            Severity::Low => write!(f, "low"),
+            Severity::Informational => write!(f, "informational"),
+        }
+    }
+}
+
 impl Display for dyn CombinedDetector {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.id())
@@ -254,5 +549,97 @@ mod tests {
         let det: CompactDetector = Box::new(Dummy);
         // Display should use id()
         assert_eq!(det.to_string(), "dummy");
+        assert_eq!(det.category(), "general");
+        assert!(det.enabled_by_default());
+    }
+    #[test]
+    fn test_detector_result_hash_set_dedup() {
+        let result = |extra: &[(&str, &str)]| DetectorResult {
+            file_path: "f".into(),
+            offset_start: 0,
+            offset_end: 1,
+            extra: Some(
+                extra
+                    .iter()
+                    .map(|(k, v)| ((*k).to_string(), (*v).to_string()))
+                    .collect(),
+            ),
+        };
+        let mut results = std::collections::HashSet::new();
+        results.insert(result(&[("NAME", "admin"), ("CIRCUIT", "set_admin")]));
+        results.insert(result(&[("CIRCUIT", "set_admin"), ("NAME", "admin")]));
+        assert_eq!(results.len(), 1);
+        results.insert(result(&[("NAME", "owner")]));
+        assert_eq!(results.len(), 2);
+    }
+    #[allow(unused_imports)]
+    mod attributed {
+        use crate::codebase::{Codebase, SealedState};
+        use crate::detector::DetectorResult;
+
+        crate::detector! {
+            #[type_name = AttributedDetector]
+            #[id = "attributed-detector"]
+            #[severity = "high"]
+            #[tags("audit", "compact")]
+            #[category = "access-control"]
+            #[enabled_by_default = false]
+            fn attributed_detector(
+                _codebase: &Codebase<SealedState>,
+            ) -> Option<Vec<DetectorResult>> {
+                None
+            }
+        }
+    }
+
+    #[allow(unused_imports)]
+    mod reordered {
+        use crate::codebase::{Codebase, SealedState};
+        use crate::detector::DetectorResult;
+
+        crate::detector! {
+            #[severity = "low"]
+            #[id = "reordered-detector"]
+            #[type_name = ReorderedDetector]
+            #[uid = "rE0rd1"]
+            fn reordered_detector(
+                _codebase: &Codebase<SealedState>,
+            ) -> Option<Vec<DetectorResult>> {
+                None
+            }
+        }
+    }
+
+    #[test]
+    fn test_detector_macro_attribute_order() {
+        let detector: CompactDetector = Box::new(reordered::ReorderedDetector);
+        assert_eq!(detector.id(), "reordered-detector");
+        assert_eq!(detector.uid(), "rE0rd1");
+        assert_eq!(detector.severity(), "low");
+        assert!(detector.tags().is_empty());
+        assert_eq!(detector.category(), "general");
+        assert!(detector.enabled_by_default());
+    }
+
+    #[test]
+    fn test_detector_macro_report_attributes() {
+        let detector: CompactDetector = Box::new(attributed::AttributedDetector);
+        assert_eq!(detector.id(), "attributed-detector");
+        assert_eq!(detector.uid(), "attributed-detector");
+        assert_eq!(detector.severity(), "high");
+        assert_eq!(detector.tags(), vec!["audit", "compact"]);
+        assert_eq!(detector.category(), "access-control");
+        assert!(!detector.enabled_by_default());
+        assert!(detector.description().is_empty());
+        assert_eq!(Severity::from(detector.severity().as_str()), Severity::High);
+    }
+
+    #[test]
+    fn test_severity_from_str() {
+        assert_eq!(Severity::from("Critical"), Severity::Critical);
+        assert_eq!(Severity::from("low"), Severity::Low);
+        assert_eq!(Severity::from("unknown"), Severity::Informational);
+        assert!(Severity::High > Severity::Medium);
+        assert_eq!(Severity::Medium.to_string(), "medium");
     }
 }
@@ -0,0 +1,198 @@
+//! # Source diagnostics
+//!
+//! Checks running on the raw source code, for mistakes the Compact grammar rejects and that
+//! therefore never reach the AST.
+//!
+//! - `assignments_in_conditions` detects `=` used where `==` was likely intended, in `if` and
+//!   `assert` conditions. Assignments are statements in Compact, so such code does not parse.
+use serde::{Deserialize, Serialize};
+
+use crate::ast::node::Location;
+
+/// A diagnostic reported on the source code.
+///
+/// # Fields
+///
+/// - `message`: A human readable description of the issue.
+/// - `location`: The location of the offending source code.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub message: String,
+    pub location: Location,
+}
+
+/// Returns a diagnostic for every `=` used as an `if` or `assert` condition operator.
+#[must_use = "Use this function to detect assignments used as conditions"]
+pub fn assignments_in_conditions(source: &str) -> Vec<Diagnostic> {
+    let bytes = source.as_bytes();
+    let mut diagnostics = Vec::new();
+    let mut index = 0;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'"' => index = skip_string(bytes, index),
+            b'/' if bytes.get(index + 1) == Some(&b'/') => {
+                index = source[index..]
+                    .find('\n')
+                    .map_or(bytes.len(), |end| index + end);
+            }
+            b'/' if bytes.get(index + 1) == Some(&b'*') => {
+                index = source[index + 2..]
+                    .find("*/")
+                    .map_or(bytes.len(), |end| index + end + 4);
+            }
+            _ if is_keyword_at(source, index, "if") => {
+                let start = index + 2;
+                let Some(open) = source[start..]
+                    .find(|c: char| !c.is_whitespace())
+                    .map(|offset| start + offset)
+                    .filter(|open| bytes[*open] == b'(')
+                else {
+                    index = start;
+                    continue;
+                };
+                let end = condition_end(bytes, open + 1, |depth, byte| depth == 0 && byte == b')');
+                check_condition(source, open + 1, end, &mut diagnostics);
+                index = end;
+            }
+            _ if is_keyword_at(source, index, "assert") => {
+                let start = index + 6;
+                let end = condition_end(bytes, start, |depth, byte| {
+                    depth == 0 && (byte == b'"' || byte == b';')
+                });
+                check_condition(source, start, end, &mut diagnostics);
+                index = end;
+            }
+            _ => index += 1,
+        }
+    }
+    diagnostics
+}
+
+fn is_keyword_at(source: &str, index: usize, keyword: &str) -> bool {
+    let is_ident = |byte: u8| byte.is_ascii_alphanumeric() || byte == b'_';
+    let bytes = source.as_bytes();
+    // Compare bytes, as `index` may fall inside a multi-byte character.
+    bytes[index..].starts_with(keyword.as_bytes())
+        && (index == 0 || !is_ident(bytes[index - 1]))
+        && bytes
+            .get(index + keyword.len())
+            .is_none_or(|byte| !is_ident(*byte))
+}
+
+fn skip_string(bytes: &[u8], start: usize) -> usize {
+    let mut index = start + 1;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'\\' => index += 2,
+            b'"' => return index + 1,
+            _ => index += 1,
+        }
+    }
+    bytes.len()
+}
+
+/// Returns the index where the condition starting at `start` ends, tracking nested brackets.
+fn condition_end(bytes: &[u8], start: usize, is_end: impl Fn(usize, u8) -> bool) -> usize {
+    let mut depth = 0usize;
+    let mut index = start;
+    while index < bytes.len() {
+        let byte = bytes[index];
+        if is_end(depth, byte) {
+            return index;
+        }
+        match byte {
+            b'(' | b'[' | b'{' => depth += 1,
+            b')' | b']' | b'}' => depth = depth.saturating_sub(1),
+            b'"' => {
+                index = skip_string(bytes, index);
+                continue;
+            }
+            _ => {}
+        }
+        index += 1;
+    }
+    bytes.len()
+}
+
+fn check_condition(source: &str, start: usize, end: usize, diagnostics: &mut Vec<Diagnostic>) {
+    let bytes = source.as_bytes();
+    for index in start..end {
+        if bytes[index] != b'=' {
+            continue;
+        }
+        let previous = bytes[index - 1];
+        let next = bytes.get(index + 1).copied().unwrap_or_default();
+        if matches!(previous, b'=' | b'!' | b'<' | b'>') || matches!(next, b'=' | b'>') {
+            continue;
+        }
+        let condition = source[start..end].trim();
+        diagnostics.push(Diagnostic {
+            message: format!("Assignment used as a condition in `{condition}`, did you mean `==`?"),
+            location: location_at(source, index, index + 1),
+        });
+    }
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn location_at(source: &str, start: usize, end: usize) -> Location {
+    let position = |offset: usize| {
+        let before = &source[..offset];
+        let line = before.matches('\n').count() + 1;
+        let column = offset - before.rfind('\n').map_or(0, |index| index + 1) + 1;
+        (line as u32, column as u32)
+    };
+    let (start_line, start_column) = position(start);
+    let (end_line, end_column) = position(end);
+    Location::new(
+        start as u32,
+        end as u32,
+        start_line,
+        start_column,
+        end_line,
+        end_column,
+        source[start..end].to_string(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_assignment_in_if_condition() {
+        let source = "circuit foo(a: Field, b: Field): Field {\n    if (a = b) {\n        return a;\n    }\n    return b;\n}";
+        let diagnostics = assignments_in_conditions(source);
+        assert_eq!(diagnostics.len(), 1);
+        let location = &diagnostics[0].location;
+        assert_eq!((location.start_line, location.start_column), (2, 11));
+        assert_eq!(location.source, "=");
+        assert!(diagnostics[0].message.contains("`a = b`"));
+    }
+
+    #[test]
+    fn test_assignment_in_assert_condition() {
+        let source = "circuit foo(a: Field): [] {\n    assert a = 1 \"a must be 1\";\n}";
+        let diagnostics = assignments_in_conditions(source);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].location.start_line, 2);
+    }
+
+    #[test]
+    fn test_comparisons_and_statements_are_not_reported() {
+        let source = "circuit foo(a: Field, b: Field): Field {\n    // if (a = b)\n    if (a == b) {\n        a = b;\n    }\n    if (a <= b) { }\n    assert a != b \"a = b\";\n    return a;\n}";
+        assert!(assignments_in_conditions(source).is_empty());
+    }
+
+    #[test]
+    fn test_non_ascii_source() {
+        let source = "// café
+circuit foo(a: Field): [] {
+    assert a = 1 \"é\";
+    if (a = 2) { }
+}";
+        let diagnostics = assignments_in_conditions(source);
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].location.start_line, 3);
+        assert_eq!(diagnostics[1].location.start_line, 4);
+    }
+}
@@ -6,11 +6,19 @@
 //! ## Public members
 //!
 //! - `ast` module contains the abstract syntax tree (AST) representation of the codebase.
+//! - `dependency_graph` module contains the graph of imports between the files of a codebase.
+//! - `diagnostics` module contains checks on the raw source code for mistakes the grammar rejects.
 //! - `detector` module contrains Detector trait framework and macro for implementing detectors.
 //! - `codebase` module contains the Codebase struct and its methods for managing the codebase.
+//! - `lsp` module contains the conversion of detector results into LSP diagnostics.
+//! - `passes` module contains the `AnalysisPass` trait for running custom analyses when the codebase is sealed.
+//! - `source_map` module contains the conversion of byte offsets into line and column numbers.
+//! - `visitor` module contains the `Visitor` trait for walking the ASTs of a codebase in a single pass.
 //!
 //! The function `build_codebase` is the main entry point for building a codebase from source files.
-//! It takes a map of file paths to source code strings and returns a `Result` containing a boxed `Codebase` in the `SealedState`.
+//! It takes a map of file paths to source code strings and returns a `Result` containing a boxed `Codebase` in the `SealedState`
+//! built from the files that parse, together with a `ParseDiagnostic` for every file that does not.
+//! The function `build_codebase_with_prelude` additionally loads prelude files, which are used for resolution but not reported.
 //!
 //! ## Example
 //! ```
@@ -22,14 +30,14 @@
 //!     // Use DSL grammar for for-loop
 //!     let src = r"circuit foo(x: Uint<8>) : Uint<8> { for (const i of 0 .. 1) { } return x; }";
 //!     files.insert("t.compact".to_string(), src.to_string());
-//!     let cb = build_codebase(&files).unwrap();
+//!     let (cb, _diagnostics) = build_codebase(&files).unwrap();
 //!     // Only test for-loop detection; assert statements may vary by grammar
 //!     let fors: Vec<_> = cb.list_for_statement_nodes().collect();
 //!     assert_eq!(fors.len(), 1);
 //! }
 //! ```
 use anyhow::Result;
-use codebase::{Codebase, SealedState};
+use codebase::{Codebase, ParseDiagnostic, SealedState};
 use std::collections::HashMap;
 
 mod builder_tests;
@@ -39,33 +47,83 @@ pub use ast::*;
 
 pub mod codebase;
 
+pub mod dependency_graph;
+
+pub mod diagnostics;
+
 pub mod detector;
 pub use detector::*;
 
+pub mod lsp;
+
+pub mod passes;
+
+pub mod source_map;
+
+pub mod visitor;
+
 mod storage;
 mod symbol_table;
 
 /// Builds a codebase from the provided source files.
 ///
+/// Every file is parsed independently: files that cannot be parsed are left out of the codebase
+/// and reported by a `ParseDiagnostic`, so one invalid file does not prevent analyzing the others.
+///
 /// # Arguments
 ///
 /// * `files` - A map where the keys are file paths (absolute) and the values are the corresponding source code strings.
 ///
 /// # Errors
 ///
-/// This function will return an error if the source code cannot be parsed.
+/// This function will return an error if the symbol tables cannot be built.
 ///
 /// # Panics
 ///
 /// This function will panic if there is an error loading the Inference grammar.
 pub fn build_codebase<H: std::hash::BuildHasher>(
     files: &HashMap<String, String, H>,
-) -> Result<Box<Codebase<SealedState>>> {
+) -> Result<(Box<Codebase<SealedState>>, Vec<ParseDiagnostic>)> {
+    let mut codebase = Codebase::new();
+    for (file_path, source_code) in files {
+        codebase.add_file(file_path, source_code);
+    }
+    let (codebase, diagnostics) = codebase.seal_with_diagnostics()?;
+    Ok((Box::new(codebase), diagnostics))
+}
+
+/// Builds a codebase from the provided source files and prelude files.
+///
+/// Circuits of the prelude files can be called from every source file without an import, and
+/// results reported in prelude files are dropped by `Codebase::reportable_results`. As in
+/// `build_codebase`, source and prelude files that cannot be parsed are left out of the codebase
+/// and reported by a `ParseDiagnostic`.
+///
+/// # Arguments
+///
+/// * `files` - A map where the keys are file paths (absolute) and the values are the corresponding source code strings.
+/// * `prelude_files` - A map of the prelude files, in the same format as `files`.
+///
+/// # Errors
+///
+/// This function will return an error if the symbol tables cannot be built.
+///
+/// # Panics
+///
+/// This function will panic if there is an error loading the Inference grammar.
+pub fn build_codebase_with_prelude<H: std::hash::BuildHasher>(
+    files: &HashMap<String, String, H>,
+    prelude_files: &HashMap<String, String, H>,
+) -> Result<(Box<Codebase<SealedState>>, Vec<ParseDiagnostic>)> {
     let mut codebase = Codebase::new();
+    for (file_path, source_code) in prelude_files {
+        codebase.add_prelude_file(file_path, source_code);
+    }
     for (file_path, source_code) in files {
         codebase.add_file(file_path, source_code);
     }
-    Ok(Box::new(codebase.seal()?))
+    let (codebase, diagnostics) = codebase.seal_with_diagnostics()?;
+    Ok((Box::new(codebase), diagnostics))
 }
 
 #[cfg(test)]
@@ -77,7 +135,7 @@ mod tests {
         node::Location,
         node_type::NodeType,
         statement::Statement,
-        ty::{Type, TypeBool, TypeNat, TypeString, Vector, VectorSize},
+        ty::{Type, TypeBool, TypeNat, TypeString, Uint, Vector, VectorSize},
     };
 
     use super::*;
@@ -99,7 +157,8 @@ mod tests {
             let content = std::fs::read_to_string(path).unwrap();
             files.insert(file_name, content);
         }
-        let _ = build_codebase(&files).unwrap();
+        let (_, diagnostics) = build_codebase(&files).unwrap();
+        assert!(diagnostics.is_empty(), "{diagnostics:?}");
     }
 
     #[test]
@@ -107,7 +166,7 @@ mod tests {
         let mut files = HashMap::new();
         let src = "circuit foo() : Uint<8> { return 0; }";
         files.insert("a.compact".to_string(), src.to_string());
-        let cb = build_codebase(&files).expect("build_codebase failed");
+        let (cb, _) = build_codebase(&files).expect("build_codebase failed");
         assert_eq!(cb.files.len(), 1);
         assert_eq!(cb.symbol_tables.len(), 1);
         let scf = cb
@@ -122,13 +181,75 @@ mod tests {
         assert_eq!(circuits[0].name(), "foo");
     }
 
+    #[test]
+    fn test_build_codebase_with_prelude() {
+        let mut files = HashMap::new();
+        files.insert(
+            "main.compact".to_string(),
+            "circuit foo(x: Field) : Field { return helper(x); }".to_string(),
+        );
+        let mut prelude_files = HashMap::new();
+        prelude_files.insert(
+            "prelude.compact".to_string(),
+            "circuit helper(x: Field) : Field { return x; }".to_string(),
+        );
+        let (cb, diagnostics) = build_codebase_with_prelude(&files, &prelude_files).unwrap();
+        assert!(diagnostics.is_empty());
+        assert!(cb.is_prelude_file("prelude.compact"));
+        assert!(!cb.is_prelude_file("main.compact"));
+        let resolved: Vec<_> = cb
+            .storage
+            .nodes
+            .iter()
+            .filter_map(|node| match node {
+                NodeType::Expression(Expression::FunctionCall(call)) => {
+                    call.reference.as_ref().map(|circuit| circuit.name())
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(resolved, vec!["helper"]);
+        let results: Vec<_> = cb
+            .files
+            .iter()
+            .flat_map(|file| file.ast.circuits())
+            .map(|circuit| DetectorResult {
+                file_path: cb.find_node_file(circuit.id).unwrap().file_path,
+                offset_start: circuit.location.offset_start,
+                offset_end: circuit.location.offset_end,
+                extra: None,
+            })
+            .collect();
+        assert_eq!(results.len(), 2);
+        let reportable = cb.reportable_results(results);
+        assert_eq!(reportable.len(), 1);
+        assert_eq!(reportable[0].file_path, "main.compact");
+    }
+
+    #[test]
+    fn test_build_codebase_with_unparseable_prelude() {
+        let files = HashMap::from([(
+            "main.compact".to_string(),
+            "circuit foo() : Uint<8> { return 1; }".to_string(),
+        )]);
+        let prelude_files = HashMap::from([(
+            "prelude.compact".to_string(),
+            "circuit helper(: Field {".to_string(),
+        )]);
+        let (cb, diagnostics) = build_codebase_with_prelude(&files, &prelude_files).unwrap();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].file, "prelude.compact");
+        assert!(!cb.is_prelude_file("prelude.compact"));
+        assert_eq!(cb.files().count(), 1);
+    }
+
     /// Test files iterator and parent container resolution
     #[test]
     fn test_files_and_parent_container() {
         let mut files = HashMap::new();
         let src = "circuit foo() : Uint<8> { return 1; }";
         files.insert("a.compact".to_string(), src.to_string());
-        let cb = build_codebase(&files).unwrap();
+        let (cb, _) = build_codebase(&files).unwrap();
         let fs: Vec<_> = cb.files().collect();
         assert_eq!(fs.len(), 1);
         assert_eq!(fs[0].file_path, "a.compact");
@@ -194,12 +315,102 @@ mod tests {
         assert_eq!(vec.size_nat(), Some(5));
     }
 
+    #[test]
+    fn test_type_parse() {
+        let Type::Uint(uint) = Type::parse("Uint<0..255>").unwrap() else {
+            panic!("Expected Uint type");
+        };
+        assert_eq!(uint.start.value, 0);
+        assert_eq!(uint.end.as_ref().map(|end| end.value), Some(255));
+
+        let Type::Vector(vector) = Type::parse("Vector<2, Field>").unwrap() else {
+            panic!("Expected Vector type");
+        };
+        assert_eq!(vector.size_nat(), Some(2));
+        assert!(matches!(vector.ty, Type::Field(_)));
+
+        assert!(Type::parse("Uint<>").is_err());
+    }
+
+    #[test]
+    fn test_uint_range_matches() {
+        let uint = |start: u64, end: Option<u64>| {
+            let nat = |value| {
+                Rc::new(Nat {
+                    id: 0,
+                    location: Location::default(),
+                    value,
+                })
+            };
+            Type::Uint(Rc::new(Uint {
+                id: 0,
+                location: Location::default(),
+                start: nat(start),
+                end: end.map(nat),
+            }))
+        };
+        let Type::Uint(bits) = uint(8, None) else {
+            unreachable!()
+        };
+        assert_eq!(bits.range(), 0..=255);
+        let Type::Uint(wide) = uint(128, None) else {
+            unreachable!()
+        };
+        assert_eq!(wide.range(), 0..=u128::MAX);
+        // containment
+        assert!(uint(8, None).matches(&uint(0, Some(255))));
+        assert!(uint(8, None).matches(&uint(16, None)));
+        assert!(uint(16, None).matches(&uint(10, Some(20))));
+        // overlap without containment
+        assert!(!uint(0, Some(100)).matches(&uint(50, Some(300))));
+        // disjoint
+        assert!(!uint(8, None).matches(&uint(256, Some(300))));
+
+        let Type::Uint(parsed) = Type::parse("Uint<10..20>").unwrap() else {
+            panic!("Expected Uint type");
+        };
+        assert_eq!(parsed.range(), 10..=20);
+        assert!(Type::parse("Uint<8>")
+            .unwrap()
+            .matches(&Type::parse("Uint<0..255>").unwrap()));
+    }
+
+    #[test]
+    fn test_type_map_matches() {
+        let map = Type::parse("Map<Bytes<32>, Map<Field, Uint<64>>>").unwrap();
+        assert_eq!(map.to_string(), "map");
+        assert!(map.matches(&Type::parse("Map<Bytes<16>, Map<Field, Uint<8>>>").unwrap()));
+        assert!(!map.matches(&Type::parse("Map<Bytes<32>, Map<Field, Boolean>>").unwrap()));
+        assert!(!map.matches(&Type::parse("Map<Field, Map<Field, Uint<64>>>").unwrap()));
+        assert!(!map.matches(&Type::parse("Map<Bytes<32>, Uint<64>>").unwrap()));
+        assert!(!map.matches(&Type::parse("Set<Bytes<32>>").unwrap()));
+        let same = Type::parse("Map<Bytes<32>, Map<Field, Uint<64>>>").unwrap();
+        assert!(map.structurally_eq(&same));
+        let resized = Type::parse("Map<Bytes<16>, Map<Field, Uint<64>>>").unwrap();
+        assert!(!map.structurally_eq(&resized));
+    }
+
+    #[test]
+    fn test_build_codebase_mixed_validity() {
+        let mut files = HashMap::new();
+        let valid = "circuit foo(x: Field): Field { return x; }";
+        let invalid = "circuit bar(: Field { return %%; }";
+        files.insert("valid.compact".to_string(), valid.to_string());
+        files.insert("invalid.compact".to_string(), invalid.to_string());
+        let (cb, diagnostics) = build_codebase(&files).unwrap();
+        let paths: Vec<String> = cb.files().map(|file| file.file_path).collect();
+        assert_eq!(paths, vec!["valid.compact"]);
+        assert!(cb.circuit_by_name_in_file("valid.compact", "foo").is_some());
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].file, "invalid.compact");
+    }
+
     #[test]
     fn test_get_symbol_type_by_id() {
         let mut files = HashMap::new();
         let src = "circuit foo(x: Uint<8>) : Uint<8> { return x; }";
         files.insert("a.compact".to_string(), src.to_string());
-        let cb = build_codebase(&files).unwrap();
+        let (cb, _) = build_codebase(&files).unwrap();
         // find identifier for 'x'
         let x_id = cb
             .storage
@@ -0,0 +1,240 @@
+//! # LSP diagnostics
+//!
+//! Conversion of detector results into Language Server Protocol diagnostics.
+//!
+//! Detector results carry byte offsets, while LSP positions are 0-based lines and characters
+//! counted in UTF-16 code units. `Codebase::to_lsp_diagnostics` looks up the line of each offset
+//! with the source map of the file the result belongs to, and counts the code units of that line.
+use serde::Serialize;
+
+use crate::{
+    codebase::{Codebase, SealedState},
+    detector::{DetectorReportTemplate, DetectorResult},
+    source_map::SourceMap,
+};
+
+/// A 0-based position in a document, the character is counted in UTF-16 code units.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct LspPosition {
+    pub line: u32,
+    pub character: u32,
+}
+
+/// A range in a document, the end position is exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct LspRange {
+    pub start: LspPosition,
+    pub end: LspPosition,
+}
+
+/// LSP `DiagnosticSeverity`, serialized as its numeric value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(into = "u8")]
+pub enum LspSeverity {
+    Error = 1,
+    Warning = 2,
+    Information = 3,
+    Hint = 4,
+}
+
+impl From<LspSeverity> for u8 {
+    fn from(severity: LspSeverity) -> Self {
+        severity as u8
+    }
+}
+
+impl LspSeverity {
+    /// Maps a detector severity (`critical`, `high`, `medium`, `low`, ...) to an LSP severity.
+    #[must_use]
+    pub fn from_detector_severity(severity: &str) -> Self {
+        match severity.to_ascii_lowercase().as_str() {
+            "critical" | "high" => LspSeverity::Error,
+            "medium" => LspSeverity::Warning,
+            "low" => LspSeverity::Information,
+            _ => LspSeverity::Hint,
+        }
+    }
+}
+
+/// A diagnostic in the LSP shape. `file_path` identifies the document the diagnostic belongs to
+/// and is not part of the serialized diagnostic.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LspDiagnostic {
+    #[serde(skip)]
+    pub file_path: String,
+    pub range: LspRange,
+    pub severity: LspSeverity,
+    pub code: String,
+    pub source: String,
+    pub message: String,
+}
+
+impl Codebase<SealedState> {
+    /// Converts the results of a detector into LSP diagnostics.
+    ///
+    /// The detector id is used as the diagnostic code, its severity is mapped to the LSP severity
+    /// and its title is used as the message. Results for files that are not part of the codebase
+    /// are skipped.
+    #[must_use = "Use this function to publish detector results through an LSP server"]
+    pub fn to_lsp_diagnostics(
+        &self,
+        detector: &dyn DetectorReportTemplate,
+        results: &[DetectorResult],
+    ) -> Vec<LspDiagnostic> {
+        let severity = LspSeverity::from_detector_severity(&detector.severity());
+        let title = detector.title_single_instance();
+        let message = if title.is_empty() {
+            detector.description()
+        } else {
+            title
+        };
+        results
+            .iter()
+            .filter_map(|result| {
+                let file = self
+                    .files
+                    .iter()
+                    .find(|file| file.file_path == result.file_path)?;
+                Some(LspDiagnostic {
+                    file_path: result.file_path.clone(),
+                    range: LspRange {
+                        start: lsp_position(&file.source, &file.source_map, result.offset_start),
+                        end: lsp_position(&file.source, &file.source_map, result.offset_end),
+                    },
+                    severity,
+                    code: detector.id(),
+                    source: String::from("compact-security-detectors"),
+                    message: message.clone(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Converts a byte offset of `source` into an LSP position. The line is looked up with the source
+/// map, so only the text of that line is scanned to count the UTF-16 code units.
+#[allow(clippy::cast_possible_truncation)]
+fn lsp_position(source: &str, source_map: &SourceMap, offset: u32) -> LspPosition {
+    let mut offset = (offset as usize).min(source.len());
+    while !source.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    let (line, line_start) = source_map.line_start(offset).unwrap_or((1, 0));
+    LspPosition {
+        line: (line - 1) as u32,
+        character: source[line_start..offset].encode_utf16().count() as u32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codebase::OpenState;
+
+    struct Dummy;
+
+    impl DetectorReportTemplate for Dummy {
+        fn id(&self) -> String {
+            "dummy".into()
+        }
+        fn uid(&self) -> String {
+            "uid".into()
+        }
+        fn description(&self) -> String {
+            "Dummy description".into()
+        }
+        fn severity(&self) -> String {
+            "medium".into()
+        }
+        fn tags(&self) -> Vec<String> {
+            vec![]
+        }
+        fn title_single_instance(&self) -> String {
+            "Dummy finding".into()
+        }
+        fn title_multiple_instance(&self) -> String {
+            String::new()
+        }
+        fn opening(&self) -> String {
+            String::new()
+        }
+        fn body_single_file_single_instance(&self) -> String {
+            String::new()
+        }
+        fn body_single_file_multiple_instance(&self) -> String {
+            String::new()
+        }
+        fn body_multiple_file_multiple_instance(&self) -> String {
+            String::new()
+        }
+        fn body_list_item_single_file(&self) -> String {
+            String::new()
+        }
+        fn body_list_item_multiple_file(&self) -> String {
+            String::new()
+        }
+        fn closing(&self) -> String {
+            String::new()
+        }
+        fn template(&self) -> String {
+            String::new()
+        }
+    }
+
+    #[test]
+    fn test_to_lsp_diagnostics() -> anyhow::Result<()> {
+        let source = "circuit foo(x: Field): Field {\n    assert x == 1 \"\";\n    return x;\n}";
+        let mut codebase = Codebase::<OpenState>::new();
+        codebase.add_file("test.compact", source);
+        let sealed = codebase.seal()?;
+        let assert_node = sealed.list_assert_nodes().next().expect("assert not found");
+        let result = DetectorResult {
+            file_path: "test.compact".to_string(),
+            offset_start: assert_node.location.offset_start,
+            offset_end: assert_node.location.offset_end,
+            extra: None,
+        };
+        let diagnostics = sealed.to_lsp_diagnostics(&Dummy, &[result]);
+        assert_eq!(diagnostics.len(), 1);
+        let diagnostic = &diagnostics[0];
+        assert_eq!(
+            diagnostic.range.start,
+            LspPosition {
+                line: 1,
+                character: 4
+            }
+        );
+        assert_eq!(
+            diagnostic.range.end,
+            LspPosition {
+                line: 1,
+                character: 4 + u32::try_from(assert_node.location.source.len())?
+            }
+        );
+        assert_eq!(diagnostic.severity, LspSeverity::Warning);
+        assert_eq!(diagnostic.code, "dummy");
+        assert_eq!(diagnostic.message, "Dummy finding");
+        Ok(())
+    }
+
+    #[test]
+    fn test_lsp_position_counts_utf16() -> anyhow::Result<()> {
+        let source = "const s = \"é\";\nx";
+        let source_map = SourceMap::new(source);
+        assert_eq!(
+            lsp_position(source, &source_map, u32::try_from(source.len() - 1)?),
+            LspPosition {
+                line: 1,
+                character: 0
+            }
+        );
+        assert_eq!(
+            lsp_position(source, &source_map, 14),
+            LspPosition {
+                line: 0,
+                character: 13
+            }
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,22 @@
+//! # Analysis passes
+//!
+//! Custom analysis passes allow precomputing detector-specific indices once per codebase.
+//!
+//! A pass implements the `AnalysisPass` trait and is registered on an open codebase with
+//! `Codebase::register_pass`. Registered passes run at the end of `Codebase::seal`, after imports,
+//! symbol tables and function calls are resolved, and get read access to the sealed codebase.
+//! The value returned by a pass is stored in a side-table keyed by its type and can be retrieved
+//! from the sealed codebase with `Codebase::pass_result::<T>()`.
+use std::any::Any;
+
+use crate::codebase::{Codebase, SealedState};
+
+/// `AnalysisPass` trait
+/// A custom analysis that runs when the codebase is sealed.
+///
+/// # Functions
+/// - `run`: Analyses the sealed codebase and returns the result to be stored in the pass side-table.
+///   Results are keyed by their concrete type, so each pass should return a distinct type.
+pub trait AnalysisPass {
+    fn run(&self, codebase: &Codebase<SealedState>) -> Box<dyn Any>;
+}
@@ -0,0 +1,93 @@
+//! # Source map
+//!
+//! Conversion of byte offsets into line and column numbers.
+//!
+//! `SourceMap` records the byte offset of every line start of a source file, so that a line is
+//! found with a binary search instead of scanning the source from its start. Lines and columns
+//! are 1-based, and columns are counted in characters, so multi-byte UTF-8 characters count once.
+//! Lines are split on `\n`, a `\r` of a CRLF line ending belongs to the line it ends.
+use serde::{Deserialize, Serialize};
+
+/// The line starts of a source file.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct SourceMap {
+    line_starts: Vec<usize>,
+}
+
+impl SourceMap {
+    /// Builds the source map of the given source code.
+    #[must_use]
+    pub fn new(source: &str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
+        Self { line_starts }
+    }
+
+    /// Returns the 1-based line and column of a byte offset of `source`, the source the map was
+    /// built from.
+    ///
+    /// The offset right after the last character is valid. Returns `None` if the offset is past
+    /// the end of the source or not on a character boundary.
+    #[must_use = "Use this method to get the line and column of an offset"]
+    pub fn line_col(&self, source: &str, offset: usize) -> Option<(usize, usize)> {
+        let (line, line_start) = self.line_start(offset)?;
+        let column = source.get(line_start..offset)?.chars().count() + 1;
+        Some((line, column))
+    }
+
+    /// Returns the 1-based line containing a byte offset and the byte offset the line starts at.
+    ///
+    /// Offsets past the end of the source belong to its last line.
+    #[must_use = "Use this method to get the line of an offset and where it starts"]
+    pub fn line_start(&self, offset: usize) -> Option<(usize, usize)> {
+        let line = self.line_starts.partition_point(|start| *start <= offset);
+        let line_start = *self.line_starts.get(line.checked_sub(1)?)?;
+        Some((line, line_start))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_col_lf() {
+        let source = "ab\ncd\n";
+        let map = SourceMap::new(source);
+        assert_eq!(map.line_col(source, 0), Some((1, 1)));
+        assert_eq!(map.line_col(source, 2), Some((1, 3)));
+        assert_eq!(map.line_col(source, 3), Some((2, 1)));
+        assert_eq!(map.line_col(source, 6), Some((3, 1)));
+        assert_eq!(map.line_col(source, 7), None);
+    }
+
+    #[test]
+    fn test_line_col_crlf() {
+        let source = "ab\r\ncd";
+        let map = SourceMap::new(source);
+        assert_eq!(map.line_col(source, 2), Some((1, 3)));
+        assert_eq!(map.line_col(source, 4), Some((2, 1)));
+        assert_eq!(map.line_col(source, 6), Some((2, 3)));
+    }
+
+    #[test]
+    fn test_line_col_multibyte() {
+        let source = "é€\n😀x";
+        let map = SourceMap::new(source);
+        assert_eq!(map.line_col(source, 2), Some((1, 2)));
+        assert_eq!(map.line_col(source, 5), Some((1, 3)));
+        assert_eq!(map.line_col(source, 10), Some((2, 2)));
+        assert_eq!(map.line_col(source, 11), Some((2, 3)));
+        assert_eq!(map.line_col(source, 1), None);
+    }
+
+    #[test]
+    fn test_line_start() {
+        let source = "ab\ncd";
+        let map = SourceMap::new(source);
+        assert_eq!(map.line_start(1), Some((1, 0)));
+        assert_eq!(map.line_start(3), Some((2, 3)));
+        assert_eq!(map.line_start(9), Some((2, 3)));
+    }
+}
@@ -4,17 +4,29 @@
 /// # Fields
 /// - `node_routes`: A vector of `NodeRoute` structures that define the relationships between nodes.
 /// - `nodes`: A vector of `NodeType` structures representing the stored nodes.
+/// - `node_index`: A map from node IDs to their position in `nodes`, built by `seal`.
+/// - `route_index`: A map from node IDs to their position in `node_routes`, built by `seal`.
 ///
 /// # Methods
-/// - `find_node`: Finds a node by its ID and returns an optional cloned `NodeType`.
+/// - `find_node`: Finds a node by its ID and returns an optional cloned `NodeType`. Lookups use
+///   the node index once the storage is sealed.
+/// - `find_node_ref`: Finds a node by its ID and returns a reference to it.
 /// - `find_node_mut`: Finds a mutable reference to a node by its ID.
-/// - `find_parent_node`: Finds the parent node ID of a given node, if it exists.
+/// - `find_parent_node`: Finds the parent node ID of a given node, if it exists. Lookups use the
+///   route index once the storage is sealed.
+/// - `find_child_nodes`: Returns the IDs of the nodes routed below a given node.
 /// - `add_node`: Adds a new node to the storage and establishes its parent-child relationship.
-/// - `seal`: Finalizes the storage by ensuring all parent nodes have their children properly recorded.
+/// - `seal`: Finalizes the storage by ensuring all parent nodes have their children properly recorded,
+///   and builds the node and route indices.
+/// - `unseal`: Drops the children and the indices recorded by `seal`, so the storage can be modified and sealed again.
+/// - `remove_subtree`: Removes a node and all nodes routed below it.
+/// - `truncate`: Drops the nodes added after the storage had the given number of nodes.
 ///
 /// # Usage
 /// This structure is designed to manage hierarchical relationships between nodes, allowing
 /// for efficient querying and modification of nodes and their relationships.
+use std::collections::{HashMap, HashSet};
+
 use crate::ast::node_type::NodeType;
 use serde::{Deserialize, Serialize};
 
@@ -23,12 +35,25 @@ use serde::{Deserialize, Serialize};
 pub struct NodesStorage {
     node_routes: Vec<NodeRoute>,
     pub nodes: Vec<NodeType>,
+    #[serde(skip)]
+    pub(crate) node_index: HashMap<u32, usize>,
+    #[serde(skip)]
+    route_index: HashMap<u32, usize>,
 }
 
 impl NodesStorage {
     /// Returns a cloned `NodeType`
     pub fn find_node(&self, id: u32) -> Option<NodeType> {
-        self.nodes.iter().find(|n| n.id() == id).cloned()
+        self.find_node_ref(id).cloned()
+    }
+
+    /// Returns a reference to a node by its ID.
+    #[must_use = "Use this method to find a Node by its ID"]
+    pub fn find_node_ref(&self, id: u32) -> Option<&NodeType> {
+        if let Some(&index) = self.node_index.get(&id) {
+            return self.nodes.get(index);
+        }
+        self.nodes.iter().find(|n| n.id() == id)
     }
 
     /// Returns a mutable reference to a node by its ID.
@@ -39,11 +64,22 @@ impl NodesStorage {
     /// Finds the parent node ID of a given node, if it exists.
     #[must_use = "Use this method to find a Node's parent Node"]
     pub fn find_parent_node(&self, id: u32) -> Option<u32> {
-        self.node_routes
-            .iter()
-            .find(|n| n.id == id)
-            .cloned()
-            .and_then(|node| node.parent)
+        self.find_route(id).and_then(|route| route.parent)
+    }
+
+    /// Returns the IDs of the nodes routed below the node with the given ID. Once the storage is
+    /// sealed, an ID may be listed more than once.
+    #[must_use = "Use this method to find a Node's child Nodes"]
+    pub fn find_child_nodes(&self, id: u32) -> &[u32] {
+        self.find_route(id)
+            .map_or(&[], |route| route.children.as_slice())
+    }
+
+    fn find_route(&self, id: u32) -> Option<&NodeRoute> {
+        if let Some(&index) = self.route_index.get(&id) {
+            return self.node_routes.get(index);
+        }
+        self.node_routes.iter().find(|n| n.id == id)
     }
 
     /// Adds a new node to the storage and establishes its parent-child relationship.
@@ -78,6 +114,79 @@ impl NodesStorage {
                 }
             }
         }
+        self.node_index.clear();
+        for (index, node) in self.nodes.iter().enumerate() {
+            self.node_index.entry(node.id()).or_insert(index);
+        }
+        self.route_index.clear();
+        for (index, route) in self.node_routes.iter().enumerate() {
+            self.route_index.entry(route.id).or_insert(index);
+        }
+    }
+
+    /// Drops the children and the indices recorded by `seal`, so the storage can be modified and
+    /// sealed again.
+    pub fn unseal(&mut self) {
+        for route in &mut self.node_routes {
+            route.children.clear();
+        }
+        self.node_index.clear();
+        self.route_index.clear();
+    }
+
+    /// Drops the nodes added after the storage had `len` nodes, e.g. the partial AST of a file
+    /// that failed to build.
+    pub fn truncate(&mut self, len: usize) {
+        let removed: HashSet<u32> = self.nodes.iter().skip(len).map(NodeType::id).collect();
+        self.nodes.truncate(len);
+        self.node_index.clear();
+        self.route_index.clear();
+        self.node_routes
+            .retain(|route| !removed.contains(&route.id));
+        for route in &mut self.node_routes {
+            route.children.retain(|child| !removed.contains(child));
+        }
+    }
+
+    /// Removes the node with the given ID and all nodes routed below it.
+    pub fn remove_subtree(&mut self, id: u32) {
+        let mut removed = HashSet::from([id]);
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for route in &self.node_routes {
+                if !removed.contains(&route.id)
+                    && route.parent.is_some_and(|parent| removed.contains(&parent))
+                {
+                    removed.insert(route.id);
+                    changed = true;
+                }
+            }
+        }
+        self.nodes.retain(|node| !removed.contains(&node.id()));
+        self.node_index.clear();
+        self.route_index.clear();
+        self.node_routes
+            .retain(|route| !removed.contains(&route.id));
+        for route in &mut self.node_routes {
+            route.children.retain(|child| !removed.contains(child));
+        }
+    }
+}
+
+/// The routes of a serialized `NodesStorage`, read without deserializing its nodes.
+#[derive(Deserialize)]
+pub(crate) struct SerializedRoutes {
+    node_routes: Vec<NodeRoute>,
+}
+
+impl SerializedRoutes {
+    /// Returns the parent ID of every routed node, keyed by the node ID.
+    pub(crate) fn parents(&self) -> HashMap<u32, u32> {
+        self.node_routes
+            .iter()
+            .filter_map(|route| route.parent.map(|parent| (route.id, parent)))
+            .collect()
     }
 }
 
@@ -174,8 +283,38 @@ mod tests {
             .find(|r| r.id == parent_id)
             .unwrap();
         assert_eq!(route_after.children, vec![11, 12, 11, 12]);
+        // Sealing indexes every node
+        assert_eq!(storage.node_index.len(), 3);
+        assert_eq!(storage.find_node_ref(12).unwrap().id(), 12);
+        assert_eq!(storage.route_index.len(), 3);
+        assert_eq!(storage.find_parent_node(12), Some(parent_id));
+        assert_eq!(storage.find_child_nodes(parent_id), &[11, 12, 11, 12]);
+        storage.unseal();
+        assert!(storage.node_index.is_empty());
+        assert!(storage.route_index.is_empty());
+        assert_eq!(storage.find_node_ref(12).unwrap().id(), 12);
         // find_parent_node for children
         assert_eq!(storage.find_parent_node(11), Some(parent_id));
         assert_eq!(storage.find_parent_node(12), Some(parent_id));
     }
+
+    #[test]
+    fn test_remove_subtree() {
+        let mut storage = NodesStorage::default();
+        for (id, parent) in [(1, 0), (2, 1), (3, 2), (4, 0)] {
+            let nat = Rc::new(Nat {
+                id,
+                location: Location::default(),
+                value: u64::from(id),
+            });
+            storage.add_node(NodeType::Literal(Literal::Nat(nat)), parent);
+        }
+        storage.seal();
+        storage.remove_subtree(1);
+        assert!(storage.find_node(1).is_none());
+        assert!(storage.find_node(2).is_none());
+        assert!(storage.find_node(3).is_none());
+        assert!(storage.find_node(4).is_some());
+        assert_eq!(storage.find_parent_node(4), Some(0));
+    }
 }
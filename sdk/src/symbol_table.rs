@@ -353,22 +353,16 @@ fn infer_expr(expr: &Expression, env: &Rc<SymbolTable>) -> Option<Type> {
                 | BinaryExpressionOperator::Ge
                 | BinaryExpressionOperator::And
                 | BinaryExpressionOperator::Or => {
-                    if left.matches(&right) {
-                        Some(left)
-                    } else {
-                        panic!("Error: type mismatch in the binary expression")
-                    }
+                    // The type is only known when both operands agree on it.
+                    left.matches(&right).then_some(left)
                 }
             }
         }
         Expression::Conditional(conditional) => {
+            // The type is only known when both branches agree on it.
             let then_type = infer_expr(&conditional.then_branch, env)?;
             let else_type = infer_expr(&conditional.else_branch, env)?;
-            if then_type.matches(&else_type) {
-                Some(then_type)
-            } else {
-                panic!("Error: type mismatch in the conditional expression")
-            }
+            then_type.matches(&else_type).then_some(then_type)
         }
         Expression::Cast(cast) => Some(cast.target_type.clone()),
         Expression::IndexAccess(index_access) => infer_expr(&index_access.base, env),
@@ -796,6 +790,31 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_conditional_expr_mismatched_branches() {
+        let env = Rc::new(SymbolTable::new(None));
+        let cond_expr = Expression::Conditional(Rc::new(Conditional {
+            id: 8,
+            location: default_location(),
+            condition: Expression::Literal(Literal::Bool(Rc::new(Bool {
+                id: 5,
+                location: default_location(),
+                value: true,
+            }))),
+            then_branch: Expression::Literal(Literal::Nat(Rc::new(Nat {
+                id: 6,
+                location: default_location(),
+                value: 0,
+            }))),
+            else_branch: Expression::Literal(Literal::Bool(Rc::new(Bool {
+                id: 7,
+                location: default_location(),
+                value: false,
+            }))),
+        }));
+        assert!(infer_expr(&cond_expr, &env).is_none());
+    }
+
     #[test]
     fn test_binary_expr_add() -> Result<()> {
         let env = Rc::new(SymbolTable::new(None));
@@ -822,6 +841,34 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_binary_expr_overlapping_uint_ranges() {
+        let env = Rc::new(SymbolTable::new(None));
+        let nat = |id, value| {
+            Rc::new(Nat {
+                id,
+                location: default_location(),
+                value,
+            })
+        };
+        let uint = |id, start, end| {
+            Expression::TypeExpression(Type::Uint(Rc::new(crate::ast::ty::Uint {
+                id,
+                location: default_location(),
+                start: nat(id + 1, start),
+                end: Some(nat(id + 2, end)),
+            })))
+        };
+        let binary = crate::ast::expression::Binary {
+            id: 20,
+            location: default_location(),
+            left: uint(21, 0, 100),
+            right: uint(24, 50, 300),
+            operator: BinaryExpressionOperator::Add,
+        };
+        assert!(infer_expr(&Expression::Binary(Rc::new(binary)), &env).is_none());
+    }
+
     #[test]
     fn test_cast_expr() -> Result<()> {
         let env = Rc::new(SymbolTable::new(None));
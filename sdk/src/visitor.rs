@@ -0,0 +1,82 @@
+//! # AST visitor
+//!
+//! A visitor walks the ASTs of a sealed codebase in a single pass and accumulates state, instead
+//! of querying the codebase once per node kind.
+//!
+//! A visitor implements the `Visitor` trait, overriding the hooks of the node kinds it is
+//! interested in, and is run with `Codebase::walk`. Every hook has an empty default
+//! implementation. For each node, `visit_node` is called first, then the hook of its kind, and
+//! `leave_node` once all of its descendants have been visited, e.g. to pop the state pushed for
+//! an enclosing circuit.
+use std::rc::Rc;
+
+use crate::ast::{
+    declaration::{Declaration, Pattern},
+    definition::Definition,
+    directive::Directive,
+    expression::Expression,
+    function::Function,
+    literal::Literal,
+    node_type::NodeType,
+    program::Program,
+    statement::Statement,
+    ty::Type,
+};
+
+/// `Visitor` trait
+/// Hooks called by `Codebase::walk` for the nodes of the codebase, in source order.
+///
+/// # Functions
+/// - `visit_node`: Called for every node, before the hook of its kind.
+/// - `leave_node`: Called for every node, after all of its descendants have been visited.
+/// - `visit_program`: Called for the program node of every file.
+/// - `visit_directive`, `visit_declaration`, `visit_definition`, `visit_statement`,
+///   `visit_expression`, `visit_function`, `visit_literal`, `visit_type`, `visit_pattern`: Called
+///   for the nodes of the corresponding kind.
+pub trait Visitor {
+    fn visit_node(&mut self, _node: &NodeType) {}
+
+    fn leave_node(&mut self, _node: &NodeType) {}
+
+    fn visit_program(&mut self, _program: &Rc<Program>) {}
+
+    fn visit_directive(&mut self, _directive: &Directive) {}
+
+    fn visit_declaration(&mut self, _declaration: &Declaration) {}
+
+    fn visit_definition(&mut self, _definition: &Definition) {}
+
+    fn visit_statement(&mut self, _statement: &Statement) {}
+
+    fn visit_expression(&mut self, _expression: &Expression) {}
+
+    fn visit_function(&mut self, _function: &Function) {}
+
+    fn visit_literal(&mut self, _literal: &Literal) {}
+
+    fn visit_type(&mut self, _ty: &Type) {}
+
+    fn visit_pattern(&mut self, _pattern: &Pattern) {}
+}
+
+/// Calls the hooks of `visitor` for a single node.
+pub(crate) fn visit<V: Visitor + ?Sized>(visitor: &mut V, node: &NodeType) {
+    visitor.visit_node(node);
+    match node {
+        NodeType::Program(program) => visitor.visit_program(program),
+        NodeType::Directive(directive) => visitor.visit_directive(directive),
+        NodeType::Declaration(declaration) => visitor.visit_declaration(declaration),
+        NodeType::Definition(definition) => visitor.visit_definition(definition),
+        NodeType::Statement(statement) => visitor.visit_statement(statement),
+        NodeType::Expression(expression) => visitor.visit_expression(expression),
+        NodeType::Function(function) => visitor.visit_function(function),
+        NodeType::Literal(literal) => visitor.visit_literal(literal),
+        NodeType::Type(ty) => visitor.visit_type(ty),
+        NodeType::Pattern(pattern) => visitor.visit_pattern(pattern),
+        NodeType::FunctionArgument(_)
+        | NodeType::VectorSize(_)
+        | NodeType::GArgument(_)
+        | NodeType::StructExprArg(_)
+        | NodeType::StructArgument(_) => {}
+    }
+}